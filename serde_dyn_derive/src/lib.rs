@@ -0,0 +1,62 @@
+//! Procedural macros for [`serde_dyn`](https://docs.rs/serde_dyn).
+//!
+//! Use them through the `derive` feature of `serde_dyn` rather than depending on this crate.
+
+#![deny(missing_docs)]
+#![forbid(unsafe_code)]
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{ItemTrait, parse_macro_input};
+
+/// Implements `serde::Serialize` for the trait objects of a trait extending `serde_dyn::Serialize`.
+///
+/// The implementations cover `dyn Trait`, `dyn Trait + Send`, `dyn Trait + Sync` and
+/// `dyn Trait + Send + Sync`, and forward to `serde_dyn::Serialize::dyn_serialize` through
+/// `serde_dyn::ser::with_dyn`. Smart pointers such as `Box<dyn Trait>` are then covered by the
+/// implementations of `serde`.
+#[proc_macro_attribute]
+pub fn serializable(args: TokenStream, input: TokenStream) -> TokenStream {
+    if !args.is_empty() {
+        let args = proc_macro2::TokenStream::from(args);
+        return syn::Error::new_spanned(args, "`serializable` takes no arguments")
+            .into_compile_error()
+            .into();
+    }
+
+    let item = parse_macro_input!(input as ItemTrait);
+    let ident = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    let impls = [
+        quote!(),
+        quote!(+ ::core::marker::Send),
+        quote!(+ ::core::marker::Sync),
+        quote!(+ ::core::marker::Send + ::core::marker::Sync),
+    ]
+    .into_iter()
+    .map(|bounds| {
+        quote! {
+            impl #impl_generics ::serde_dyn::__private::serde::Serialize
+                for dyn #ident #ty_generics #bounds + '_
+            #where_clause
+            {
+                fn serialize<S>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error>
+                where
+                    S: ::serde_dyn::__private::serde::Serializer,
+                {
+                    let value: &dyn ::serde_dyn::Serialize = self;
+                    ::serde_dyn::ser::with_dyn(serializer, |serializer| {
+                        value.dyn_serialize(serializer)
+                    })
+                }
+            }
+        }
+    });
+
+    quote! {
+        #item
+        #(#impls)*
+    }
+    .into()
+}
@@ -6,16 +6,38 @@
 use core::{error, fmt, mem};
 
 #[cfg(any(feature = "std", feature = "alloc"))]
-use core::num::NonZeroUsize;
-#[cfg(any(feature = "std", feature = "alloc"))]
-use core::ptr::NonNull;
+use core::any::Any;
+
+use serde::de::{Error as _, Expected, Unexpected};
 
 #[cfg(all(not(feature = "std"), feature = "alloc"))]
 use alloc::boxed::Box;
 #[cfg(all(not(feature = "std"), feature = "alloc"))]
 use alloc::string::{String, ToString};
 #[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::sync::Arc;
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
 use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+#[cfg(all(
+    not(any(feature = "std", feature = "alloc")),
+    feature = "heapless-errors"
+))]
+use crate::message::InlineMessage;
+#[cfg(any(feature = "std", feature = "alloc"))]
+use crate::path::Path;
+
+mod closure;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod tokens;
+
+pub use self::closure::FnSeed;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub use self::closure::FnVisitor;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub use self::tokens::Tokens;
 
 /// A data format that can dynamically deserialize the data structure supported by [`serde`].
 ///
@@ -280,6 +302,39 @@ impl<'de> dyn Deserializer<'de> {
     }
 }
 
+/// Runs `f` with the dynamic version of `deserializer`, then recovers the concrete error.
+///
+/// If the closure fails and the [`InplaceDeserializer`] holds the original `D::Error`, that error
+/// is returned as is, so that format-specific details such as the line and the column are kept.
+/// Otherwise the [`DeserializeError`] returned by the closure is converted into `D::Error`.
+///
+/// # Examples
+///
+/// ```
+/// # use serde_dyn::Deserializer;
+/// let mut deserializer = serde_json::Deserializer::from_str("\n\"Hello\"");
+/// let error = serde_dyn::de::with_dyn(&mut deserializer, |deserializer: &mut dyn Deserializer| {
+///     <u16 as serde::Deserialize>::deserialize(deserializer)
+/// })
+/// .unwrap_err();
+/// assert_eq!(error.line(), 2);
+/// assert_eq!(error.column(), 7);
+/// ```
+pub fn with_dyn<'de, D, T, F>(deserializer: D, f: F) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    F: FnOnce(&mut dyn Deserializer<'de>) -> DeserializeResult<T>,
+{
+    let mut deserializer = InplaceDeserializer::Deserializer(deserializer);
+    match f(&mut deserializer) {
+        Ok(value) => Ok(value),
+        Err(error) => match deserializer {
+            InplaceDeserializer::Error(error) => Err(error),
+            _ => Err(error.into_de_error()),
+        },
+    }
+}
+
 impl<'de> serde::Deserializer<'de> for &mut (dyn Deserializer<'de> + '_) {
     type Error = DeserializeError;
 
@@ -708,18 +763,182 @@ impl<'de> serde::de::DeserializeSeed<'de> for &mut (dyn DeserializeSeed<'de> + '
     where
         D: serde::Deserializer<'de>,
     {
-        let mut deserializer = InplaceDeserializer::Deserializer(deserializer);
-        let result = self.dyn_deserialize(&mut deserializer);
-        match deserializer {
-            InplaceDeserializer::Error(error) => Err(error),
-            _ => result.map_err(DeserializeError::into_de_error),
-        }
+        with_dyn(deserializer, |deserializer| {
+            self.dyn_deserialize(deserializer)
+        })
+    }
+}
+
+/// A dyn-compatible output location for a deserialized value.
+///
+/// Unlike [`serde::Deserialize`] which returns the value, `Deserialize` writes the value into
+/// itself, so that `&mut dyn Deserialize` can be passed around as a typed slot. The slots are:
+///
+/// - `Option<T>`: stores the value, replacing any previous one.
+/// - `Vec<T>`: appends the value, if `std` or `alloc` is enabled.
+///
+/// Also see [`serde::Deserialize`].
+///
+/// # Examples
+///
+/// ```
+/// # use serde_dyn::Deserializer;
+/// # use serde_dyn::de::Deserialize;
+/// let mut name: Option<String> = None;
+/// let mut port: Option<u16> = None;
+/// let slots: [&mut dyn Deserialize; 2] = [&mut name, &mut port];
+///
+/// for (slot, json) in slots.into_iter().zip(["\"localhost\"", "8080"]) {
+///     let mut deserializer = serde_json::Deserializer::from_str(json);
+///     let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+///     slot.dyn_deserialize(&mut deserializer).unwrap();
+/// }
+/// assert_eq!(name.as_deref(), Some("localhost"));
+/// assert_eq!(port, Some(8080));
+/// ```
+pub trait Deserialize<'de> {
+    /// Deserializes the value from the dynamic deserializer and stores it in this slot.
+    ///
+    /// The slot is left unchanged on failure.
+    fn dyn_deserialize(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()>;
+}
+
+impl<'de, T: serde::Deserialize<'de>> Deserialize<'de> for Option<T> {
+    fn dyn_deserialize(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        *self = Some(T::deserialize(deserializer)?);
+        Ok(())
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'de, T: serde::Deserialize<'de>> Deserialize<'de> for Vec<T> {
+    fn dyn_deserialize(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        self.push(T::deserialize(deserializer)?);
+        Ok(())
+    }
+}
+
+impl<'de> serde::de::DeserializeSeed<'de> for &mut (dyn Deserialize<'de> + '_) {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        with_dyn(deserializer, |deserializer| {
+            self.dyn_deserialize(deserializer)
+        })
+    }
+}
+
+/// A dyn-compatible place that is deserialized in place, reusing its allocations.
+///
+/// Every `T: serde::Deserialize` implements this trait by forwarding to
+/// [`serde::Deserialize::deserialize_in_place`], which reuses the buffers of `Vec`, `String` and
+/// so on. Since `&mut dyn Deserializer` forwards the visitors untouched, calling
+/// `T::deserialize_in_place` with it directly reuses the buffers as well.
+///
+/// On failure, the place is left in a valid but unspecified state.
+///
+/// # Examples
+///
+/// ```
+/// # use serde_dyn::Deserializer;
+/// # use serde_dyn::de::DeserializeInPlace;
+/// let mut names = vec![String::with_capacity(16)];
+/// let buffer = names[0].as_ptr();
+///
+/// let place: &mut dyn DeserializeInPlace = &mut names;
+/// let mut deserializer = serde_json::Deserializer::from_str("[\"Hello\"]");
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// place.dyn_deserialize_in_place(&mut deserializer).unwrap();
+///
+/// assert_eq!(names, ["Hello"]);
+/// assert_eq!(names[0].as_ptr(), buffer);
+/// ```
+pub trait DeserializeInPlace<'de> {
+    /// Deserializes the value from the dynamic deserializer into this place.
+    ///
+    /// Also see [`serde::Deserialize::deserialize_in_place`].
+    fn dyn_deserialize_in_place(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()>;
+}
+
+impl<'de, T: serde::Deserialize<'de>> DeserializeInPlace<'de> for T {
+    fn dyn_deserialize_in_place(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        T::deserialize_in_place(deserializer, self)
+    }
+}
+
+impl<'de> serde::de::DeserializeSeed<'de> for &mut (dyn DeserializeInPlace<'de> + '_) {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        with_dyn(deserializer, |deserializer| {
+            self.dyn_deserialize_in_place(deserializer)
+        })
     }
 }
 
 /// The dyn-compatible version of the [`serde::de::Visitor`] trait.
 ///
 /// This trait represents a visitor that walks through a deserializer.
+///
+/// # Implementation
+///
+/// Like [`serde::de::Visitor`], only [`Visitor::dyn_expecting`] is required. The other methods
+/// forward to each other by default, for example, small integers to `dyn_visit_i64` or
+/// `dyn_visit_u64` and owned or borrowed strings to `dyn_visit_str`, and finally return an
+/// "invalid type" error built from `dyn_expecting`.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fmt;
+/// # use serde_dyn::Deserializer;
+/// # use serde::de::Error;
+/// # use serde_dyn::de::{DeserializeError, DeserializeResult, Visitor};
+/// struct Port(Option<u16>);
+///
+/// impl<'de> Visitor<'de> for Port {
+///     fn dyn_expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         formatter.write_str("a port number")
+///     }
+///
+///     fn dyn_visit_u64(&mut self, v: u64) -> DeserializeResult<()> {
+///         self.0 = Some(u16::try_from(v).map_err(DeserializeError::custom)?);
+///         Ok(())
+///     }
+/// }
+///
+/// let mut port = Port(None);
+/// let mut json = serde_json::Deserializer::from_str("80");
+/// let mut deserializer = <dyn Deserializer>::new(&mut json);
+/// deserializer.dyn_deserialize_any(&mut port).unwrap();
+/// assert_eq!(port.0, Some(80));
+///
+/// // Fails with "invalid type: string \"80\", expected a port number".
+/// let mut json = serde_json::Deserializer::from_str("\"80\"");
+/// let mut deserializer = <dyn Deserializer>::new(&mut json);
+/// assert!(deserializer.dyn_deserialize_any(&mut port).is_err());
+/// ```
 pub trait Visitor<'de> {
     /// Format a message stating what data this `Visitor` expects to receive.
     ///
@@ -728,153 +947,320 @@ pub trait Visitor<'de> {
 
     /// The input contains a boolean.
     ///
+    /// Returns an invalid type error by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_bool`].
-    fn dyn_visit_bool(&mut self, v: bool) -> DeserializeResult<()>;
+    fn dyn_visit_bool(&mut self, v: bool) -> DeserializeResult<()> {
+        Err(invalid_type(self, Unexpected::Bool(v)))
+    }
 
     /// The input contains an `i8`.
     ///
+    /// Forwards to [`dyn_visit_i64`](Self::dyn_visit_i64) by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_i8`].
-    fn dyn_visit_i8(&mut self, v: i8) -> DeserializeResult<()>;
+    fn dyn_visit_i8(&mut self, v: i8) -> DeserializeResult<()> {
+        self.dyn_visit_i64(v.into())
+    }
 
     /// The input contains an `i16`.
     ///
+    /// Forwards to [`dyn_visit_i64`](Self::dyn_visit_i64) by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_i16`].
-    fn dyn_visit_i16(&mut self, v: i16) -> DeserializeResult<()>;
+    fn dyn_visit_i16(&mut self, v: i16) -> DeserializeResult<()> {
+        self.dyn_visit_i64(v.into())
+    }
 
     /// The input contains an `i32`.
     ///
+    /// Forwards to [`dyn_visit_i64`](Self::dyn_visit_i64) by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_i32`].
-    fn dyn_visit_i32(&mut self, v: i32) -> DeserializeResult<()>;
+    fn dyn_visit_i32(&mut self, v: i32) -> DeserializeResult<()> {
+        self.dyn_visit_i64(v.into())
+    }
 
     /// The input contains an `i64`.
     ///
+    /// Returns an invalid type error by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_i64`].
-    fn dyn_visit_i64(&mut self, v: i64) -> DeserializeResult<()>;
+    fn dyn_visit_i64(&mut self, v: i64) -> DeserializeResult<()> {
+        Err(invalid_type(self, Unexpected::Signed(v)))
+    }
 
     /// The input contains an `i128`.
     ///
+    /// Returns an invalid type error by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_i128`].
-    fn dyn_visit_i128(&mut self, v: i128) -> DeserializeResult<()>;
+    fn dyn_visit_i128(&mut self, v: i128) -> DeserializeResult<()> {
+        Err(invalid_integer(self, v, "i128"))
+    }
 
     /// The input contains an `u8`.
     ///
+    /// Forwards to [`dyn_visit_u64`](Self::dyn_visit_u64) by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_u8`].
-    fn dyn_visit_u8(&mut self, v: u8) -> DeserializeResult<()>;
+    fn dyn_visit_u8(&mut self, v: u8) -> DeserializeResult<()> {
+        self.dyn_visit_u64(v.into())
+    }
 
     /// The input contains an `u16`.
     ///
+    /// Forwards to [`dyn_visit_u64`](Self::dyn_visit_u64) by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_u16`].
-    fn dyn_visit_u16(&mut self, v: u16) -> DeserializeResult<()>;
+    fn dyn_visit_u16(&mut self, v: u16) -> DeserializeResult<()> {
+        self.dyn_visit_u64(v.into())
+    }
 
     /// The input contains an `u32`.
     ///
+    /// Forwards to [`dyn_visit_u64`](Self::dyn_visit_u64) by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_u32`].
-    fn dyn_visit_u32(&mut self, v: u32) -> DeserializeResult<()>;
+    fn dyn_visit_u32(&mut self, v: u32) -> DeserializeResult<()> {
+        self.dyn_visit_u64(v.into())
+    }
 
     /// The input contains an `u64`.
     ///
+    /// Returns an invalid type error by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_u64`].
-    fn dyn_visit_u64(&mut self, v: u64) -> DeserializeResult<()>;
+    fn dyn_visit_u64(&mut self, v: u64) -> DeserializeResult<()> {
+        Err(invalid_type(self, Unexpected::Unsigned(v)))
+    }
 
     /// The input contains an `u128`.
     ///
+    /// Returns an invalid type error by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_u128`].
-    fn dyn_visit_u128(&mut self, v: u128) -> DeserializeResult<()>;
+    fn dyn_visit_u128(&mut self, v: u128) -> DeserializeResult<()> {
+        Err(invalid_integer(self, v, "u128"))
+    }
 
     /// The input contains an `f32`.
     ///
+    /// Forwards to [`dyn_visit_f64`](Self::dyn_visit_f64) by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_f32`].
-    fn dyn_visit_f32(&mut self, v: f32) -> DeserializeResult<()>;
+    fn dyn_visit_f32(&mut self, v: f32) -> DeserializeResult<()> {
+        self.dyn_visit_f64(v.into())
+    }
 
     /// The input contains an `f64`.
     ///
+    /// Returns an invalid type error by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_f64`].
-    fn dyn_visit_f64(&mut self, v: f64) -> DeserializeResult<()>;
+    fn dyn_visit_f64(&mut self, v: f64) -> DeserializeResult<()> {
+        Err(invalid_type(self, Unexpected::Float(v)))
+    }
 
     /// The input contains a `char`.
     ///
+    /// Forwards to [`dyn_visit_str`](Self::dyn_visit_str) by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_char`].
-    fn dyn_visit_char(&mut self, v: char) -> DeserializeResult<()>;
+    fn dyn_visit_char(&mut self, v: char) -> DeserializeResult<()> {
+        self.dyn_visit_str(v.encode_utf8(&mut [0; 4]))
+    }
 
     /// The input contains a string. The lifetime of the string is ephemeral and
     /// it may be destroyed after this method returns.
     ///
+    /// Returns an invalid type error by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_str`].
-    fn dyn_visit_str(&mut self, v: &str) -> DeserializeResult<()>;
+    fn dyn_visit_str(&mut self, v: &str) -> DeserializeResult<()> {
+        Err(invalid_type(self, Unexpected::Str(v)))
+    }
 
     /// The input contains a string that lives at least as long as the
     /// `Deserializer`.
     ///
+    /// Forwards to [`dyn_visit_str`](Self::dyn_visit_str) by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_borrowed_str`].
-    fn dyn_visit_borrowed_str(&mut self, v: &'de str) -> DeserializeResult<()>;
+    fn dyn_visit_borrowed_str(&mut self, v: &'de str) -> DeserializeResult<()> {
+        self.dyn_visit_str(v)
+    }
 
     /// The input contains a string and ownership of the string is being given
     /// to the `Visitor`.
     ///
+    /// Forwards to [`dyn_visit_str`](Self::dyn_visit_str) by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_string`].
     #[cfg(any(feature = "std", feature = "alloc"))]
-    fn dyn_visit_string(&mut self, v: String) -> DeserializeResult<()>;
+    fn dyn_visit_string(&mut self, v: String) -> DeserializeResult<()> {
+        self.dyn_visit_str(&v)
+    }
 
     /// The input contains a byte array. The lifetime of the byte array is
     /// ephemeral and it may be destroyed after this method returns.
     ///
+    /// Returns an invalid type error by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_bytes`].
-    fn dyn_visit_bytes(&mut self, v: &[u8]) -> DeserializeResult<()>;
+    fn dyn_visit_bytes(&mut self, v: &[u8]) -> DeserializeResult<()> {
+        Err(invalid_type(self, Unexpected::Bytes(v)))
+    }
 
     /// The input contains a byte array that lives at least as long as the
     /// `Deserializer`.
     ///
+    /// Forwards to [`dyn_visit_bytes`](Self::dyn_visit_bytes) by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_borrowed_bytes`].
-    fn dyn_visit_borrowed_bytes(&mut self, v: &'de [u8]) -> DeserializeResult<()>;
+    fn dyn_visit_borrowed_bytes(&mut self, v: &'de [u8]) -> DeserializeResult<()> {
+        self.dyn_visit_bytes(v)
+    }
 
     /// The input contains a byte array and ownership of the byte array is being
     /// given to the `Visitor`.
     ///
+    /// Forwards to [`dyn_visit_bytes`](Self::dyn_visit_bytes) by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_byte_buf`].
     #[cfg(any(feature = "std", feature = "alloc"))]
-    fn dyn_visit_byte_buf(&mut self, v: Vec<u8>) -> DeserializeResult<()>;
+    fn dyn_visit_byte_buf(&mut self, v: Vec<u8>) -> DeserializeResult<()> {
+        self.dyn_visit_bytes(&v)
+    }
 
     /// The input contains an optional that is absent.
     ///
+    /// Returns an invalid type error by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_none`].
-    fn dyn_visit_none(&mut self) -> DeserializeResult<()>;
+    fn dyn_visit_none(&mut self) -> DeserializeResult<()> {
+        Err(invalid_type(self, Unexpected::Option))
+    }
 
     /// The input contains an optional that is present.
     ///
+    /// Returns an invalid type error by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_some`].
-    fn dyn_visit_some(&mut self, deserializer: &mut dyn Deserializer<'de>)
-    -> DeserializeResult<()>;
+    fn dyn_visit_some(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        let _ = deserializer;
+        Err(invalid_type(self, Unexpected::Option))
+    }
 
     /// The input contains a unit `()`.
     ///
+    /// Returns an invalid type error by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_unit`].
-    fn dyn_visit_unit(&mut self) -> DeserializeResult<()>;
+    fn dyn_visit_unit(&mut self) -> DeserializeResult<()> {
+        Err(invalid_type(self, Unexpected::Unit))
+    }
 
     /// The input contains a newtype struct.
     ///
     /// The content of the newtype struct may be read from the given
     /// `Deserializer`.
     ///
+    /// Returns an invalid type error by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_newtype_struct`].
     fn dyn_visit_newtype_struct(
         &mut self,
         deserializer: &mut dyn Deserializer<'de>,
-    ) -> DeserializeResult<()>;
+    ) -> DeserializeResult<()> {
+        let _ = deserializer;
+        Err(invalid_type(self, Unexpected::NewtypeStruct))
+    }
 
     /// The input contains a sequence of elements.
     ///
+    /// Returns an invalid type error by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_seq`].
-    fn dyn_visit_seq(&mut self, seq: &mut dyn SeqAccess<'de>) -> DeserializeResult<()>;
+    fn dyn_visit_seq(&mut self, seq: &mut dyn SeqAccess<'de>) -> DeserializeResult<()> {
+        let _ = seq;
+        Err(invalid_type(self, Unexpected::Seq))
+    }
 
     /// The input contains a key-value map.
     ///
+    /// Returns an invalid type error by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_map`].
-    fn dyn_visit_map(&mut self, map: &mut dyn MapAccess<'de>) -> DeserializeResult<()>;
+    fn dyn_visit_map(&mut self, map: &mut dyn MapAccess<'de>) -> DeserializeResult<()> {
+        let _ = map;
+        Err(invalid_type(self, Unexpected::Map))
+    }
 
     /// The input contains an enum.
     ///
+    /// Returns an invalid type error by default.
+    ///
     /// Also see [`serde::de::Visitor::visit_enum`].
-    fn dyn_visit_enum(&mut self, data: &mut dyn EnumAccess<'de>) -> DeserializeResult<()>;
+    fn dyn_visit_enum(&mut self, data: &mut dyn EnumAccess<'de>) -> DeserializeResult<()> {
+        let _ = data;
+        Err(invalid_type(self, Unexpected::Enum))
+    }
+}
+
+/// Formats [`Visitor::dyn_expecting`] as [`Expected`].
+struct Expecting<'a, V: ?Sized>(&'a V);
+
+impl<'de, V: ?Sized + Visitor<'de>> Expected for Expecting<'_, V> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.dyn_expecting(formatter)
+    }
+}
+
+/// The default error of the [`Visitor`] methods.
+#[cold]
+fn invalid_type<'de, V: ?Sized + Visitor<'de>>(
+    visitor: &V,
+    unexp: Unexpected<'_>,
+) -> DeserializeError {
+    DeserializeError::invalid_type(unexp, &Expecting(visitor))
+}
+
+/// The default error of [`Visitor::dyn_visit_i128`] and [`Visitor::dyn_visit_u128`], worded as
+/// in [`serde`].
+#[cold]
+fn invalid_integer<'de, V: ?Sized + Visitor<'de>>(
+    visitor: &V,
+    v: impl fmt::Display,
+    ty: &str,
+) -> DeserializeError {
+    let mut buf = IntegerBuf {
+        bytes: [0; 58],
+        len: 0,
+    };
+    // The longest message, for `i128::MIN`, fits in the buffer.
+    let _ = fmt::write(&mut buf, format_args!("integer `{v}` as {ty}"));
+    let message = core::str::from_utf8(&buf.bytes[..buf.len]).unwrap_or_default();
+    invalid_type(visitor, Unexpected::Other(message))
+}
+
+/// The buffer of [`invalid_integer`].
+struct IntegerBuf {
+    bytes: [u8; 58],
+    len: usize,
+}
+
+impl fmt::Write for IntegerBuf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        let bytes = self.bytes.get_mut(self.len..end).ok_or(fmt::Error)?;
+        bytes.copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
 }
 
 impl<'de> serde::de::Visitor<'de> for &mut (dyn Visitor<'de> + '_) {
@@ -1059,13 +1445,9 @@ impl<'de> serde::de::Visitor<'de> for &mut (dyn Visitor<'de> + '_) {
     where
         D: serde::Deserializer<'de>,
     {
-        let mut deserializer = InplaceDeserializer::Deserializer(deserializer);
-        let result = self.dyn_visit_some(&mut deserializer);
-        if let InplaceDeserializer::Error(error) = deserializer {
-            Err(error)
-        } else {
-            result.map_err(DeserializeError::into_de_error)
-        }
+        with_dyn(deserializer, |deserializer| {
+            self.dyn_visit_some(deserializer)
+        })
     }
 
     fn visit_unit<E>(self) -> Result<(), E>
@@ -1080,13 +1462,9 @@ impl<'de> serde::de::Visitor<'de> for &mut (dyn Visitor<'de> + '_) {
     where
         D: serde::Deserializer<'de>,
     {
-        let mut deserializer = InplaceDeserializer::Deserializer(deserializer);
-        let result = self.dyn_visit_newtype_struct(&mut deserializer);
-        if let InplaceDeserializer::Error(error) = deserializer {
-            Err(error)
-        } else {
-            result.map_err(DeserializeError::into_de_error)
-        }
+        with_dyn(deserializer, |deserializer| {
+            self.dyn_visit_newtype_struct(deserializer)
+        })
     }
 
     fn visit_seq<A>(self, seq: A) -> Result<(), A::Error>
@@ -1408,39 +1786,74 @@ pub enum DeserializerError {
     /// An error occurred during the deserialization.
     #[default]
     Error,
-    /// The deserializer is not ready.
+    /// The operation is called in the wrong state.
+    WrongState {
+        /// The attempted operation, such as `"Visitor::dyn_visit_str"`.
+        operation: &'static str,
+        /// The state that the callee is found in.
+        found: DeserializerState,
+    },
+}
+
+impl fmt::Display for DeserializerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeserializerError::Error => f.write_str("an error occurred during the deserialization"),
+            DeserializerError::WrongState { operation, found } => {
+                write!(f, "`{operation}` is called, but it {found}")
+            }
+        }
+    }
+}
+
+impl error::Error for DeserializerError {}
+
+/// The state of an [`InplaceDeserializer`], [`InplaceDeserializeSeed`], [`InplaceVisitor`],
+/// [`InplaceSeqAccess`], [`InplaceMapAccess`] or [`InplaceEnumAccess`], reported by
+/// [`DeserializerError::WrongState`].
+///
+/// It is displayed as a predicate, for example "has already produced a value".
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DeserializerState {
+    /// It is not ready.
+    None,
+    /// The deserialization has done successfully.
+    Value,
+    /// The deserialization has done unsuccessfully.
+    Error,
+    /// The deserializer is ready.
     Deserializer,
-    /// The deserialize-seed is not ready.
+    /// The deserialize-seed is ready.
     DeserializeSeed,
-    /// The visitor is not ready.
+    /// The visitor is ready.
     Visitor,
-    /// The sequence access is not ready.
+    /// The sequence access is ready.
     SeqAccess,
-    /// The map access is not ready.
+    /// The map access is ready.
     MapAccess,
-    /// The enum access is not ready.
+    /// The enum access is ready.
     EnumAccess,
-    /// The enum variant access is not ready.
+    /// The enum variant access is ready.
     VariantAccess,
 }
 
-impl fmt::Display for DeserializerError {
+impl fmt::Display for DeserializerState {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(match self {
-            DeserializerError::Error => "an error occurred during the deserialization",
-            DeserializerError::Deserializer => "the deserializer is not ready",
-            DeserializerError::DeserializeSeed => "the deserialize-seed is not ready",
-            DeserializerError::Visitor => "the visitor is not ready",
-            DeserializerError::SeqAccess => "the sequence access is not ready",
-            DeserializerError::MapAccess => "the map access is not ready",
-            DeserializerError::EnumAccess => "the enum access is not ready",
-            DeserializerError::VariantAccess => "the enum variant access is not ready",
+            DeserializerState::None => "is not ready",
+            DeserializerState::Value => "has already produced a value",
+            DeserializerState::Error => "has already failed",
+            DeserializerState::Deserializer => "is a ready deserializer",
+            DeserializerState::DeserializeSeed => "is a ready deserialize-seed",
+            DeserializerState::Visitor => "is a ready visitor",
+            DeserializerState::SeqAccess => "is a ready sequence access",
+            DeserializerState::MapAccess => "is a ready map access",
+            DeserializerState::EnumAccess => "has not deserialized the variant yet",
+            DeserializerState::VariantAccess => "has already deserialized the variant",
         })
     }
 }
 
-impl error::Error for DeserializerError {}
-
 /// [`Result`] type alias with error type [`DeserializeError`].
 pub type DeserializeResult<T> = Result<T, DeserializeError>;
 
@@ -1448,21 +1861,121 @@ pub type DeserializeResult<T> = Result<T, DeserializeError>;
 ///
 /// This error tells why the dynamic deserialization failed and is returned by
 /// [`&mut dyn Deserializer`](crate::de::Deserializer) as [`serde::Deserializer`].
+///
+/// Besides the message, the error remembers which constructor of [`serde::de::Error`] created it,
+/// see [`DeserializeError::kind`]. When the error leaves the dynamic world, the same constructor
+/// of the concrete error type is called, so the category survives the round trip.
+///
+/// With `std` or `alloc`, the error is as large as a pointer. It is always [`Send`], [`Sync`] and
+/// [`Clone`], so that it can cross threads and async tasks.
+///
+/// Without `std` or `alloc`, only the state is kept. The `heapless-errors` feature keeps the first
+/// 64 bytes of the message instead, in an inline buffer that the error carries by value.
 #[repr(transparent)]
-#[cfg_attr(not(any(feature = "std", feature = "alloc")), derive(Clone, Copy))]
+#[derive(Clone)]
+#[cfg_attr(not(any(feature = "std", feature = "alloc")), derive(Copy))]
 pub struct DeserializeError(
-    #[cfg(any(feature = "std", feature = "alloc"))] NonNull<String>,
-    #[cfg(not(any(feature = "std", feature = "alloc")))] DeserializerError,
+    /// `None` stands for [`DeserializerError::Error`], the most common error, so it never allocates.
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    Option<Box<ErrorImpl>>,
+    #[cfg(all(
+        not(any(feature = "std", feature = "alloc")),
+        not(feature = "heapless-errors")
+    ))]
+    DeserializerError,
+    /// The message rendered inline, see the `heapless-errors` feature.
+    #[cfg(all(
+        not(any(feature = "std", feature = "alloc")),
+        feature = "heapless-errors"
+    ))]
+    InlineMessage,
 );
 
 impl DeserializeError {
+    /// Creates an error that wraps the original error transparently.
+    ///
+    /// The message of the new error is the message of `error`, and the kind is
+    /// [`DeserializeErrorKind::Custom`]. Its [`source`] is the source of `error`, so that the chain
+    /// of errors does not repeat the message. The original error is returned by
+    /// [`downcast_ref`](Self::downcast_ref). An error of this type is returned as is.
+    ///
+    /// [`source`]: error::Error::source
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde_dyn::de::DeserializeError;
+    /// let error = DeserializeError::from_source("x".parse::<u8>().unwrap_err());
+    /// assert_eq!(error.to_string(), "invalid digit found in string");
+    /// assert!(error.downcast_ref::<std::num::ParseIntError>().is_some());
+    /// ```
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[cold]
+    pub fn from_source<E>(error: E) -> Self
+    where
+        E: error::Error + Send + Sync + 'static,
+    {
+        // Wrapping the error in itself would hide its source.
+        if let Some(error) = (&error as &dyn Any).downcast_ref::<Self>() {
+            return error.clone();
+        }
+
+        DeserializeError::from_inner(ErrorImpl {
+            message: error.to_string(),
+            kind: ErrorKind::Custom,
+            path: None,
+            source: Some(Arc::new(error)),
+        })
+    }
+
+    /// Returns the original error if it's of type `E`.
+    ///
+    /// Also see [`DeserializeError::from_source`].
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[must_use]
+    pub fn downcast_ref<E: error::Error + 'static>(&self) -> Option<&E> {
+        self.as_inner().ok()?.source.as_deref()?.downcast_ref()
+    }
+
+    /// Returns the category of this error.
+    ///
+    /// Errors created by [`serde::de::Error::custom`] and errors caused by the dynamic deserializer
+    /// being in the wrong state are [`DeserializeErrorKind::Custom`]. Without the `alloc` feature,
+    /// nothing else is remembered, so the kind is always `Custom`.
+    ///
+    /// Note that when the concrete deserializer fails, its error is kept in the
+    /// [`InplaceDeserializer`] and the returned error only tells the state. Use
+    /// [`with_dyn`] to get the concrete error back.
+    #[must_use]
+    pub fn kind(&self) -> DeserializeErrorKind<'_> {
+        #[cfg(any(feature = "std", feature = "alloc"))]
+        match self.as_inner() {
+            Ok(error) => error.kind.as_kind(),
+            Err(_) => DeserializeErrorKind::Custom,
+        }
+
+        #[cfg(not(any(feature = "std", feature = "alloc")))]
+        {
+            DeserializeErrorKind::Custom
+        }
+    }
+
+    /// Returns the path to the value where the deserialization failed.
+    ///
+    /// The path is only recorded by [`path::deserialize`](crate::path::deserialize).
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[must_use]
+    pub fn path(&self) -> Option<&Path> {
+        self.as_inner().ok()?.path.as_ref()
+    }
+
     #[cold]
     #[must_use]
     fn into_de_error<E: serde::de::Error>(self) -> E {
         #[cfg(any(feature = "std", feature = "alloc"))]
-        match self.into_string() {
+        match self.into_inner() {
             Err(error) => E::custom(error),
-            Ok(error) => E::custom(error),
+            Ok(error) => error.into_de_error(),
         }
 
         #[cfg(not(any(feature = "std", feature = "alloc")))]
@@ -1474,71 +1987,45 @@ impl DeserializeError {
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 impl DeserializeError {
-    const fn encode(error: DeserializerError) -> NonZeroUsize {
-        const ALIGN: usize = mem::align_of::<String>();
-        NonZeroUsize::new(((error as usize) << ALIGN.trailing_zeros()) | ALIGN.strict_sub(1))
-            .unwrap()
-    }
-
-    const fn decode(error: NonZeroUsize) -> Option<DeserializerError> {
-        const DESERIALIZE_ERROR_ERROR: NonZeroUsize =
-            DeserializeError::encode(DeserializerError::Error);
-        const DESERIALIZE_ERROR_DESERIALIZER: NonZeroUsize =
-            DeserializeError::encode(DeserializerError::Deserializer);
-        const DESERIALIZE_ERROR_DESERIALIZE_SEED: NonZeroUsize =
-            DeserializeError::encode(DeserializerError::DeserializeSeed);
-        const DESERIALIZE_ERROR_VISITOR: NonZeroUsize =
-            DeserializeError::encode(DeserializerError::Visitor);
-        const DESERIALIZE_ERROR_SEQ_ACCESS: NonZeroUsize =
-            DeserializeError::encode(DeserializerError::SeqAccess);
-        const DESERIALIZE_ERROR_MAP_ACCESS: NonZeroUsize =
-            DeserializeError::encode(DeserializerError::MapAccess);
-        const DESERIALIZE_ERROR_ENUM_ACCESS: NonZeroUsize =
-            DeserializeError::encode(DeserializerError::EnumAccess);
-        const DESERIALIZE_ERROR_VARIANT_ACCESS: NonZeroUsize =
-            DeserializeError::encode(DeserializerError::VariantAccess);
-
-        match error {
-            DESERIALIZE_ERROR_ERROR => Some(DeserializerError::Error),
-            DESERIALIZE_ERROR_DESERIALIZER => Some(DeserializerError::Deserializer),
-            DESERIALIZE_ERROR_DESERIALIZE_SEED => Some(DeserializerError::DeserializeSeed),
-            DESERIALIZE_ERROR_VISITOR => Some(DeserializerError::Visitor),
-            DESERIALIZE_ERROR_SEQ_ACCESS => Some(DeserializerError::SeqAccess),
-            DESERIALIZE_ERROR_MAP_ACCESS => Some(DeserializerError::MapAccess),
-            DESERIALIZE_ERROR_ENUM_ACCESS => Some(DeserializerError::EnumAccess),
-            DESERIALIZE_ERROR_VARIANT_ACCESS => Some(DeserializerError::VariantAccess),
-            _ => None,
-        }
+    fn new(message: String, kind: ErrorKind) -> Self {
+        DeserializeError::from_inner(ErrorImpl {
+            message,
+            kind,
+            path: None,
+            source: None,
+        })
     }
 
-    fn into_string(self) -> DeserializerResult<String> {
-        let this = mem::ManuallyDrop::new(self);
+    fn from_inner(error: ErrorImpl) -> Self {
+        DeserializeError(Some(Box::new(error)))
+    }
 
-        match DeserializeError::decode(this.0.expose_provenance()) {
-            Some(error) => Err(error),
-            // TODO: Replace `Box::from_raw` with `Box::from_non_null` once it's stablized.
-            // SAFETY: We have handled the `SerializerError` case.
-            None => Ok(*unsafe { Box::from_raw(this.0.as_ptr()) }),
-        }
+    #[cold]
+    pub(crate) fn with_path(self, path: Path) -> Self {
+        let error = match self.into_inner() {
+            Ok(error) => error,
+            Err(error) => ErrorImpl {
+                message: error.to_string(),
+                kind: ErrorKind::Custom,
+                path: None,
+                source: None,
+            },
+        };
+        DeserializeError::from_inner(ErrorImpl {
+            path: Some(path),
+            ..error
+        })
     }
 
-    fn as_string(&self) -> DeserializerResult<&String> {
-        match DeserializeError::decode(self.0.expose_provenance()) {
-            Some(error) => Err(error),
-            // SAFETY: We have handled the `SerializerError` case.
-            None => Ok(unsafe { self.0.as_ref() }),
+    fn into_inner(self) -> DeserializerResult<ErrorImpl> {
+        match self.0 {
+            Some(error) => Ok(*error),
+            None => Err(DeserializerError::Error),
         }
     }
-}
-
-#[cfg(any(feature = "std", feature = "alloc"))]
-impl Drop for DeserializeError {
-    fn drop(&mut self) {
-        const REPLACEMENT: DeserializeError = DeserializeError(NonNull::without_provenance(
-            DeserializeError::encode(DeserializerError::Error),
-        ));
 
-        let _ = mem::replace(self, REPLACEMENT).into_string();
+    fn as_inner(&self) -> DeserializerResult<&ErrorImpl> {
+        self.0.as_deref().ok_or(DeserializerError::Error)
     }
 }
 
@@ -1547,13 +2034,30 @@ impl From<DeserializerError> for DeserializeError {
     #[inline(never)]
     fn from(value: DeserializerError) -> Self {
         #[cfg(any(feature = "std", feature = "alloc"))]
+        match value {
+            DeserializerError::Error => DeserializeError(None),
+            // The other errors only happen when the dynamic traits are misused.
+            error => DeserializeError::from_inner(ErrorImpl {
+                message: error.to_string(),
+                kind: ErrorKind::Custom,
+                path: None,
+                source: None,
+            }),
+        }
+
+        #[cfg(all(
+            not(any(feature = "std", feature = "alloc")),
+            not(feature = "heapless-errors")
+        ))]
         {
-            DeserializeError(NonNull::without_provenance(DeserializeError::encode(value)))
+            DeserializeError(value)
         }
-
-        #[cfg(not(any(feature = "std", feature = "alloc")))]
+        #[cfg(all(
+            not(any(feature = "std", feature = "alloc")),
+            feature = "heapless-errors"
+        ))]
         {
-            DeserializeError(value)
+            DeserializeError(InlineMessage::new(&value))
         }
     }
 }
@@ -1562,28 +2066,127 @@ impl serde::de::Error for DeserializeError {
     #[cold]
     #[inline(never)]
     fn custom<T: fmt::Display>(msg: T) -> Self {
+        #[cfg(feature = "std")]
+        if let Some(error) = FORWARDED.take() {
+            return DeserializeError::from_inner(error);
+        }
+
         #[cfg(any(feature = "std", feature = "alloc"))]
         {
-            // TODO: Replace `Box::into_raw` with `Box::into_non_null` once it's stablized.
-            DeserializeError(
-                NonNull::new(Box::into_raw(Box::new(msg.to_string())))
-                    .expect("`Box::into_raw` never returns null pointer"),
-            )
+            DeserializeError::new(msg.to_string(), ErrorKind::Custom)
         }
 
-        #[cfg(not(any(feature = "std", feature = "alloc")))]
+        #[cfg(all(
+            not(any(feature = "std", feature = "alloc")),
+            not(feature = "heapless-errors")
+        ))]
         {
             let _ = msg;
             DeserializeError(DeserializerError::Error)
         }
+        #[cfg(all(
+            not(any(feature = "std", feature = "alloc")),
+            feature = "heapless-errors"
+        ))]
+        {
+            DeserializeError(InlineMessage::new(&msg))
+        }
+    }
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[cold]
+    #[inline(never)]
+    fn invalid_type(unexp: Unexpected<'_>, exp: &dyn Expected) -> Self {
+        let exp = exp.to_string();
+        DeserializeError::new(
+            Message::invalid_type(unexp, &exp.as_str()).0,
+            ErrorKind::InvalidType(UnexpectedBuf::from(unexp), exp),
+        )
+    }
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[cold]
+    #[inline(never)]
+    fn invalid_value(unexp: Unexpected<'_>, exp: &dyn Expected) -> Self {
+        let exp = exp.to_string();
+        DeserializeError::new(
+            Message::invalid_value(unexp, &exp.as_str()).0,
+            ErrorKind::InvalidValue(UnexpectedBuf::from(unexp), exp),
+        )
+    }
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[cold]
+    #[inline(never)]
+    fn invalid_length(len: usize, exp: &dyn Expected) -> Self {
+        let exp = exp.to_string();
+        DeserializeError::new(
+            Message::invalid_length(len, &exp.as_str()).0,
+            ErrorKind::InvalidLength(len, exp),
+        )
+    }
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[cold]
+    #[inline(never)]
+    fn unknown_variant(variant: &str, expected: &'static [&'static str]) -> Self {
+        DeserializeError::new(
+            Message::unknown_variant(variant, expected).0,
+            ErrorKind::UnknownVariant(variant.to_string(), expected),
+        )
+    }
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[cold]
+    #[inline(never)]
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+        DeserializeError::new(
+            Message::unknown_field(field, expected).0,
+            ErrorKind::UnknownField(field.to_string(), expected),
+        )
+    }
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[cold]
+    #[inline(never)]
+    fn missing_field(field: &'static str) -> Self {
+        DeserializeError::new(
+            Message::missing_field(field).0,
+            ErrorKind::MissingField(field),
+        )
+    }
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[cold]
+    #[inline(never)]
+    fn duplicate_field(field: &'static str) -> Self {
+        DeserializeError::new(
+            Message::duplicate_field(field).0,
+            ErrorKind::DuplicateField(field),
+        )
     }
 }
 
 impl fmt::Debug for DeserializeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         #[cfg(any(feature = "std", feature = "alloc"))]
-        match self.as_string() {
-            Ok(error) => f.write_str(error),
+        match self.as_inner() {
+            Ok(error) => fmt::Display::fmt(error, f),
+            Err(error) => error.fmt(f),
+        }
+
+        #[cfg(not(any(feature = "std", feature = "alloc")))]
+        {
+            self.0.fmt(f)
+        }
+    }
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(any(feature = "std", feature = "alloc"))]
+        match self.as_inner() {
+            Ok(error) => fmt::Display::fmt(error, f),
             Err(error) => error.fmt(f),
         }
 
@@ -1594,235 +2197,1083 @@ impl fmt::Debug for DeserializeError {
     }
 }
 
-impl fmt::Display for DeserializeError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        #[cfg(any(feature = "std", feature = "alloc"))]
-        match self.as_string() {
-            Ok(error) => f.write_str(error),
-            Err(error) => error.fmt(f),
+impl error::Error for DeserializeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        #[cfg(any(feature = "std", feature = "alloc"))]
+        match self.as_inner() {
+            // The wrapped error has the same message, see `from_source`.
+            Ok(error) => error.source.as_deref()?.source(),
+            Err(_) => None,
+        }
+
+        #[cfg(not(any(feature = "std", feature = "alloc")))]
+        {
+            None
+        }
+    }
+}
+
+/// The category of a [`DeserializeError`].
+///
+/// Each variant mirrors a constructor of [`serde::de::Error`] and carries its arguments, with the
+/// [`serde::de::Expected`] rendered as text.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum DeserializeErrorKind<'a> {
+    /// Created by [`serde::de::Error::custom`], or the dynamic deserializer is in the wrong state.
+    Custom,
+    /// Created by [`serde::de::Error::invalid_type`].
+    InvalidType(Unexpected<'a>, &'a str),
+    /// Created by [`serde::de::Error::invalid_value`].
+    InvalidValue(Unexpected<'a>, &'a str),
+    /// Created by [`serde::de::Error::invalid_length`].
+    InvalidLength(usize, &'a str),
+    /// Created by [`serde::de::Error::unknown_variant`].
+    UnknownVariant(&'a str, &'static [&'static str]),
+    /// Created by [`serde::de::Error::unknown_field`].
+    UnknownField(&'a str, &'static [&'static str]),
+    /// Created by [`serde::de::Error::missing_field`].
+    MissingField(&'static str),
+    /// Created by [`serde::de::Error::duplicate_field`].
+    DuplicateField(&'static str),
+}
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    /// The error being converted by `ErrorImpl::into_de_error`, which `DeserializeError::custom`
+    /// takes back as is.
+    static FORWARDED: core::cell::Cell<Option<ErrorImpl>> = const { core::cell::Cell::new(None) };
+}
+
+/// The heap-allocated payload of [`DeserializeError`].
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[derive(Clone)]
+struct ErrorImpl {
+    message: String,
+    kind: ErrorKind,
+    path: Option<Path>,
+    source: Option<Arc<dyn error::Error + Send + Sync>>,
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl ErrorImpl {
+    fn into_de_error<E: serde::de::Error>(self) -> E {
+        if self.path.is_some() || self.source.is_some() {
+            // The constructors of `serde::de::Error` have nowhere to put the path and the source,
+            // so with `std` they are forwarded to `DeserializeError::custom` if `E` is
+            // `DeserializeError`.
+            let message = self.to_string();
+            #[cfg(feature = "std")]
+            FORWARDED.set(Some(self));
+            let error = E::custom(message);
+            #[cfg(feature = "std")]
+            FORWARDED.take();
+            return error;
+        }
+
+        match self.kind {
+            ErrorKind::Custom => E::custom(self.message),
+            ErrorKind::InvalidType(unexp, exp) => E::invalid_type(unexp.as_unexpected(), &&*exp),
+            ErrorKind::InvalidValue(unexp, exp) => E::invalid_value(unexp.as_unexpected(), &&*exp),
+            ErrorKind::InvalidLength(len, exp) => E::invalid_length(len, &&*exp),
+            ErrorKind::UnknownVariant(variant, expected) => E::unknown_variant(&variant, expected),
+            ErrorKind::UnknownField(field, expected) => E::unknown_field(&field, expected),
+            ErrorKind::MissingField(field) => E::missing_field(field),
+            ErrorKind::DuplicateField(field) => E::duplicate_field(field),
+        }
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl fmt::Display for ErrorImpl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(path) = &self.path
+            && !path.is_empty()
+        {
+            write!(f, "{path}: ")?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+/// The owned version of [`DeserializeErrorKind`].
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[derive(Clone)]
+enum ErrorKind {
+    Custom,
+    InvalidType(UnexpectedBuf, String),
+    InvalidValue(UnexpectedBuf, String),
+    InvalidLength(usize, String),
+    UnknownVariant(String, &'static [&'static str]),
+    UnknownField(String, &'static [&'static str]),
+    MissingField(&'static str),
+    DuplicateField(&'static str),
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl ErrorKind {
+    fn as_kind(&self) -> DeserializeErrorKind<'_> {
+        match self {
+            ErrorKind::Custom => DeserializeErrorKind::Custom,
+            ErrorKind::InvalidType(unexp, exp) => {
+                DeserializeErrorKind::InvalidType(unexp.as_unexpected(), exp)
+            }
+            ErrorKind::InvalidValue(unexp, exp) => {
+                DeserializeErrorKind::InvalidValue(unexp.as_unexpected(), exp)
+            }
+            ErrorKind::InvalidLength(len, exp) => DeserializeErrorKind::InvalidLength(*len, exp),
+            ErrorKind::UnknownVariant(variant, expected) => {
+                DeserializeErrorKind::UnknownVariant(variant, expected)
+            }
+            ErrorKind::UnknownField(field, expected) => {
+                DeserializeErrorKind::UnknownField(field, expected)
+            }
+            ErrorKind::MissingField(field) => DeserializeErrorKind::MissingField(field),
+            ErrorKind::DuplicateField(field) => DeserializeErrorKind::DuplicateField(field),
+        }
+    }
+}
+
+/// The owned version of [`Unexpected`].
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[derive(Clone)]
+enum UnexpectedBuf {
+    Bool(bool),
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+    Char(char),
+    Str(String),
+    Bytes(Vec<u8>),
+    Unit,
+    Option,
+    NewtypeStruct,
+    Seq,
+    Map,
+    Enum,
+    UnitVariant,
+    NewtypeVariant,
+    TupleVariant,
+    StructVariant,
+    Other(String),
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl UnexpectedBuf {
+    fn as_unexpected(&self) -> Unexpected<'_> {
+        match self {
+            UnexpectedBuf::Bool(v) => Unexpected::Bool(*v),
+            UnexpectedBuf::Unsigned(v) => Unexpected::Unsigned(*v),
+            UnexpectedBuf::Signed(v) => Unexpected::Signed(*v),
+            UnexpectedBuf::Float(v) => Unexpected::Float(*v),
+            UnexpectedBuf::Char(v) => Unexpected::Char(*v),
+            UnexpectedBuf::Str(v) => Unexpected::Str(v),
+            UnexpectedBuf::Bytes(v) => Unexpected::Bytes(v),
+            UnexpectedBuf::Unit => Unexpected::Unit,
+            UnexpectedBuf::Option => Unexpected::Option,
+            UnexpectedBuf::NewtypeStruct => Unexpected::NewtypeStruct,
+            UnexpectedBuf::Seq => Unexpected::Seq,
+            UnexpectedBuf::Map => Unexpected::Map,
+            UnexpectedBuf::Enum => Unexpected::Enum,
+            UnexpectedBuf::UnitVariant => Unexpected::UnitVariant,
+            UnexpectedBuf::NewtypeVariant => Unexpected::NewtypeVariant,
+            UnexpectedBuf::TupleVariant => Unexpected::TupleVariant,
+            UnexpectedBuf::StructVariant => Unexpected::StructVariant,
+            UnexpectedBuf::Other(v) => Unexpected::Other(v),
+        }
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl From<Unexpected<'_>> for UnexpectedBuf {
+    fn from(value: Unexpected<'_>) -> Self {
+        match value {
+            Unexpected::Bool(v) => UnexpectedBuf::Bool(v),
+            Unexpected::Unsigned(v) => UnexpectedBuf::Unsigned(v),
+            Unexpected::Signed(v) => UnexpectedBuf::Signed(v),
+            Unexpected::Float(v) => UnexpectedBuf::Float(v),
+            Unexpected::Char(v) => UnexpectedBuf::Char(v),
+            Unexpected::Str(v) => UnexpectedBuf::Str(v.to_string()),
+            Unexpected::Bytes(v) => UnexpectedBuf::Bytes(v.to_vec()),
+            Unexpected::Unit => UnexpectedBuf::Unit,
+            Unexpected::Option => UnexpectedBuf::Option,
+            Unexpected::NewtypeStruct => UnexpectedBuf::NewtypeStruct,
+            Unexpected::Seq => UnexpectedBuf::Seq,
+            Unexpected::Map => UnexpectedBuf::Map,
+            Unexpected::Enum => UnexpectedBuf::Enum,
+            Unexpected::UnitVariant => UnexpectedBuf::UnitVariant,
+            Unexpected::NewtypeVariant => UnexpectedBuf::NewtypeVariant,
+            Unexpected::TupleVariant => UnexpectedBuf::TupleVariant,
+            Unexpected::StructVariant => UnexpectedBuf::StructVariant,
+            Unexpected::Other(v) => UnexpectedBuf::Other(v.to_string()),
+        }
+    }
+}
+
+/// Renders the messages exactly as the provided methods of [`serde::de::Error`] do.
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[derive(Debug)]
+struct Message(String);
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl error::Error for Message {}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl serde::de::Error for Message {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Message(msg.to_string())
+    }
+}
+
+/// An implementation of the [`Deserializer`] trait.
+///
+/// Also see [`serde::de::Deserializer`].
+#[derive(Debug, Default)]
+pub enum InplaceDeserializer<'de, D: serde::Deserializer<'de>> {
+    /// The deserializer is not ready.
+    #[default]
+    None,
+    /// The deserialization has done unsuccessfully.
+    Error(D::Error),
+    /// The deserializer is ready.
+    Deserializer(D),
+}
+
+impl<'de, D: serde::Deserializer<'de>> InplaceDeserializer<'de, D> {
+    /// Returns the current state of the deserializer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde_dyn::Deserializer;
+    /// # use serde_dyn::de::DeserializerState;
+    /// let mut json = serde_json::Deserializer::from_str("null");
+    /// let mut deserializer = <dyn Deserializer>::new(&mut json);
+    /// assert_eq!(deserializer.state(), DeserializerState::Deserializer);
+    /// <() as serde::Deserialize>::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+    /// assert_eq!(deserializer.state(), DeserializerState::None);
+    /// ```
+    #[must_use]
+    pub fn state(&self) -> DeserializerState {
+        match self {
+            InplaceDeserializer::None => DeserializerState::None,
+            InplaceDeserializer::Error(_) => DeserializerState::Error,
+            InplaceDeserializer::Deserializer(_) => DeserializerState::Deserializer,
+        }
+    }
+
+    #[cold]
+    fn wrong_state(&self, operation: &'static str) -> DeserializerError {
+        DeserializerError::WrongState {
+            operation,
+            found: self.state(),
+        }
+    }
+
+    /// Puts back the `state` taken out for `operation`, and returns the error of the misuse.
+    #[cold]
+    fn restore(&mut self, state: Self, operation: &'static str) -> DeserializerError {
+        let error = state.wrong_state(operation);
+        *self = state;
+        error
+    }
+
+    fn write_error(&mut self, error: D::Error) -> DeserializerError {
+        *self = InplaceDeserializer::Error(error);
+        DeserializerError::Error
+    }
+
+    /// Consumes the deserializer after `result` of the dynamic deserialization.
+    ///
+    /// This works like [`with_dyn`], except that the error is a [`DeserializeError`]. If the
+    /// concrete deserializer failed, its error is kept as the source, see
+    /// [`DeserializeError::downcast_ref`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the recorded concrete error, or the error of `result`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde_dyn::Deserializer;
+    /// let mut json = serde_json::Deserializer::from_str("[1, 2");
+    /// let mut deserializer = <dyn Deserializer>::new(&mut json);
+    /// let result = <Vec<u8> as serde::Deserialize>::deserialize(&mut deserializer as &mut dyn Deserializer);
+    /// let error = deserializer.into_result(result).unwrap_err();
+    /// assert!(error.downcast_ref::<serde_json::Error>().unwrap().is_eof());
+    /// ```
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    pub fn into_result<T>(self, result: DeserializeResult<T>) -> DeserializeResult<T>
+    where
+        D::Error: error::Error + Send + Sync + 'static,
+    {
+        match (self, result) {
+            (_, Ok(value)) => Ok(value),
+            (InplaceDeserializer::Error(error), Err(_)) => {
+                Err(DeserializeError::from_source(error))
+            }
+            (_, Err(error)) => Err(error),
+        }
+    }
+}
+
+impl<'de, D: serde::Deserializer<'de>> Deserializer<'de> for InplaceDeserializer<'de, D> {
+    fn dyn_deserialize_any(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_any(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_any")),
+        }
+    }
+
+    fn dyn_deserialize_bool(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_bool(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_bool")),
+        }
+    }
+
+    fn dyn_deserialize_i8(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_i8(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_i8")),
+        }
+    }
+
+    fn dyn_deserialize_i16(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_i16(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_i16")),
+        }
+    }
+
+    fn dyn_deserialize_i32(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_i32(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_i32")),
+        }
+    }
+
+    fn dyn_deserialize_i64(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_i64(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_i64")),
+        }
+    }
+
+    fn dyn_deserialize_128(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_i128(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_128")),
+        }
+    }
+
+    fn dyn_deserialize_u8(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_u8(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_u8")),
+        }
+    }
+
+    fn dyn_deserialize_u16(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_u16(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_u16")),
+        }
+    }
+
+    fn dyn_deserialize_u32(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_u32(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_u32")),
+        }
+    }
+
+    fn dyn_deserialize_u64(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_u64(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_u64")),
+        }
+    }
+
+    fn dyn_deserialize_u128(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_u128(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_u128")),
+        }
+    }
+
+    fn dyn_deserialize_f32(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_f32(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_f32")),
+        }
+    }
+
+    fn dyn_deserialize_f64(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_f64(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_f64")),
+        }
+    }
+
+    fn dyn_deserialize_char(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_char(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_char")),
+        }
+    }
+
+    fn dyn_deserialize_str(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_str(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_str")),
+        }
+    }
+
+    fn dyn_deserialize_string(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_string(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_string")),
+        }
+    }
+
+    fn dyn_deserialize_bytes(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_bytes(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_bytes")),
+        }
+    }
+
+    fn dyn_deserialize_byte_buf(
+        &mut self,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_byte_buf(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_byte_buf")),
+        }
+    }
+
+    fn dyn_deserialize_option(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_option(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_option")),
+        }
+    }
+
+    fn dyn_deserialize_unit(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_unit(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_unit")),
+        }
+    }
+
+    fn dyn_deserialize_unit_struct(
+        &mut self,
+        name: &'static str,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_unit_struct(name, visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_unit_struct")),
+        }
+    }
+
+    fn dyn_deserialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_newtype_struct(name, visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_newtype_struct")),
+        }
+    }
+
+    fn dyn_deserialize_seq(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_seq(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_seq")),
+        }
+    }
+
+    fn dyn_deserialize_tuple(
+        &mut self,
+        len: usize,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_tuple(len, visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_tuple")),
+        }
+    }
+
+    fn dyn_deserialize_tuple_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_tuple_struct(name, len, visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_tuple_struct")),
+        }
+    }
+
+    fn dyn_deserialize_map(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_map(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_map")),
+        }
+    }
+
+    fn dyn_deserialize_struct(
+        &mut self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_struct(name, fields, visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_struct")),
+        }
+    }
+
+    fn dyn_deserialize_enum(
+        &mut self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_enum(name, variants, visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_enum")),
+        }
+    }
+
+    fn dyn_deserialize_identifier(
+        &mut self,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_identifier(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_identifier")),
+        }
+    }
+
+    fn dyn_deserialize_ignored_any(
+        &mut self,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        match mem::take(self) {
+            InplaceDeserializer::Deserializer(de) => de
+                .deserialize_ignored_any(visitor)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "Deserializer::dyn_deserialize_ignored_any")),
+        }
+    }
+
+    fn dyn_is_human_readable(&self) -> bool {
+        if let InplaceDeserializer::Deserializer(de) = self {
+            de.is_human_readable()
+        } else {
+            true
+        }
+    }
+}
+
+/// Wraps `deserializer` into an owned [`BoxDeserializer`].
+///
+/// Unlike `<dyn Deserializer>::new` whose result has to be borrowed to be used as
+/// `dyn Deserializer`, the returned deserializer can be returned from functions and stored in
+/// struct fields.
+///
+/// This is for the deserializers taken by value. For the ones used through a mutable reference,
+/// such as `serde_json::Deserializer`, see [`boxed_mut`].
+///
+/// # Examples
+///
+/// ```
+/// # use serde_dyn::Deserializer;
+/// # use serde_dyn::de::{self, BoxDeserializer};
+/// fn open(value: serde_json::Value) -> BoxDeserializer<'static> {
+///     de::boxed(value)
+/// }
+///
+/// let mut deserializer = open(serde_json::json!([1, 2]));
+/// let deserializer: &mut dyn Deserializer = &mut deserializer;
+/// let value: (u8, u8) = serde::Deserialize::deserialize(deserializer).unwrap();
+/// assert_eq!(value, (1, 2));
+/// ```
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn boxed<'de, D>(deserializer: D) -> BoxDeserializer<'de>
+where
+    D: serde::Deserializer<'de> + 'de,
+    D::Error: 'static,
+{
+    BoxDeserializer(Box::new(InplaceDeserializer::Deserializer(deserializer)))
+}
+
+/// Wraps `deserializer`, which is used through a mutable reference, into an owned
+/// [`BoxDeserializer`].
+///
+/// The box owns `deserializer` and borrows it again for every dynamic deserialization, so that a
+/// format such as `serde_json::Deserializer` can be handed out together with its input.
+///
+/// # Examples
+///
+/// ```
+/// # use serde_dyn::Deserializer;
+/// # use serde_dyn::de::{self, BoxDeserializer};
+/// fn open(input: &str) -> BoxDeserializer<'_> {
+///     de::boxed_mut(serde_json::Deserializer::from_str(input))
+/// }
+///
+/// let mut deserializer = open("[1,2]");
+/// let deserializer: &mut dyn Deserializer = &mut deserializer;
+/// let value: (u8, u8) = serde::Deserialize::deserialize(deserializer).unwrap();
+/// assert_eq!(value, (1, 2));
+/// ```
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn boxed_mut<'de, D, E>(deserializer: D) -> BoxDeserializer<'de>
+where
+    D: 'de,
+    for<'a> &'a mut D: serde::Deserializer<'de, Error = E>,
+    E: 'static,
+{
+    let mut deserializer = MutDeserializer {
+        deserializer,
+        is_human_readable: true,
+        error: None,
+    };
+    deserializer.is_human_readable =
+        serde::Deserializer::is_human_readable(&&mut deserializer.deserializer);
+    BoxDeserializer(Box::new(deserializer))
+}
+
+/// An owned [`Deserializer`] wrapping any [`serde::Deserializer`], created by [`boxed`] or
+/// [`boxed_mut`].
+///
+/// Like [`InplaceDeserializer`], the original error of the concrete deserializer is stashed
+/// inside, and can be taken back by [`BoxDeserializer::error`] or [`BoxDeserializer::into_error`].
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub struct BoxDeserializer<'de>(Box<dyn ErasedDeserializer<'de> + 'de>);
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl BoxDeserializer<'_> {
+    /// Returns the original error, if the concrete deserializer failed with an error of type `E`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde_dyn::Deserializer;
+    /// # use serde_dyn::de;
+    /// let mut deserializer = de::boxed_mut(serde_json::Deserializer::from_str("\n[1,"));
+    /// let value = <Vec<u8> as serde::Deserialize>::deserialize(&mut deserializer as &mut dyn Deserializer);
+    /// assert!(value.is_err());
+    ///
+    /// let error = deserializer.error::<serde_json::Error>().unwrap();
+    /// assert_eq!((error.line(), error.column()), (2, 3));
+    /// ```
+    #[must_use]
+    pub fn error<E: 'static>(&self) -> Option<&E> {
+        self.0.dyn_error()?.downcast_ref()
+    }
+
+    /// Consumes the deserializer, and returns the original error like [`BoxDeserializer::error`].
+    #[must_use]
+    pub fn into_error<E: 'static>(self) -> Option<E> {
+        self.0.dyn_into_error()?.downcast().ok().map(|error| *error)
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'de> Deserializer<'de> for BoxDeserializer<'de> {
+    fn dyn_deserialize_any(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_bool(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_bool(visitor)
+    }
+
+    fn dyn_deserialize_i8(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_i8(visitor)
+    }
+
+    fn dyn_deserialize_i16(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_i16(visitor)
+    }
+
+    fn dyn_deserialize_i32(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_i32(visitor)
+    }
+
+    fn dyn_deserialize_i64(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_i64(visitor)
+    }
+
+    fn dyn_deserialize_128(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_128(visitor)
+    }
+
+    fn dyn_deserialize_u8(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_u8(visitor)
+    }
+
+    fn dyn_deserialize_u16(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_u16(visitor)
+    }
+
+    fn dyn_deserialize_u32(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_u32(visitor)
+    }
+
+    fn dyn_deserialize_u64(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_u64(visitor)
+    }
+
+    fn dyn_deserialize_u128(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_u128(visitor)
+    }
+
+    fn dyn_deserialize_f32(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_f32(visitor)
+    }
+
+    fn dyn_deserialize_f64(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_f64(visitor)
+    }
+
+    fn dyn_deserialize_char(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_char(visitor)
+    }
+
+    fn dyn_deserialize_str(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_str(visitor)
+    }
+
+    fn dyn_deserialize_string(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_string(visitor)
+    }
+
+    fn dyn_deserialize_bytes(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_bytes(visitor)
+    }
+
+    fn dyn_deserialize_byte_buf(
+        &mut self,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_byte_buf(visitor)
+    }
+
+    fn dyn_deserialize_option(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_option(visitor)
+    }
+
+    fn dyn_deserialize_unit(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_unit(visitor)
+    }
+
+    fn dyn_deserialize_unit_struct(
+        &mut self,
+        name: &'static str,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_unit_struct(name, visitor)
+    }
+
+    fn dyn_deserialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_newtype_struct(name, visitor)
+    }
+
+    fn dyn_deserialize_seq(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_seq(visitor)
+    }
+
+    fn dyn_deserialize_tuple(
+        &mut self,
+        len: usize,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_tuple(len, visitor)
+    }
+
+    fn dyn_deserialize_tuple_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_tuple_struct(name, len, visitor)
+    }
+
+    fn dyn_deserialize_map(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_map(visitor)
+    }
+
+    fn dyn_deserialize_struct(
+        &mut self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_struct(name, fields, visitor)
+    }
+
+    fn dyn_deserialize_enum(
+        &mut self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_enum(name, variants, visitor)
+    }
+
+    fn dyn_deserialize_identifier(
+        &mut self,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_identifier(visitor)
+    }
+
+    fn dyn_deserialize_ignored_any(
+        &mut self,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.0.dyn_deserialize_ignored_any(visitor)
+    }
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.0.dyn_is_human_readable()
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl fmt::Debug for BoxDeserializer<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BoxDeserializer").finish_non_exhaustive()
+    }
+}
+
+/// The object-safe part of the deserializers kept by [`BoxDeserializer`].
+#[cfg(any(feature = "std", feature = "alloc"))]
+trait ErasedDeserializer<'de>: Deserializer<'de> {
+    fn dyn_error(&self) -> Option<&dyn Any>;
+
+    fn dyn_into_error(self: Box<Self>) -> Option<Box<dyn Any>>;
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'de, D> ErasedDeserializer<'de> for InplaceDeserializer<'de, D>
+where
+    D: serde::Deserializer<'de>,
+    D::Error: 'static,
+{
+    fn dyn_error(&self) -> Option<&dyn Any> {
+        match self {
+            InplaceDeserializer::Error(error) => Some(error),
+            _ => None,
         }
+    }
 
-        #[cfg(not(any(feature = "std", feature = "alloc")))]
-        {
-            self.0.fmt(f)
+    fn dyn_into_error(self: Box<Self>) -> Option<Box<dyn Any>> {
+        match *self {
+            InplaceDeserializer::Error(error) => Some(Box::new(error)),
+            _ => None,
         }
     }
 }
 
-impl error::Error for DeserializeError {}
-
-/// An implementation of the [`Deserializer`] trait.
-///
-/// Also see [`serde::de::Deserializer`].
-#[derive(Debug, Default)]
-pub enum InplaceDeserializer<'de, D: serde::Deserializer<'de>> {
-    /// The deserializer is not ready.
-    #[default]
-    None,
-    /// The deserialization has done unsuccessfully.
-    Error(D::Error),
-    /// The deserializer is ready.
-    Deserializer(D),
+/// The owned deserializer of [`boxed_mut`].
+#[cfg(any(feature = "std", feature = "alloc"))]
+struct MutDeserializer<D, E> {
+    deserializer: D,
+    is_human_readable: bool,
+    error: Option<E>,
 }
 
-impl<'de, D: serde::Deserializer<'de>> InplaceDeserializer<'de, D> {
-    fn write_error(&mut self, error: D::Error) -> DeserializerError {
-        *self = InplaceDeserializer::Error(error);
-        DeserializerError::Error
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<D, E> MutDeserializer<D, E> {
+    /// Runs `f` with a fresh [`InplaceDeserializer`] borrowing the owned deserializer, and keeps
+    /// the original error.
+    fn forward<'de, F>(&mut self, f: F) -> DeserializerResult<()>
+    where
+        for<'a> &'a mut D: serde::Deserializer<'de, Error = E>,
+        F: FnOnce(&mut dyn Deserializer<'de>) -> DeserializerResult<()>,
+    {
+        let mut deserializer = InplaceDeserializer::Deserializer(&mut self.deserializer);
+        let result = f(&mut deserializer);
+        if let InplaceDeserializer::Error(error) = deserializer {
+            self.error = Some(error);
+        }
+        result
     }
 }
 
-impl<'de, D: serde::Deserializer<'de>> Deserializer<'de> for InplaceDeserializer<'de, D> {
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'de, D, E> Deserializer<'de> for MutDeserializer<D, E>
+where
+    for<'a> &'a mut D: serde::Deserializer<'de, Error = E>,
+{
     fn dyn_deserialize_any(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_any(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_any(visitor))
     }
 
     fn dyn_deserialize_bool(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_bool(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_bool(visitor))
     }
 
     fn dyn_deserialize_i8(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_i8(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_i8(visitor))
     }
 
     fn dyn_deserialize_i16(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_i16(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_i16(visitor))
     }
 
     fn dyn_deserialize_i32(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_i32(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_i32(visitor))
     }
 
     fn dyn_deserialize_i64(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_i64(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_i64(visitor))
     }
 
     fn dyn_deserialize_128(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_i128(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_128(visitor))
     }
 
     fn dyn_deserialize_u8(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_u8(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_u8(visitor))
     }
 
     fn dyn_deserialize_u16(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_u16(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_u16(visitor))
     }
 
     fn dyn_deserialize_u32(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_u32(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_u32(visitor))
     }
 
     fn dyn_deserialize_u64(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_u64(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_u64(visitor))
     }
 
     fn dyn_deserialize_u128(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_u128(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_u128(visitor))
     }
 
     fn dyn_deserialize_f32(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_f32(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_f32(visitor))
     }
 
     fn dyn_deserialize_f64(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_f64(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_f64(visitor))
     }
 
     fn dyn_deserialize_char(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_char(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_char(visitor))
     }
 
     fn dyn_deserialize_str(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_str(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_str(visitor))
     }
 
     fn dyn_deserialize_string(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_string(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_string(visitor))
     }
 
     fn dyn_deserialize_bytes(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_bytes(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_bytes(visitor))
     }
 
     fn dyn_deserialize_byte_buf(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
     ) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_byte_buf(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_byte_buf(visitor))
     }
 
     fn dyn_deserialize_option(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_option(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_option(visitor))
     }
 
     fn dyn_deserialize_unit(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_unit(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_unit(visitor))
     }
 
     fn dyn_deserialize_unit_struct(
@@ -1830,12 +3281,7 @@ impl<'de, D: serde::Deserializer<'de>> Deserializer<'de> for InplaceDeserializer
         name: &'static str,
         visitor: &mut dyn Visitor<'de>,
     ) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_unit_struct(name, visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_unit_struct(name, visitor))
     }
 
     fn dyn_deserialize_newtype_struct(
@@ -1843,21 +3289,11 @@ impl<'de, D: serde::Deserializer<'de>> Deserializer<'de> for InplaceDeserializer
         name: &'static str,
         visitor: &mut dyn Visitor<'de>,
     ) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_newtype_struct(name, visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_newtype_struct(name, visitor))
     }
 
     fn dyn_deserialize_seq(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_seq(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_seq(visitor))
     }
 
     fn dyn_deserialize_tuple(
@@ -1865,12 +3301,7 @@ impl<'de, D: serde::Deserializer<'de>> Deserializer<'de> for InplaceDeserializer
         len: usize,
         visitor: &mut dyn Visitor<'de>,
     ) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_tuple(len, visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_tuple(len, visitor))
     }
 
     fn dyn_deserialize_tuple_struct(
@@ -1879,21 +3310,11 @@ impl<'de, D: serde::Deserializer<'de>> Deserializer<'de> for InplaceDeserializer
         len: usize,
         visitor: &mut dyn Visitor<'de>,
     ) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_tuple_struct(name, len, visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_tuple_struct(name, len, visitor))
     }
 
     fn dyn_deserialize_map(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_map(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_map(visitor))
     }
 
     fn dyn_deserialize_struct(
@@ -1902,12 +3323,7 @@ impl<'de, D: serde::Deserializer<'de>> Deserializer<'de> for InplaceDeserializer
         fields: &'static [&'static str],
         visitor: &mut dyn Visitor<'de>,
     ) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_struct(name, fields, visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_struct(name, fields, visitor))
     }
 
     fn dyn_deserialize_enum(
@@ -1916,44 +3332,40 @@ impl<'de, D: serde::Deserializer<'de>> Deserializer<'de> for InplaceDeserializer
         variants: &'static [&'static str],
         visitor: &mut dyn Visitor<'de>,
     ) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_enum(name, variants, visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_enum(name, variants, visitor))
     }
 
     fn dyn_deserialize_identifier(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
     ) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_identifier(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_identifier(visitor))
     }
 
     fn dyn_deserialize_ignored_any(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
     ) -> DeserializerResult<()> {
-        if let InplaceDeserializer::Deserializer(de) = mem::take(self) {
-            de.deserialize_ignored_any(visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::Deserializer)
-        }
+        self.forward(|deserializer| deserializer.dyn_deserialize_ignored_any(visitor))
     }
 
     fn dyn_is_human_readable(&self) -> bool {
-        if let InplaceDeserializer::Deserializer(de) = self {
-            de.is_human_readable()
-        } else {
-            true
-        }
+        self.is_human_readable
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'de, D, E> ErasedDeserializer<'de> for MutDeserializer<D, E>
+where
+    for<'a> &'a mut D: serde::Deserializer<'de, Error = E>,
+    E: 'static,
+{
+    fn dyn_error(&self) -> Option<&dyn Any> {
+        self.error.as_ref().map(|error| error as &dyn Any)
+    }
+
+    fn dyn_into_error(self: Box<Self>) -> Option<Box<dyn Any>> {
+        self.error.map(|error| Box::new(error) as Box<dyn Any>)
     }
 }
 
@@ -1972,6 +3384,55 @@ pub enum InplaceDeserializeSeed<'de, T: serde::de::DeserializeSeed<'de>> {
 }
 
 impl<'de, T: serde::de::DeserializeSeed<'de>> InplaceDeserializeSeed<'de, T> {
+    /// Returns the current state of the deserialize-seed.
+    #[must_use]
+    pub fn state(&self) -> DeserializerState {
+        match self {
+            InplaceDeserializeSeed::None => DeserializerState::None,
+            InplaceDeserializeSeed::Value(_) => DeserializerState::Value,
+            InplaceDeserializeSeed::DeserializeSeed(_) => DeserializerState::DeserializeSeed,
+        }
+    }
+
+    #[cold]
+    fn wrong_state(&self, operation: &'static str) -> DeserializerError {
+        DeserializerError::WrongState {
+            operation,
+            found: self.state(),
+        }
+    }
+
+    /// Puts back the `state` taken out for `operation`, and returns the error of the misuse.
+    #[cold]
+    fn restore(&mut self, state: Self, operation: &'static str) -> DeserializerError {
+        let error = state.wrong_state(operation);
+        *self = state;
+        error
+    }
+
+    /// Returns the deserialized value, or `None` if the deserialization has not succeeded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::marker::PhantomData;
+    /// # use serde_dyn::Deserializer;
+    /// # use serde_dyn::de::{DeserializeSeed, InplaceDeserializeSeed};
+    /// let mut seed = InplaceDeserializeSeed::DeserializeSeed(PhantomData::<(u8, bool)>);
+    /// let mut deserializer = serde_json::Deserializer::from_str("[7,true]");
+    /// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+    /// seed.dyn_deserialize(&mut deserializer).unwrap();
+    /// assert_eq!(seed.into_value(), Some((7, true)));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn into_value(self) -> Option<T::Value> {
+        match self {
+            InplaceDeserializeSeed::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
     fn expect_err<U>(&self, result: DeserializerResult<U>) -> DeserializeError {
         debug_assert!(matches!(
             self,
@@ -1992,11 +3453,13 @@ impl<'de, T: serde::de::DeserializeSeed<'de>> DeserializeSeed<'de>
         &mut self,
         deserializer: &mut dyn Deserializer<'de>,
     ) -> DeserializeResult<()> {
-        if let InplaceDeserializeSeed::DeserializeSeed(seed) = mem::take(self) {
-            seed.deserialize(deserializer)
-                .map(|value| *self = InplaceDeserializeSeed::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::DeserializeSeed))
+        match mem::take(self) {
+            InplaceDeserializeSeed::DeserializeSeed(seed) => seed
+                .deserialize(deserializer)
+                .map(|value| *self = InplaceDeserializeSeed::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "DeserializeSeed::dyn_deserialize"),
+            )),
         }
     }
 }
@@ -2016,6 +3479,32 @@ pub enum InplaceVisitor<'de, V: serde::de::Visitor<'de>> {
 }
 
 impl<'de, V: serde::de::Visitor<'de>> InplaceVisitor<'de, V> {
+    /// Returns the current state of the visitor.
+    #[must_use]
+    pub fn state(&self) -> DeserializerState {
+        match self {
+            InplaceVisitor::None => DeserializerState::None,
+            InplaceVisitor::Value(_) => DeserializerState::Value,
+            InplaceVisitor::Visitor(_) => DeserializerState::Visitor,
+        }
+    }
+
+    #[cold]
+    fn wrong_state(&self, operation: &'static str) -> DeserializerError {
+        DeserializerError::WrongState {
+            operation,
+            found: self.state(),
+        }
+    }
+
+    /// Puts back the `state` taken out for `operation`, and returns the error of the misuse.
+    #[cold]
+    fn restore(&mut self, state: Self, operation: &'static str) -> DeserializerError {
+        let error = state.wrong_state(operation);
+        *self = state;
+        error
+    }
+
     fn expect_err(&self, result: DeserializerResult<()>) -> DeserializeError {
         debug_assert!(matches!(
             self,
@@ -2039,214 +3528,235 @@ impl<'de, V: serde::de::Visitor<'de>> Visitor<'de> for InplaceVisitor<'de, V> {
     }
 
     fn dyn_visit_bool(&mut self, v: bool) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_bool(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_bool"),
+            )),
         }
     }
 
     fn dyn_visit_i8(&mut self, v: i8) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_i8(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_i8"),
+            )),
         }
     }
 
     fn dyn_visit_i16(&mut self, v: i16) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_i16(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_i16"),
+            )),
         }
     }
 
     fn dyn_visit_i32(&mut self, v: i32) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_i32(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_i32"),
+            )),
         }
     }
 
     fn dyn_visit_i64(&mut self, v: i64) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_i64(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_i64"),
+            )),
         }
     }
 
     fn dyn_visit_i128(&mut self, v: i128) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_i128(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_i128"),
+            )),
         }
     }
 
     fn dyn_visit_u8(&mut self, v: u8) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_u8(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_u8"),
+            )),
         }
     }
 
     fn dyn_visit_u16(&mut self, v: u16) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_u16(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_u16"),
+            )),
         }
     }
 
     fn dyn_visit_u32(&mut self, v: u32) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_u32(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_u32"),
+            )),
         }
     }
 
     fn dyn_visit_u64(&mut self, v: u64) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_u64(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_u64"),
+            )),
         }
     }
 
     fn dyn_visit_u128(&mut self, v: u128) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_u128(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_u128"),
+            )),
         }
     }
 
     fn dyn_visit_f32(&mut self, v: f32) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_f32(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_f32"),
+            )),
         }
     }
 
     fn dyn_visit_f64(&mut self, v: f64) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_f64(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_f64"),
+            )),
         }
     }
 
     fn dyn_visit_char(&mut self, v: char) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_char(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_char"),
+            )),
         }
     }
 
     fn dyn_visit_str(&mut self, v: &str) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_str(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_str"),
+            )),
         }
     }
 
     fn dyn_visit_borrowed_str(&mut self, v: &'de str) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_borrowed_str(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_borrowed_str"),
+            )),
         }
     }
 
     #[cfg(any(feature = "std", feature = "alloc"))]
     fn dyn_visit_string(&mut self, v: String) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_string(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_string"),
+            )),
         }
     }
 
     fn dyn_visit_bytes(&mut self, v: &[u8]) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_bytes(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_bytes"),
+            )),
         }
     }
 
     fn dyn_visit_borrowed_bytes(&mut self, v: &'de [u8]) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_borrowed_bytes(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_borrowed_bytes"),
+            )),
         }
     }
 
     #[cfg(any(feature = "std", feature = "alloc"))]
     fn dyn_visit_byte_buf(&mut self, v: Vec<u8>) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_byte_buf(v)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_byte_buf"),
+            )),
         }
     }
 
     fn dyn_visit_none(&mut self) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_none()
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_none"),
+            )),
         }
     }
 
@@ -2254,22 +3764,24 @@ impl<'de, V: serde::de::Visitor<'de>> Visitor<'de> for InplaceVisitor<'de, V> {
         &mut self,
         deserializer: &mut dyn Deserializer<'de>,
     ) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_some(deserializer)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_some"),
+            )),
         }
     }
 
     fn dyn_visit_unit(&mut self) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_unit()
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_unit"),
+            )),
         }
     }
 
@@ -2277,42 +3789,46 @@ impl<'de, V: serde::de::Visitor<'de>> Visitor<'de> for InplaceVisitor<'de, V> {
         &mut self,
         deserializer: &mut dyn Deserializer<'de>,
     ) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_newtype_struct(deserializer)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_newtype_struct"),
+            )),
         }
     }
 
     fn dyn_visit_seq(&mut self, seq: &mut dyn SeqAccess<'de>) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_seq(seq)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_seq"),
+            )),
         }
     }
 
     fn dyn_visit_map(&mut self, map: &mut dyn MapAccess<'de>) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_map(map)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_map"),
+            )),
         }
     }
 
     fn dyn_visit_enum(&mut self, data: &mut dyn EnumAccess<'de>) -> DeserializeResult<()> {
-        if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-            visitor
+        match mem::take(self) {
+            InplaceVisitor::Visitor(visitor) => visitor
                 .visit_enum(data)
-                .map(|value| *self = InplaceVisitor::Value(value))
-        } else {
-            Err(DeserializeError::from(DeserializerError::Visitor))
+                .map(|value| *self = InplaceVisitor::Value(value)),
+            state => Err(DeserializeError::from(
+                self.restore(state, "Visitor::dyn_visit_enum"),
+            )),
         }
     }
 }
@@ -2329,6 +3845,23 @@ pub enum InplaceSeqAccess<'de, A: serde::de::SeqAccess<'de>> {
 }
 
 impl<'de, A: serde::de::SeqAccess<'de>> InplaceSeqAccess<'de, A> {
+    /// Returns the current state of the sequence access.
+    #[must_use]
+    pub fn state(&self) -> DeserializerState {
+        match self {
+            InplaceSeqAccess::Error(_) => DeserializerState::Error,
+            InplaceSeqAccess::SeqAccess(_) => DeserializerState::SeqAccess,
+        }
+    }
+
+    #[cold]
+    fn wrong_state(&self, operation: &'static str) -> DeserializerError {
+        DeserializerError::WrongState {
+            operation,
+            found: self.state(),
+        }
+    }
+
     fn write_error(&mut self, error: A::Error) -> DeserializerError {
         *self = InplaceSeqAccess::Error(error);
         DeserializerError::Error
@@ -2345,7 +3878,7 @@ impl<'de, A: serde::de::SeqAccess<'de>> SeqAccess<'de> for InplaceSeqAccess<'de,
                 .next_element_seed(seed)
                 .map_err(|error| self.write_error(error))
         } else {
-            Err(DeserializerError::SeqAccess)
+            Err(self.wrong_state("SeqAccess::dyn_next_element"))
         }
     }
 
@@ -2369,6 +3902,23 @@ pub enum InplaceMapAccess<'de, A: serde::de::MapAccess<'de>> {
 }
 
 impl<'de, A: serde::de::MapAccess<'de>> InplaceMapAccess<'de, A> {
+    /// Returns the current state of the map access.
+    #[must_use]
+    pub fn state(&self) -> DeserializerState {
+        match self {
+            InplaceMapAccess::Error(_) => DeserializerState::Error,
+            InplaceMapAccess::MapAccess(_) => DeserializerState::MapAccess,
+        }
+    }
+
+    #[cold]
+    fn wrong_state(&self, operation: &'static str) -> DeserializerError {
+        DeserializerError::WrongState {
+            operation,
+            found: self.state(),
+        }
+    }
+
     fn write_error(&mut self, error: A::Error) -> DeserializerError {
         *self = InplaceMapAccess::Error(error);
         DeserializerError::Error
@@ -2385,7 +3935,7 @@ impl<'de, A: serde::de::MapAccess<'de>> MapAccess<'de> for InplaceMapAccess<'de,
                 .next_key_seed(seed)
                 .map_err(|error| self.write_error(error))
         } else {
-            Err(DeserializerError::MapAccess)
+            Err(self.wrong_state("MapAccess::dyn_next_key"))
         }
     }
 
@@ -2395,7 +3945,7 @@ impl<'de, A: serde::de::MapAccess<'de>> MapAccess<'de> for InplaceMapAccess<'de,
                 .next_value_seed(seed)
                 .map_err(|error| self.write_error(error))
         } else {
-            Err(DeserializerError::MapAccess)
+            Err(self.wrong_state("MapAccess::dyn_next_value"))
         }
     }
 
@@ -2409,7 +3959,7 @@ impl<'de, A: serde::de::MapAccess<'de>> MapAccess<'de> for InplaceMapAccess<'de,
                 .next_entry_seed(kseed, vseed)
                 .map_err(|error| self.write_error(error))
         } else {
-            Err(DeserializerError::MapAccess)
+            Err(self.wrong_state("MapAccess::dyn_next_entry"))
         }
     }
 
@@ -2438,6 +3988,33 @@ pub enum InplaceEnumAccess<'de, A: serde::de::EnumAccess<'de>> {
 }
 
 impl<'de, A: serde::de::EnumAccess<'de>> InplaceEnumAccess<'de, A> {
+    /// Returns the current state of the enum access.
+    #[must_use]
+    pub fn state(&self) -> DeserializerState {
+        match self {
+            InplaceEnumAccess::None => DeserializerState::None,
+            InplaceEnumAccess::Error(_) => DeserializerState::Error,
+            InplaceEnumAccess::EnumAccess(_) => DeserializerState::EnumAccess,
+            InplaceEnumAccess::VariantAccess(_) => DeserializerState::VariantAccess,
+        }
+    }
+
+    #[cold]
+    fn wrong_state(&self, operation: &'static str) -> DeserializerError {
+        DeserializerError::WrongState {
+            operation,
+            found: self.state(),
+        }
+    }
+
+    /// Puts back the `state` taken out for `operation`, and returns the error of the misuse.
+    #[cold]
+    fn restore(&mut self, state: Self, operation: &'static str) -> DeserializerError {
+        let error = state.wrong_state(operation);
+        *self = state;
+        error
+    }
+
     fn write_error(&mut self, error: A::Error) -> DeserializerError {
         *self = InplaceEnumAccess::Error(error);
         DeserializerError::Error
@@ -2449,16 +4026,15 @@ impl<'de, A: serde::de::EnumAccess<'de>> EnumAccess<'de> for InplaceEnumAccess<'
         &mut self,
         seed: &mut dyn DeserializeSeed<'de>,
     ) -> DeserializerResult<((), &mut dyn VariantAccess<'de>)> {
-        if let InplaceEnumAccess::EnumAccess(access) = mem::take(self) {
-            match access.variant_seed(seed) {
+        match mem::take(self) {
+            InplaceEnumAccess::EnumAccess(access) => match access.variant_seed(seed) {
                 Ok(((), variant)) => {
                     *self = InplaceEnumAccess::VariantAccess(variant);
                     Ok(((), self))
                 }
                 Err(error) => Err(self.write_error(error)),
-            }
-        } else {
-            Err(DeserializerError::EnumAccess)
+            },
+            state => Err(self.restore(state, "EnumAccess::dyn_variant_seed")),
         }
     }
 }
@@ -2467,12 +4043,11 @@ impl<'de, A: serde::de::EnumAccess<'de>> VariantAccess<'de> for InplaceEnumAcces
     fn dyn_unit_variant(&mut self) -> DeserializerResult<()> {
         use serde::de::VariantAccess;
 
-        if let InplaceEnumAccess::VariantAccess(access) = mem::take(self) {
-            access
+        match mem::take(self) {
+            InplaceEnumAccess::VariantAccess(access) => access
                 .unit_variant()
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::VariantAccess)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "VariantAccess::dyn_unit_variant")),
         }
     }
 
@@ -2482,12 +4057,11 @@ impl<'de, A: serde::de::EnumAccess<'de>> VariantAccess<'de> for InplaceEnumAcces
     ) -> DeserializerResult<()> {
         use serde::de::VariantAccess;
 
-        if let InplaceEnumAccess::VariantAccess(access) = mem::take(self) {
-            access
+        match mem::take(self) {
+            InplaceEnumAccess::VariantAccess(access) => access
                 .newtype_variant_seed(seed)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::VariantAccess)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "VariantAccess::dyn_newtype_variant")),
         }
     }
 
@@ -2498,12 +4072,11 @@ impl<'de, A: serde::de::EnumAccess<'de>> VariantAccess<'de> for InplaceEnumAcces
     ) -> DeserializerResult<()> {
         use serde::de::VariantAccess;
 
-        if let InplaceEnumAccess::VariantAccess(access) = mem::take(self) {
-            access
+        match mem::take(self) {
+            InplaceEnumAccess::VariantAccess(access) => access
                 .tuple_variant(len, visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::VariantAccess)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "VariantAccess::dyn_tuple_variant")),
         }
     }
 
@@ -2514,12 +4087,11 @@ impl<'de, A: serde::de::EnumAccess<'de>> VariantAccess<'de> for InplaceEnumAcces
     ) -> DeserializerResult<()> {
         use serde::de::VariantAccess;
 
-        if let InplaceEnumAccess::VariantAccess(access) = mem::take(self) {
-            access
+        match mem::take(self) {
+            InplaceEnumAccess::VariantAccess(access) => access
                 .struct_variant(fields, visitor)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(DeserializerError::VariantAccess)
+                .map_err(|error| self.write_error(error)),
+            state => Err(self.restore(state, "VariantAccess::dyn_struct_variant")),
         }
     }
 }
@@ -0,0 +1,401 @@
+use core::fmt;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+use serde::de::Unexpected;
+
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::boxed::Box;
+
+use super::{DeserializeResult, DeserializeSeed, Deserializer};
+#[cfg(any(feature = "std", feature = "alloc"))]
+use super::{EnumAccess, MapAccess, SeqAccess, Visitor};
+
+/// A [`Visitor`] assembled from closures.
+///
+/// Each closure handles one kind of input and produces the value, which is taken out by
+/// [`FnVisitor::into_value`] afterwards. The other methods of [`Visitor`] forward to them by
+/// default, for example, `dyn_visit_u8` to the closure of [`FnVisitor::on_u64`]. The input
+/// without a closure is rejected with an "invalid type" error mentioning what the visitor
+/// expects.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::de::Error;
+/// # use serde_dyn::Deserializer;
+/// # use serde_dyn::de::{DeserializeError, FnVisitor};
+/// let mut visitor = FnVisitor::new("a port number")
+///     .on_u64(|v| u16::try_from(v).map_err(DeserializeError::custom))
+///     .on_str(|s| s.parse().map_err(DeserializeError::custom));
+///
+/// let mut json = serde_json::Deserializer::from_str("\"8080\"");
+/// let mut deserializer = <dyn Deserializer>::new(&mut json);
+/// deserializer.dyn_deserialize_any(&mut visitor).unwrap();
+/// assert_eq!(visitor.into_value(), Some(8080));
+/// ```
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[allow(clippy::type_complexity)]
+pub struct FnVisitor<'a, 'de, T> {
+    expecting: &'a str,
+    value: Option<T>,
+    on_bool: Option<Box<dyn FnMut(bool) -> DeserializeResult<T> + 'a>>,
+    on_i64: Option<Box<dyn FnMut(i64) -> DeserializeResult<T> + 'a>>,
+    on_i128: Option<Box<dyn FnMut(i128) -> DeserializeResult<T> + 'a>>,
+    on_u64: Option<Box<dyn FnMut(u64) -> DeserializeResult<T> + 'a>>,
+    on_u128: Option<Box<dyn FnMut(u128) -> DeserializeResult<T> + 'a>>,
+    on_f64: Option<Box<dyn FnMut(f64) -> DeserializeResult<T> + 'a>>,
+    on_str: Option<Box<dyn FnMut(&str) -> DeserializeResult<T> + 'a>>,
+    on_bytes: Option<Box<dyn FnMut(&[u8]) -> DeserializeResult<T> + 'a>>,
+    on_none: Option<Box<dyn FnMut() -> DeserializeResult<T> + 'a>>,
+    on_some: Option<Box<dyn FnMut(&mut dyn Deserializer<'de>) -> DeserializeResult<T> + 'a>>,
+    on_unit: Option<Box<dyn FnMut() -> DeserializeResult<T> + 'a>>,
+    on_newtype_struct:
+        Option<Box<dyn FnMut(&mut dyn Deserializer<'de>) -> DeserializeResult<T> + 'a>>,
+    on_seq: Option<Box<dyn FnMut(&mut dyn SeqAccess<'de>) -> DeserializeResult<T> + 'a>>,
+    on_map: Option<Box<dyn FnMut(&mut dyn MapAccess<'de>) -> DeserializeResult<T> + 'a>>,
+    on_enum: Option<Box<dyn FnMut(&mut dyn EnumAccess<'de>) -> DeserializeResult<T> + 'a>>,
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a, 'de, T> FnVisitor<'a, 'de, T> {
+    /// Creates a visitor without any closures, which expects the input described by
+    /// `expecting`, such as "a port number".
+    #[must_use]
+    pub fn new(expecting: &'a str) -> Self {
+        FnVisitor {
+            expecting,
+            value: None,
+            on_bool: None,
+            on_i64: None,
+            on_i128: None,
+            on_u64: None,
+            on_u128: None,
+            on_f64: None,
+            on_str: None,
+            on_bytes: None,
+            on_none: None,
+            on_some: None,
+            on_unit: None,
+            on_newtype_struct: None,
+            on_seq: None,
+            on_map: None,
+            on_enum: None,
+        }
+    }
+
+    /// Handles a boolean.
+    #[must_use]
+    pub fn on_bool(mut self, f: impl FnMut(bool) -> DeserializeResult<T> + 'a) -> Self {
+        self.on_bool = Some(Box::new(f));
+        self
+    }
+
+    /// Handles a signed integer, as well as an `i128` that fits in `i64` unless
+    /// [`FnVisitor::on_i128`] is given.
+    #[must_use]
+    pub fn on_i64(mut self, f: impl FnMut(i64) -> DeserializeResult<T> + 'a) -> Self {
+        self.on_i64 = Some(Box::new(f));
+        self
+    }
+
+    /// Handles an `i128`.
+    #[must_use]
+    pub fn on_i128(mut self, f: impl FnMut(i128) -> DeserializeResult<T> + 'a) -> Self {
+        self.on_i128 = Some(Box::new(f));
+        self
+    }
+
+    /// Handles an unsigned integer, as well as a `u128` that fits in `u64` unless
+    /// [`FnVisitor::on_u128`] is given.
+    #[must_use]
+    pub fn on_u64(mut self, f: impl FnMut(u64) -> DeserializeResult<T> + 'a) -> Self {
+        self.on_u64 = Some(Box::new(f));
+        self
+    }
+
+    /// Handles a `u128`.
+    #[must_use]
+    pub fn on_u128(mut self, f: impl FnMut(u128) -> DeserializeResult<T> + 'a) -> Self {
+        self.on_u128 = Some(Box::new(f));
+        self
+    }
+
+    /// Handles a floating point number.
+    #[must_use]
+    pub fn on_f64(mut self, f: impl FnMut(f64) -> DeserializeResult<T> + 'a) -> Self {
+        self.on_f64 = Some(Box::new(f));
+        self
+    }
+
+    /// Handles a string or a character, whether it's borrowed or owned.
+    #[must_use]
+    pub fn on_str(mut self, f: impl FnMut(&str) -> DeserializeResult<T> + 'a) -> Self {
+        self.on_str = Some(Box::new(f));
+        self
+    }
+
+    /// Handles a byte array, whether it's borrowed or owned.
+    #[must_use]
+    pub fn on_bytes(mut self, f: impl FnMut(&[u8]) -> DeserializeResult<T> + 'a) -> Self {
+        self.on_bytes = Some(Box::new(f));
+        self
+    }
+
+    /// Handles an optional that is absent.
+    #[must_use]
+    pub fn on_none(mut self, f: impl FnMut() -> DeserializeResult<T> + 'a) -> Self {
+        self.on_none = Some(Box::new(f));
+        self
+    }
+
+    /// Handles an optional that is present, whose content is read from the given deserializer.
+    #[must_use]
+    pub fn on_some(
+        mut self,
+        f: impl FnMut(&mut dyn Deserializer<'de>) -> DeserializeResult<T> + 'a,
+    ) -> Self {
+        self.on_some = Some(Box::new(f));
+        self
+    }
+
+    /// Handles a unit `()`.
+    #[must_use]
+    pub fn on_unit(mut self, f: impl FnMut() -> DeserializeResult<T> + 'a) -> Self {
+        self.on_unit = Some(Box::new(f));
+        self
+    }
+
+    /// Handles a newtype struct, whose content is read from the given deserializer.
+    #[must_use]
+    pub fn on_newtype_struct(
+        mut self,
+        f: impl FnMut(&mut dyn Deserializer<'de>) -> DeserializeResult<T> + 'a,
+    ) -> Self {
+        self.on_newtype_struct = Some(Box::new(f));
+        self
+    }
+
+    /// Handles a sequence of elements.
+    #[must_use]
+    pub fn on_seq(
+        mut self,
+        f: impl FnMut(&mut dyn SeqAccess<'de>) -> DeserializeResult<T> + 'a,
+    ) -> Self {
+        self.on_seq = Some(Box::new(f));
+        self
+    }
+
+    /// Handles a key-value map.
+    #[must_use]
+    pub fn on_map(
+        mut self,
+        f: impl FnMut(&mut dyn MapAccess<'de>) -> DeserializeResult<T> + 'a,
+    ) -> Self {
+        self.on_map = Some(Box::new(f));
+        self
+    }
+
+    /// Handles an enum.
+    #[must_use]
+    pub fn on_enum(
+        mut self,
+        f: impl FnMut(&mut dyn EnumAccess<'de>) -> DeserializeResult<T> + 'a,
+    ) -> Self {
+        self.on_enum = Some(Box::new(f));
+        self
+    }
+
+    /// Returns the value produced by the last visit, or `None` if nothing has been visited
+    /// successfully.
+    #[must_use]
+    pub fn into_value(self) -> Option<T> {
+        self.value
+    }
+
+    /// Stores the value produced by a closure, or rejects the input without a closure.
+    fn produce(
+        &mut self,
+        result: Option<DeserializeResult<T>>,
+        unexp: Unexpected<'_>,
+    ) -> DeserializeResult<()> {
+        match result {
+            Some(result) => {
+                self.value = Some(result?);
+                Ok(())
+            }
+            None => Err(super::invalid_type(self, unexp)),
+        }
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<T: fmt::Debug> fmt::Debug for FnVisitor<'_, '_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FnVisitor")
+            .field("expecting", &self.expecting)
+            .field("value", &self.value)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'de, T> Visitor<'de> for FnVisitor<'_, 'de, T> {
+    fn dyn_expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.expecting)
+    }
+
+    fn dyn_visit_bool(&mut self, v: bool) -> DeserializeResult<()> {
+        let result = self.on_bool.as_mut().map(|f| f(v));
+        self.produce(result, Unexpected::Bool(v))
+    }
+
+    fn dyn_visit_i64(&mut self, v: i64) -> DeserializeResult<()> {
+        let result = self.on_i64.as_mut().map(|f| f(v));
+        self.produce(result, Unexpected::Signed(v))
+    }
+
+    fn dyn_visit_i128(&mut self, v: i128) -> DeserializeResult<()> {
+        match (&mut self.on_i128, i64::try_from(v)) {
+            (Some(f), _) => {
+                let result = f(v);
+                self.produce(Some(result), Unexpected::Other("128-bit integer"))
+            }
+            (None, Ok(v)) => self.dyn_visit_i64(v),
+            (None, Err(_)) => self.produce(None, Unexpected::Other("128-bit integer")),
+        }
+    }
+
+    fn dyn_visit_u64(&mut self, v: u64) -> DeserializeResult<()> {
+        let result = self.on_u64.as_mut().map(|f| f(v));
+        self.produce(result, Unexpected::Unsigned(v))
+    }
+
+    fn dyn_visit_u128(&mut self, v: u128) -> DeserializeResult<()> {
+        match (&mut self.on_u128, u64::try_from(v)) {
+            (Some(f), _) => {
+                let result = f(v);
+                self.produce(Some(result), Unexpected::Other("128-bit integer"))
+            }
+            (None, Ok(v)) => self.dyn_visit_u64(v),
+            (None, Err(_)) => self.produce(None, Unexpected::Other("128-bit integer")),
+        }
+    }
+
+    fn dyn_visit_f64(&mut self, v: f64) -> DeserializeResult<()> {
+        let result = self.on_f64.as_mut().map(|f| f(v));
+        self.produce(result, Unexpected::Float(v))
+    }
+
+    fn dyn_visit_str(&mut self, v: &str) -> DeserializeResult<()> {
+        let result = self.on_str.as_mut().map(|f| f(v));
+        self.produce(result, Unexpected::Str(v))
+    }
+
+    fn dyn_visit_bytes(&mut self, v: &[u8]) -> DeserializeResult<()> {
+        let result = self.on_bytes.as_mut().map(|f| f(v));
+        self.produce(result, Unexpected::Bytes(v))
+    }
+
+    fn dyn_visit_none(&mut self) -> DeserializeResult<()> {
+        let result = self.on_none.as_mut().map(|f| f());
+        self.produce(result, Unexpected::Option)
+    }
+
+    fn dyn_visit_some(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        let result = self.on_some.as_mut().map(|f| f(deserializer));
+        self.produce(result, Unexpected::Option)
+    }
+
+    fn dyn_visit_unit(&mut self) -> DeserializeResult<()> {
+        let result = self.on_unit.as_mut().map(|f| f());
+        self.produce(result, Unexpected::Unit)
+    }
+
+    fn dyn_visit_newtype_struct(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        let result = self.on_newtype_struct.as_mut().map(|f| f(deserializer));
+        self.produce(result, Unexpected::NewtypeStruct)
+    }
+
+    fn dyn_visit_seq(&mut self, seq: &mut dyn SeqAccess<'de>) -> DeserializeResult<()> {
+        let result = self.on_seq.as_mut().map(|f| f(seq));
+        self.produce(result, Unexpected::Seq)
+    }
+
+    fn dyn_visit_map(&mut self, map: &mut dyn MapAccess<'de>) -> DeserializeResult<()> {
+        let result = self.on_map.as_mut().map(|f| f(map));
+        self.produce(result, Unexpected::Map)
+    }
+
+    fn dyn_visit_enum(&mut self, data: &mut dyn EnumAccess<'de>) -> DeserializeResult<()> {
+        let result = self.on_enum.as_mut().map(|f| f(data));
+        self.produce(result, Unexpected::Enum)
+    }
+}
+
+/// A [`DeserializeSeed`] calling a closure with the dynamic deserializer.
+///
+/// The value produced by the closure is taken out by [`FnSeed::into_value`] afterwards.
+///
+/// # Examples
+///
+/// ```
+/// # use serde_dyn::Deserializer;
+/// # use serde_dyn::de::{DeserializeSeed, FnSeed};
+/// let mut seed = FnSeed::new(|deserializer| {
+///     <(u8, bool) as serde::Deserialize>::deserialize(deserializer)
+/// });
+///
+/// let mut json = serde_json::Deserializer::from_str("[7,true]");
+/// let mut deserializer = <dyn Deserializer>::new(&mut json);
+/// seed.dyn_deserialize(&mut deserializer).unwrap();
+/// assert_eq!(seed.into_value(), Some((7, true)));
+/// ```
+pub struct FnSeed<F, T> {
+    f: F,
+    value: Option<T>,
+}
+
+impl<F, T> FnSeed<F, T> {
+    /// Creates a seed calling `f`.
+    #[must_use]
+    pub fn new<'de>(f: F) -> Self
+    where
+        F: FnMut(&mut dyn Deserializer<'de>) -> DeserializeResult<T>,
+    {
+        FnSeed { f, value: None }
+    }
+
+    /// Returns the value produced by the last call, or `None` if the closure has not been called
+    /// successfully.
+    #[must_use]
+    pub fn into_value(self) -> Option<T> {
+        self.value
+    }
+}
+
+impl<F, T: fmt::Debug> fmt::Debug for FnSeed<F, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FnSeed")
+            .field("value", &self.value)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de, F, T> DeserializeSeed<'de> for FnSeed<F, T>
+where
+    F: FnMut(&mut dyn Deserializer<'de>) -> DeserializeResult<T>,
+{
+    fn dyn_deserialize(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        self.value = Some((self.f)(deserializer)?);
+        Ok(())
+    }
+}
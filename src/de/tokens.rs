@@ -0,0 +1,521 @@
+use serde::de::Error as _;
+
+use super::{
+    DeserializeError, DeserializeResult, DeserializeSeed, Deserializer, DeserializerError,
+    DeserializerResult, DeserializerState, EnumAccess, MapAccess, SeqAccess, VariantAccess,
+    Visitor,
+};
+use crate::token::Token;
+
+/// A [`Deserializer`] that replays a list of [`Token`]s, as recorded by
+/// [`Recorder`](crate::ser::Recorder).
+///
+/// Every hint is ignored, and the tokens alone drive the visitor like
+/// [`Deserializer::dyn_deserialize_any`] does.
+///
+/// # Examples
+///
+/// ```
+/// # use serde_dyn::Deserializer;
+/// # use serde_dyn::de::Tokens;
+/// # use serde_dyn::token::Token;
+/// let tokens = [Token::Some, Token::Str("hello".into())];
+/// let mut deserializer = Tokens::new(&tokens);
+/// let deserializer: &mut dyn Deserializer = &mut deserializer;
+/// let value: Option<&str> = serde::Deserialize::deserialize(deserializer).unwrap();
+/// assert_eq!(value, Some("hello"));
+/// ```
+#[derive(Debug)]
+pub struct Tokens<'de> {
+    tokens: &'de [Token],
+    is_human_readable: bool,
+    /// The name of the variant that is deserialized next, see [`TokensEnumAccess`].
+    variant: Option<&'static str>,
+    error: Option<DeserializeError>,
+}
+
+impl<'de> Tokens<'de> {
+    /// Creates a human-readable deserializer replaying `tokens`.
+    #[must_use]
+    pub fn new(tokens: &'de [Token]) -> Self {
+        Tokens {
+            tokens,
+            is_human_readable: true,
+            variant: None,
+            error: None,
+        }
+    }
+
+    /// Sets whether the deserializer asks for the human-readable form, see
+    /// [`Deserializer::dyn_is_human_readable`].
+    #[must_use]
+    pub fn with_human_readable(mut self, is_human_readable: bool) -> Self {
+        self.is_human_readable = is_human_readable;
+        self
+    }
+
+    /// Returns the tokens that are not replayed yet.
+    #[must_use]
+    pub fn remaining(&self) -> &'de [Token] {
+        self.tokens
+    }
+
+    /// Returns the first error of the deserialization.
+    ///
+    /// The dynamic methods only report [`DeserializerError::Error`],
+    /// so the original error is kept here. An error may also be kept after the value has been
+    /// produced, for example if the tokens of a tuple don't end where the tuple does.
+    #[must_use]
+    pub fn error(&self) -> Option<&DeserializeError> {
+        self.error.as_ref()
+    }
+
+    fn write_error(&mut self, error: DeserializeError) -> DeserializerError {
+        self.error.get_or_insert(error);
+        DeserializerError::Error
+    }
+
+    fn visit(&mut self, result: DeserializeResult<()>) -> DeserializerResult<()> {
+        result.map_err(|error| self.write_error(error))
+    }
+
+    fn peek(&self) -> Option<&'de Token> {
+        self.tokens.first()
+    }
+
+    fn next(&mut self) -> DeserializerResult<&'de Token> {
+        match self.tokens.split_first() {
+            Some((token, tokens)) => {
+                self.tokens = tokens;
+                Ok(token)
+            }
+            None => Err(self.write_error(DeserializeError::custom("unexpected end of tokens"))),
+        }
+    }
+
+    /// Consumes the token that ends a compound value.
+    fn expect_end(&mut self, end: &Token) -> DeserializerResult<()> {
+        match self.next()? {
+            token if token == end => Ok(()),
+            token => Err(self.write_error(DeserializeError::custom(format_args!(
+                "expected token {end:?}, found {token:?}"
+            )))),
+        }
+    }
+
+    fn visit_seq(
+        &mut self,
+        visitor: &mut dyn Visitor<'de>,
+        len: Option<usize>,
+        end: Token,
+    ) -> DeserializerResult<()> {
+        let mut seq = TokensAccess {
+            tokens: self,
+            len,
+            end: &end,
+        };
+        let result = visitor.dyn_visit_seq(&mut seq);
+        self.visit(result)?;
+        self.expect_end(&end)
+    }
+
+    fn visit_map(
+        &mut self,
+        visitor: &mut dyn Visitor<'de>,
+        len: Option<usize>,
+        end: Token,
+    ) -> DeserializerResult<()> {
+        let mut map = TokensAccess {
+            tokens: self,
+            len,
+            end: &end,
+        };
+        let result = visitor.dyn_visit_map(&mut map);
+        self.visit(result)?;
+        self.skip_fields();
+        self.expect_end(&end)
+    }
+
+    fn skip_fields(&mut self) {
+        while let Some(Token::SkippedField(_)) = self.peek() {
+            self.tokens = &self.tokens[1..];
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> for Tokens<'de> {
+    fn dyn_deserialize_any(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        if let Some(variant) = self.variant.take() {
+            let result = visitor.dyn_visit_borrowed_str(variant);
+            return self.visit(result);
+        }
+
+        let result = match self.next()? {
+            Token::Bool(v) => visitor.dyn_visit_bool(*v),
+            Token::I8(v) => visitor.dyn_visit_i8(*v),
+            Token::I16(v) => visitor.dyn_visit_i16(*v),
+            Token::I32(v) => visitor.dyn_visit_i32(*v),
+            Token::I64(v) => visitor.dyn_visit_i64(*v),
+            Token::I128(v) => visitor.dyn_visit_i128(*v),
+            Token::U8(v) => visitor.dyn_visit_u8(*v),
+            Token::U16(v) => visitor.dyn_visit_u16(*v),
+            Token::U32(v) => visitor.dyn_visit_u32(*v),
+            Token::U64(v) => visitor.dyn_visit_u64(*v),
+            Token::U128(v) => visitor.dyn_visit_u128(*v),
+            Token::F32(v) => visitor.dyn_visit_f32(*v),
+            Token::F64(v) => visitor.dyn_visit_f64(*v),
+            Token::Char(v) => visitor.dyn_visit_char(*v),
+            Token::Str(v) => visitor.dyn_visit_borrowed_str(v),
+            Token::Bytes(v) => visitor.dyn_visit_borrowed_bytes(v),
+            Token::Field(v) => visitor.dyn_visit_borrowed_str(v),
+            Token::None => visitor.dyn_visit_none(),
+            Token::Some => visitor.dyn_visit_some(self),
+            Token::Unit | Token::UnitStruct(_) => visitor.dyn_visit_unit(),
+            Token::NewtypeStruct { .. } => visitor.dyn_visit_newtype_struct(self),
+            Token::Seq { len } => return self.visit_seq(visitor, *len, Token::SeqEnd),
+            Token::Tuple { len } => return self.visit_seq(visitor, Some(*len), Token::TupleEnd),
+            Token::TupleStruct { len, .. } => {
+                return self.visit_seq(visitor, Some(*len), Token::TupleStructEnd);
+            }
+            Token::Map { len } => return self.visit_map(visitor, *len, Token::MapEnd),
+            Token::Struct { len, .. } => {
+                return self.visit_map(visitor, Some(*len), Token::StructEnd);
+            }
+            token @ (Token::UnitVariant { .. }
+            | Token::NewtypeVariant { .. }
+            | Token::TupleVariant { .. }
+            | Token::StructVariant { .. }) => {
+                let mut data = TokensEnumAccess {
+                    tokens: self,
+                    token,
+                    variant: false,
+                };
+                visitor.dyn_visit_enum(&mut data)
+            }
+            token => Err(DeserializeError::custom(format_args!(
+                "unexpected token {token:?}"
+            ))),
+        };
+        self.visit(result)
+    }
+
+    fn dyn_deserialize_bool(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_i8(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_i16(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_i32(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_i64(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_128(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_u8(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_u16(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_u32(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_u64(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_u128(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_f32(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_f64(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_char(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_str(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_string(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_bytes(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_byte_buf(
+        &mut self,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_option(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_unit(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_unit_struct(
+        &mut self,
+        _name: &'static str,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_newtype_struct(
+        &mut self,
+        _name: &'static str,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_seq(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_tuple(
+        &mut self,
+        _len: usize,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_tuple_struct(
+        &mut self,
+        _name: &'static str,
+        _len: usize,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_map(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_struct(
+        &mut self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_enum(
+        &mut self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_identifier(
+        &mut self,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_deserialize_ignored_any(
+        &mut self,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.is_human_readable
+    }
+}
+
+/// Replays the elements of a sequence, or the entries of a map, until the end token.
+struct TokensAccess<'a, 'de> {
+    tokens: &'a mut Tokens<'de>,
+    /// The number of the remaining elements or entries, if known.
+    len: Option<usize>,
+    end: &'a Token,
+}
+
+impl<'de> TokensAccess<'_, 'de> {
+    fn next(&mut self, seed: &mut dyn DeserializeSeed<'de>) -> DeserializerResult<Option<()>> {
+        if self.tokens.peek() == Some(self.end) {
+            return Ok(None);
+        }
+        self.len = self.len.map(|len| len.saturating_sub(1));
+        self.value(seed).map(Some)
+    }
+
+    fn value(&mut self, seed: &mut dyn DeserializeSeed<'de>) -> DeserializerResult<()> {
+        let result = seed.dyn_deserialize(self.tokens);
+        self.tokens.visit(result)
+    }
+}
+
+impl<'de> SeqAccess<'de> for TokensAccess<'_, 'de> {
+    fn dyn_next_element(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> DeserializerResult<Option<()>> {
+        self.next(seed)
+    }
+
+    fn dyn_size_hint(&self) -> Option<usize> {
+        self.len
+    }
+}
+
+impl<'de> MapAccess<'de> for TokensAccess<'_, 'de> {
+    fn dyn_next_key(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> DeserializerResult<Option<()>> {
+        self.tokens.skip_fields();
+        self.next(seed)
+    }
+
+    fn dyn_next_value(&mut self, seed: &mut dyn DeserializeSeed<'de>) -> DeserializerResult<()> {
+        self.value(seed)
+    }
+
+    fn dyn_next_entry(
+        &mut self,
+        kseed: &mut dyn DeserializeSeed<'de>,
+        vseed: &mut dyn DeserializeSeed<'de>,
+    ) -> DeserializerResult<Option<((), ())>> {
+        match self.dyn_next_key(kseed)? {
+            Some(()) => self.value(vseed).map(|()| Some(((), ()))),
+            None => Ok(None),
+        }
+    }
+
+    fn dyn_size_hint(&self) -> Option<usize> {
+        self.len
+    }
+}
+
+/// Replays an enum, starting with one of the variant tokens.
+struct TokensEnumAccess<'a, 'de> {
+    tokens: &'a mut Tokens<'de>,
+    token: &'de Token,
+    /// Whether the name of the variant has been deserialized.
+    variant: bool,
+}
+
+impl TokensEnumAccess<'_, '_> {
+    fn unexpected(&mut self, expected: &str) -> DeserializerError {
+        let token = self.token;
+        self.tokens
+            .write_error(DeserializeError::custom(format_args!(
+                "expected {expected}, found token {token:?}"
+            )))
+    }
+}
+
+impl<'de> EnumAccess<'de> for TokensEnumAccess<'_, 'de> {
+    fn dyn_variant_seed(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> DeserializerResult<((), &mut dyn VariantAccess<'de>)> {
+        if self.variant {
+            return Err(DeserializerError::WrongState {
+                operation: "EnumAccess::dyn_variant_seed",
+                found: DeserializerState::VariantAccess,
+            });
+        }
+        let (Token::UnitVariant { variant, .. }
+        | Token::NewtypeVariant { variant, .. }
+        | Token::TupleVariant { variant, .. }
+        | Token::StructVariant { variant, .. }) = self.token
+        else {
+            unreachable!("`TokensEnumAccess` is only created for the variant tokens")
+        };
+        self.variant = true;
+        self.tokens.variant = Some(variant);
+        let result = seed.dyn_deserialize(self.tokens);
+        self.tokens.variant = None;
+        self.tokens.visit(result)?;
+        Ok(((), self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for TokensEnumAccess<'_, 'de> {
+    fn dyn_unit_variant(&mut self) -> DeserializerResult<()> {
+        match self.token {
+            Token::UnitVariant { .. } => Ok(()),
+            _ => Err(self.unexpected("a unit variant")),
+        }
+    }
+
+    fn dyn_newtype_variant(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> DeserializerResult<()> {
+        match self.token {
+            Token::NewtypeVariant { .. } => {
+                let result = seed.dyn_deserialize(self.tokens);
+                self.tokens.visit(result)
+            }
+            _ => Err(self.unexpected("a newtype variant")),
+        }
+    }
+
+    fn dyn_tuple_variant(
+        &mut self,
+        _len: usize,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        match self.token {
+            Token::TupleVariant { len, .. } => {
+                self.tokens
+                    .visit_seq(visitor, Some(*len), Token::TupleVariantEnd)
+            }
+            _ => Err(self.unexpected("a tuple variant")),
+        }
+    }
+
+    fn dyn_struct_variant(
+        &mut self,
+        _fields: &'static [&'static str],
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        match self.token {
+            Token::StructVariant { len, .. } => {
+                self.tokens
+                    .visit_map(visitor, Some(*len), Token::StructVariantEnd)
+            }
+            _ => Err(self.unexpected("a struct variant")),
+        }
+    }
+}
@@ -9,13 +9,37 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![deny(missing_docs)]
 #![deny(missing_debug_implementations)]
+#![forbid(unsafe_code)]
 
 #[cfg(all(not(feature = "std"), feature = "alloc"))]
 extern crate alloc;
 
 pub mod de;
+#[cfg(all(
+    not(any(feature = "std", feature = "alloc")),
+    feature = "heapless-errors"
+))]
+mod message;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub mod path;
+pub mod registry;
 pub mod ser;
+pub mod testing;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub mod token;
+mod transcode;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub mod value;
 
 // re-exports
 pub use crate::de::Deserializer;
 pub use crate::ser::{Serialize, Serializer};
+pub use crate::transcode::transcode;
+#[cfg(feature = "derive")]
+pub use serde_dyn_derive::serializable;
+
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    pub use serde;
+}
@@ -0,0 +1,71 @@
+//! # Inline Error Messages.
+//!
+//! Without `std` or `alloc`, the errors cannot own a `String`, so the `heapless-errors`
+//! feature renders the messages into a fixed-capacity buffer instead.
+
+use core::{fmt, str};
+
+/// The capacity of [`InlineMessage`] in bytes.
+pub(crate) const CAPACITY: usize = 64;
+
+/// A message written into an inline buffer, truncated to [`CAPACITY`] bytes.
+///
+/// The message is always cut at a character boundary, so the buffer is valid UTF-8.
+#[derive(Clone, Copy)]
+pub(crate) struct InlineMessage {
+    len: u8,
+    truncated: bool,
+    buf: [u8; CAPACITY],
+}
+
+impl InlineMessage {
+    /// Renders the message into a new buffer.
+    pub(crate) fn new<T: fmt::Display + ?Sized>(msg: &T) -> Self {
+        let mut message = InlineMessage {
+            len: 0,
+            truncated: false,
+            buf: [0; CAPACITY],
+        };
+        // Running out of space is recorded by `truncated`.
+        let _ = fmt::write(&mut message, format_args!("{msg}"));
+        message
+    }
+
+    /// Returns the message written so far.
+    pub(crate) fn as_str(&self) -> &str {
+        str::from_utf8(&self.buf[..usize::from(self.len)]).unwrap_or_default()
+    }
+}
+
+impl fmt::Write for InlineMessage {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let len = usize::from(self.len);
+        let mut n = s.len().min(CAPACITY - len);
+        while !s.is_char_boundary(n) {
+            n -= 1;
+        }
+        self.buf[len..len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n as u8;
+        if n < s.len() {
+            self.truncated = true;
+            return Err(fmt::Error);
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for InlineMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::Display for InlineMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())?;
+        if self.truncated {
+            f.write_str("...")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1423 @@
+//! # Path Tracking.
+//!
+//! This module records where a dynamic deserialization or serialization failed, such as
+//! `servers[3].tls.cert_path`, in the way [`serde_path_to_error`] does for static types.
+//!
+//! Tracking is opt-in: [`deserialize`] and [`serialize`] wrap every nested value on the way, and
+//! attach the [`Path`] to the returned error, see [`DeserializeError::path`] and
+//! [`SerializeError::path`].
+//!
+//! [`serde_path_to_error`]: https://docs.rs/serde_path_to_error
+//! [`DeserializeError::path`]: crate::de::DeserializeError::path
+//! [`SerializeError::path`]: crate::ser::SerializeError::path
+
+use core::cell::RefCell;
+use core::{error, fmt, mem, slice};
+
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::string::{String, ToString};
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::vec::Vec;
+
+use crate::de::{
+    DeserializeResult, DeserializeSeed, Deserializer, DeserializerError, DeserializerResult,
+    DeserializerState, EnumAccess, InplaceDeserializer, MapAccess, SeqAccess, VariantAccess,
+    Visitor,
+};
+use crate::ser::{
+    InplaceSerializer, Serialize, SerializeMap, SerializeResult, SerializeSeq, SerializeStruct,
+    SerializeStructVariant, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+    Serializer, SerializerError, SerializerResult, SerializerState,
+};
+
+/// Deserializes with the dynamic version of `deserializer`, while tracking the path.
+///
+/// This works like [`de::with_dyn`](crate::de::with_dyn), except that the error is always a
+/// [`DeserializeError`] carrying the path to the innermost value that failed. If the concrete
+/// deserializer fails, its error is kept as the source, see [`DeserializeError::downcast_ref`].
+///
+/// [`DeserializeError`]: crate::de::DeserializeError
+/// [`DeserializeError::downcast_ref`]: crate::de::DeserializeError::downcast_ref
+///
+/// # Examples
+///
+/// ```
+/// # use serde_dyn::Deserializer;
+/// #[derive(Debug, serde::Deserialize)]
+/// struct Config {
+///     servers: Vec<Server>,
+/// }
+///
+/// #[derive(Debug, serde::Deserialize)]
+/// struct Server {
+///     port: u16,
+/// }
+///
+/// let json = r#"{"servers":[{"port":80},{"port":"443"}]}"#;
+/// let mut deserializer = serde_json::Deserializer::from_str(json);
+/// let error = serde_dyn::path::deserialize(&mut deserializer, |deserializer| {
+///     <Config as serde::Deserialize>::deserialize(deserializer)
+/// })
+/// .unwrap_err();
+/// assert_eq!(error.path().unwrap().to_string(), "servers[1].port");
+/// assert_eq!(
+///     error.to_string(),
+///     "servers[1].port: invalid type: string \"443\", expected u16 at line 1 column 37"
+/// );
+/// ```
+pub fn deserialize<'de, D, T, F>(deserializer: D, f: F) -> DeserializeResult<T>
+where
+    D: serde::Deserializer<'de>,
+    D::Error: error::Error + Send + Sync + 'static,
+    F: FnOnce(&mut dyn Deserializer<'de>) -> DeserializeResult<T>,
+{
+    let track = Track::default();
+    let mut deserializer = InplaceDeserializer::Deserializer(deserializer);
+    let result = f(&mut TrackDeserializer::new(
+        &mut deserializer,
+        &track,
+        false,
+    ));
+    deserializer.into_result(result).map_err(|error| {
+        track.fail();
+        error.with_path(track.into_path())
+    })
+}
+
+/// Serializes with the dynamic version of `serializer`, while tracking the path.
+///
+/// This works like [`ser::with_dyn`](crate::ser::with_dyn), except that the error is always a
+/// [`SerializeError`] carrying the path to the innermost value that failed. If the concrete
+/// serializer fails, its error is kept as the source, see [`SerializeError::downcast_ref`].
+///
+/// [`SerializeError`]: crate::ser::SerializeError
+/// [`SerializeError::downcast_ref`]: crate::ser::SerializeError::downcast_ref
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use serde_dyn::Serialize;
+/// let mut inner = BTreeMap::new();
+/// inner.insert(vec![1], 2);
+/// let mut outer = BTreeMap::new();
+/// outer.insert("key", vec![inner]);
+///
+/// let error = serde_dyn::path::serialize(serde_json::value::Serializer, |serializer| {
+///     outer.dyn_serialize(serializer)
+/// })
+/// .unwrap_err();
+/// assert_eq!(error.to_string(), "key[0]: key must be a string");
+/// ```
+pub fn serialize<S, F>(serializer: S, f: F) -> SerializeResult<S::Ok>
+where
+    S: serde::Serializer,
+    S::Error: error::Error + Send + Sync + 'static,
+    F: FnOnce(&mut dyn Serializer) -> SerializeResult<()>,
+{
+    let track = Track::default();
+    let mut serializer = InplaceSerializer::Serializer(serializer);
+    let result = f(&mut TrackSerializer::new(&mut serializer, &track, false));
+    serializer.into_result(result).map_err(|error| {
+        track.fail();
+        error.with_path(track.into_path())
+    })
+}
+
+/// The path to the value where the failure happened, such as `servers[3].tls.cert_path`.
+///
+/// An empty path means the root value, and is displayed as `.`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Path {
+    segments: Vec<Segment>,
+}
+
+impl Path {
+    /// Returns an iterator over the segments, from the root to the failed value.
+    pub fn iter(&self) -> slice::Iter<'_, Segment> {
+        self.segments.iter()
+    }
+
+    /// Returns `true` if the failure happened at the root value.
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+}
+
+impl<'a> IntoIterator for &'a Path {
+    type Item = &'a Segment;
+    type IntoIter = slice::Iter<'a, Segment>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str(".");
+        }
+
+        let mut separator = "";
+        for segment in self {
+            if !matches!(segment, Segment::Seq { .. }) {
+                f.write_str(separator)?;
+            }
+            segment.fmt(f)?;
+            separator = ".";
+        }
+        Ok(())
+    }
+}
+
+/// A single step of a [`Path`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Segment {
+    /// An element of a sequence, a tuple or a tuple struct, displayed as `[index]`.
+    Seq {
+        /// The index of the element.
+        index: usize,
+    },
+    /// A value of a map or a field of a struct.
+    Map {
+        /// The key of the value.
+        key: String,
+    },
+    /// The content of an enum variant.
+    Enum {
+        /// The name of the variant.
+        variant: String,
+    },
+    /// A value whose key is neither a string, a boolean nor a number, displayed as `?`.
+    Unknown,
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Segment::Seq { index } => write!(f, "[{index}]"),
+            Segment::Map { key } => f.write_str(key),
+            Segment::Enum { variant } => f.write_str(variant),
+            Segment::Unknown => f.write_str("?"),
+        }
+    }
+}
+
+/// The state shared by all the wrappers of one tracking session.
+#[derive(Default)]
+struct Track {
+    /// The segments from the root to the current value.
+    stack: RefCell<Vec<Segment>>,
+    /// The path of the innermost failure.
+    failure: RefCell<Option<Path>>,
+    /// The last map key or variant name captured.
+    key: RefCell<Option<String>>,
+}
+
+impl Track {
+    fn enter<T, E>(&self, segment: Segment, f: impl FnOnce() -> Result<T, E>) -> Result<T, E> {
+        self.stack.borrow_mut().push(segment);
+        let result = f();
+        if result.is_err() {
+            self.fail();
+        }
+        self.stack.borrow_mut().pop();
+        result
+    }
+
+    /// Records the current path, unless a deeper failure has been recorded.
+    fn fail(&self) {
+        let mut failure = self.failure.borrow_mut();
+        if failure.is_none() {
+            *failure = Some(Path {
+                segments: self.stack.borrow().clone(),
+            });
+        }
+    }
+
+    fn capture(&self, key: &dyn fmt::Display) {
+        *self.key.borrow_mut() = Some(key.to_string());
+    }
+
+    fn segment(&self, f: impl FnOnce(String) -> Segment) -> Segment {
+        self.key.take().map_or(Segment::Unknown, f)
+    }
+
+    fn into_path(self) -> Path {
+        self.failure.into_inner().unwrap_or_default()
+    }
+}
+
+/// Wraps a dynamic deserializer, so that the nested values are tracked.
+struct TrackDeserializer<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    track: &'a Track,
+    /// Whether the visited scalar should be captured as a map key or a variant name.
+    capture: bool,
+}
+
+impl<'a, 'de> TrackDeserializer<'a, 'de> {
+    fn new(deserializer: &'a mut dyn Deserializer<'de>, track: &'a Track, capture: bool) -> Self {
+        TrackDeserializer {
+            deserializer,
+            track,
+            capture,
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> for TrackDeserializer<'_, 'de> {
+    fn dyn_deserialize_any(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_any(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_bool(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_bool(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_i8(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer.dyn_deserialize_i8(&mut TrackVisitor::new(
+            visitor,
+            self.track,
+            self.capture,
+        ))
+    }
+
+    fn dyn_deserialize_i16(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_i16(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_i32(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_i32(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_i64(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_i64(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_128(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_128(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_u8(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer.dyn_deserialize_u8(&mut TrackVisitor::new(
+            visitor,
+            self.track,
+            self.capture,
+        ))
+    }
+
+    fn dyn_deserialize_u16(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_u16(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_u32(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_u32(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_u64(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_u64(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_u128(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_u128(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_f32(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_f32(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_f64(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_f64(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_char(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_char(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_str(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_str(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_string(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_string(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_bytes(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_bytes(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_byte_buf(
+        &mut self,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_byte_buf(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_option(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_option(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_unit(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_unit(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_unit_struct(
+        &mut self,
+        name: &'static str,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.deserializer.dyn_deserialize_unit_struct(
+            name,
+            &mut TrackVisitor::new(visitor, self.track, self.capture),
+        )
+    }
+
+    fn dyn_deserialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.deserializer.dyn_deserialize_newtype_struct(
+            name,
+            &mut TrackVisitor::new(visitor, self.track, self.capture),
+        )
+    }
+
+    fn dyn_deserialize_seq(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_seq(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_tuple(
+        &mut self,
+        len: usize,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.deserializer.dyn_deserialize_tuple(
+            len,
+            &mut TrackVisitor::new(visitor, self.track, self.capture),
+        )
+    }
+
+    fn dyn_deserialize_tuple_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.deserializer.dyn_deserialize_tuple_struct(
+            name,
+            len,
+            &mut TrackVisitor::new(visitor, self.track, self.capture),
+        )
+    }
+
+    fn dyn_deserialize_map(&mut self, visitor: &mut dyn Visitor<'de>) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_map(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_struct(
+        &mut self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.deserializer.dyn_deserialize_struct(
+            name,
+            fields,
+            &mut TrackVisitor::new(visitor, self.track, self.capture),
+        )
+    }
+
+    fn dyn_deserialize_enum(
+        &mut self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.deserializer.dyn_deserialize_enum(
+            name,
+            variants,
+            &mut TrackVisitor::new(visitor, self.track, self.capture),
+        )
+    }
+
+    fn dyn_deserialize_identifier(
+        &mut self,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_identifier(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_deserialize_ignored_any(
+        &mut self,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        self.deserializer
+            .dyn_deserialize_ignored_any(&mut TrackVisitor::new(visitor, self.track, self.capture))
+    }
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+/// Wraps a dynamic visitor, so that the nested values are tracked.
+struct TrackVisitor<'a, 'de> {
+    visitor: &'a mut dyn Visitor<'de>,
+    track: &'a Track,
+    capture: bool,
+}
+
+impl<'a, 'de> TrackVisitor<'a, 'de> {
+    fn new(visitor: &'a mut dyn Visitor<'de>, track: &'a Track, capture: bool) -> Self {
+        TrackVisitor {
+            visitor,
+            track,
+            capture,
+        }
+    }
+
+    fn capture(&self, key: &dyn fmt::Display) {
+        if self.capture {
+            self.track.capture(key);
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for TrackVisitor<'_, 'de> {
+    fn dyn_expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.visitor.dyn_expecting(formatter)
+    }
+
+    fn dyn_visit_bool(&mut self, v: bool) -> DeserializeResult<()> {
+        self.capture(&v);
+        self.visitor.dyn_visit_bool(v)
+    }
+
+    fn dyn_visit_i8(&mut self, v: i8) -> DeserializeResult<()> {
+        self.capture(&v);
+        self.visitor.dyn_visit_i8(v)
+    }
+
+    fn dyn_visit_i16(&mut self, v: i16) -> DeserializeResult<()> {
+        self.capture(&v);
+        self.visitor.dyn_visit_i16(v)
+    }
+
+    fn dyn_visit_i32(&mut self, v: i32) -> DeserializeResult<()> {
+        self.capture(&v);
+        self.visitor.dyn_visit_i32(v)
+    }
+
+    fn dyn_visit_i64(&mut self, v: i64) -> DeserializeResult<()> {
+        self.capture(&v);
+        self.visitor.dyn_visit_i64(v)
+    }
+
+    fn dyn_visit_i128(&mut self, v: i128) -> DeserializeResult<()> {
+        self.capture(&v);
+        self.visitor.dyn_visit_i128(v)
+    }
+
+    fn dyn_visit_u8(&mut self, v: u8) -> DeserializeResult<()> {
+        self.capture(&v);
+        self.visitor.dyn_visit_u8(v)
+    }
+
+    fn dyn_visit_u16(&mut self, v: u16) -> DeserializeResult<()> {
+        self.capture(&v);
+        self.visitor.dyn_visit_u16(v)
+    }
+
+    fn dyn_visit_u32(&mut self, v: u32) -> DeserializeResult<()> {
+        self.capture(&v);
+        self.visitor.dyn_visit_u32(v)
+    }
+
+    fn dyn_visit_u64(&mut self, v: u64) -> DeserializeResult<()> {
+        self.capture(&v);
+        self.visitor.dyn_visit_u64(v)
+    }
+
+    fn dyn_visit_u128(&mut self, v: u128) -> DeserializeResult<()> {
+        self.capture(&v);
+        self.visitor.dyn_visit_u128(v)
+    }
+
+    fn dyn_visit_f32(&mut self, v: f32) -> DeserializeResult<()> {
+        self.capture(&v);
+        self.visitor.dyn_visit_f32(v)
+    }
+
+    fn dyn_visit_f64(&mut self, v: f64) -> DeserializeResult<()> {
+        self.capture(&v);
+        self.visitor.dyn_visit_f64(v)
+    }
+
+    fn dyn_visit_char(&mut self, v: char) -> DeserializeResult<()> {
+        self.capture(&v);
+        self.visitor.dyn_visit_char(v)
+    }
+
+    fn dyn_visit_str(&mut self, v: &str) -> DeserializeResult<()> {
+        self.capture(&v);
+        self.visitor.dyn_visit_str(v)
+    }
+
+    fn dyn_visit_borrowed_str(&mut self, v: &'de str) -> DeserializeResult<()> {
+        self.capture(&v);
+        self.visitor.dyn_visit_borrowed_str(v)
+    }
+
+    fn dyn_visit_string(&mut self, v: String) -> DeserializeResult<()> {
+        self.capture(&v);
+        self.visitor.dyn_visit_string(v)
+    }
+
+    fn dyn_visit_bytes(&mut self, v: &[u8]) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_bytes(v)
+    }
+
+    fn dyn_visit_borrowed_bytes(&mut self, v: &'de [u8]) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_borrowed_bytes(v)
+    }
+
+    fn dyn_visit_byte_buf(&mut self, v: Vec<u8>) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_byte_buf(v)
+    }
+
+    fn dyn_visit_none(&mut self) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_none()
+    }
+
+    fn dyn_visit_some(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_some(&mut TrackDeserializer::new(
+            deserializer,
+            self.track,
+            self.capture,
+        ))
+    }
+
+    fn dyn_visit_unit(&mut self) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_unit()
+    }
+
+    fn dyn_visit_newtype_struct(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        self.visitor
+            .dyn_visit_newtype_struct(&mut TrackDeserializer::new(
+                deserializer,
+                self.track,
+                self.capture,
+            ))
+    }
+
+    fn dyn_visit_seq(&mut self, seq: &mut dyn SeqAccess<'de>) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_seq(&mut TrackSeqAccess {
+            seq,
+            track: self.track,
+            index: 0,
+        })
+    }
+
+    fn dyn_visit_map(&mut self, map: &mut dyn MapAccess<'de>) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_map(&mut TrackMapAccess {
+            map,
+            track: self.track,
+            key: None,
+        })
+    }
+
+    fn dyn_visit_enum(&mut self, data: &mut dyn EnumAccess<'de>) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_enum(&mut TrackEnumAccess {
+            data: Some(data),
+            track: self.track,
+            variant: None,
+        })
+    }
+}
+
+/// Wraps a dynamic deserialize-seed, so that the nested values are tracked.
+struct TrackSeed<'a, 'de> {
+    seed: &'a mut dyn DeserializeSeed<'de>,
+    track: &'a Track,
+    capture: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for TrackSeed<'_, 'de> {
+    fn dyn_deserialize(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        self.seed.dyn_deserialize(&mut TrackDeserializer::new(
+            deserializer,
+            self.track,
+            self.capture,
+        ))
+    }
+}
+
+/// Tracks the index of each element.
+struct TrackSeqAccess<'a, 'de> {
+    seq: &'a mut dyn SeqAccess<'de>,
+    track: &'a Track,
+    index: usize,
+}
+
+impl<'de> SeqAccess<'de> for TrackSeqAccess<'_, 'de> {
+    fn dyn_next_element(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> DeserializerResult<Option<()>> {
+        let track = self.track;
+        let segment = Segment::Seq { index: self.index };
+        let result = track.enter(segment, || {
+            self.seq.dyn_next_element(&mut TrackSeed {
+                seed,
+                track,
+                capture: false,
+            })
+        });
+        if let Ok(Some(())) = result {
+            self.index += 1;
+        }
+        result
+    }
+
+    fn dyn_size_hint(&self) -> Option<usize> {
+        self.seq.dyn_size_hint()
+    }
+}
+
+/// Tracks the key of each value.
+struct TrackMapAccess<'a, 'de> {
+    map: &'a mut dyn MapAccess<'de>,
+    track: &'a Track,
+    /// The key of the next value.
+    key: Option<Segment>,
+}
+
+impl<'de> MapAccess<'de> for TrackMapAccess<'_, 'de> {
+    fn dyn_next_key(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> DeserializerResult<Option<()>> {
+        self.track.key.take();
+        let result = self.map.dyn_next_key(&mut TrackSeed {
+            seed,
+            track: self.track,
+            capture: true,
+        });
+        self.key = Some(self.track.segment(|key| Segment::Map { key }));
+        result
+    }
+
+    fn dyn_next_value(&mut self, seed: &mut dyn DeserializeSeed<'de>) -> DeserializerResult<()> {
+        let track = self.track;
+        let segment = self.key.take().unwrap_or(Segment::Unknown);
+        track.enter(segment, || {
+            self.map.dyn_next_value(&mut TrackSeed {
+                seed,
+                track,
+                capture: false,
+            })
+        })
+    }
+
+    fn dyn_next_entry(
+        &mut self,
+        kseed: &mut dyn DeserializeSeed<'de>,
+        vseed: &mut dyn DeserializeSeed<'de>,
+    ) -> DeserializerResult<Option<((), ())>> {
+        // Split the entry, so that the key is known before the value is deserialized.
+        match self.dyn_next_key(kseed)? {
+            Some(()) => self.dyn_next_value(vseed).map(|()| Some(((), ()))),
+            None => Ok(None),
+        }
+    }
+
+    fn dyn_size_hint(&self) -> Option<usize> {
+        self.map.dyn_size_hint()
+    }
+}
+
+/// Tracks the name of the variant.
+struct TrackEnumAccess<'a, 'de> {
+    data: Option<&'a mut dyn EnumAccess<'de>>,
+    track: &'a Track,
+    variant: Option<TrackVariantAccess<'a, 'de>>,
+}
+
+impl<'de> EnumAccess<'de> for TrackEnumAccess<'_, 'de> {
+    fn dyn_variant_seed(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> DeserializerResult<((), &mut dyn VariantAccess<'de>)> {
+        let data = self.data.take().ok_or(DeserializerError::WrongState {
+            operation: "EnumAccess::dyn_variant_seed",
+            found: DeserializerState::VariantAccess,
+        })?;
+        self.track.key.take();
+        let ((), variant) = data.dyn_variant_seed(&mut TrackSeed {
+            seed,
+            track: self.track,
+            capture: true,
+        })?;
+        let segment = self.track.segment(|variant| Segment::Enum { variant });
+        let variant = self.variant.insert(TrackVariantAccess {
+            variant,
+            track: self.track,
+            segment,
+        });
+        Ok(((), variant))
+    }
+}
+
+/// Tracks the content of the variant.
+struct TrackVariantAccess<'a, 'de> {
+    variant: &'a mut dyn VariantAccess<'de>,
+    track: &'a Track,
+    segment: Segment,
+}
+
+impl<'de> VariantAccess<'de> for TrackVariantAccess<'_, 'de> {
+    fn dyn_unit_variant(&mut self) -> DeserializerResult<()> {
+        self.variant.dyn_unit_variant()
+    }
+
+    fn dyn_newtype_variant(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> DeserializerResult<()> {
+        let track = self.track;
+        track.enter(self.segment.clone(), || {
+            self.variant.dyn_newtype_variant(&mut TrackSeed {
+                seed,
+                track,
+                capture: false,
+            })
+        })
+    }
+
+    fn dyn_tuple_variant(
+        &mut self,
+        len: usize,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        let track = self.track;
+        track.enter(self.segment.clone(), || {
+            self.variant.dyn_tuple_variant(
+                len,
+                &mut TrackVisitor {
+                    visitor,
+                    track,
+                    capture: false,
+                },
+            )
+        })
+    }
+
+    fn dyn_struct_variant(
+        &mut self,
+        fields: &'static [&'static str],
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializerResult<()> {
+        let track = self.track;
+        track.enter(self.segment.clone(), || {
+            self.variant.dyn_struct_variant(
+                fields,
+                &mut TrackVisitor {
+                    visitor,
+                    track,
+                    capture: false,
+                },
+            )
+        })
+    }
+}
+
+/// Wraps a dynamic serialize, so that the nested values are tracked.
+struct TrackSerialize<'a> {
+    value: &'a dyn Serialize,
+    track: &'a Track,
+    capture: bool,
+}
+
+impl<'a> TrackSerialize<'a> {
+    fn new(value: &'a dyn Serialize, track: &'a Track, capture: bool) -> Self {
+        TrackSerialize {
+            value,
+            track,
+            capture,
+        }
+    }
+}
+
+impl Serialize for TrackSerialize<'_> {
+    fn dyn_serialize(&self, serializer: &mut dyn Serializer) -> SerializeResult<()> {
+        self.value.dyn_serialize(&mut TrackSerializer::new(
+            serializer,
+            self.track,
+            self.capture,
+        ))
+    }
+}
+
+/// Wraps a dynamic serializer and its compound states, so that the nested values are tracked.
+struct TrackSerializer<'a> {
+    state: TrackState<'a>,
+    track: &'a Track,
+    /// Whether the serialized scalar should be captured as a map key.
+    capture: bool,
+}
+
+#[derive(Default)]
+enum TrackState<'a> {
+    #[default]
+    None,
+    Serializer(&'a mut dyn Serializer),
+    SerializeSeq(&'a mut dyn SerializeSeq, usize),
+    SerializeTuple(&'a mut dyn SerializeTuple, usize),
+    SerializeTupleStruct(&'a mut dyn SerializeTupleStruct, usize),
+    SerializeTupleVariant(&'a mut dyn SerializeTupleVariant, &'static str, usize),
+    SerializeMap(&'a mut dyn SerializeMap, Option<Segment>),
+    SerializeStruct(&'a mut dyn SerializeStruct),
+    SerializeStructVariant(&'a mut dyn SerializeStructVariant, &'static str),
+}
+
+impl TrackState<'_> {
+    fn state(&self) -> SerializerState {
+        match self {
+            TrackState::None => SerializerState::None,
+            TrackState::Serializer(_) => SerializerState::Serializer,
+            TrackState::SerializeSeq(..) => SerializerState::SerializeSeq,
+            TrackState::SerializeTuple(..) => SerializerState::SerializeTuple,
+            TrackState::SerializeTupleStruct(..) => SerializerState::SerializeTupleStruct,
+            TrackState::SerializeTupleVariant(..) => SerializerState::SerializeTupleVariant,
+            TrackState::SerializeMap(..) => SerializerState::SerializeMap,
+            TrackState::SerializeStruct(_) => SerializerState::SerializeStruct,
+            TrackState::SerializeStructVariant(..) => SerializerState::SerializeStructVariant,
+        }
+    }
+
+    #[cold]
+    fn wrong_state(&self, operation: &'static str) -> SerializerError {
+        SerializerError::WrongState {
+            operation,
+            found: self.state(),
+        }
+    }
+
+    /// Puts back the `state` taken out for `operation`, and returns the error of the misuse.
+    #[cold]
+    fn restore(&mut self, state: Self, operation: &'static str) -> SerializerError {
+        let error = state.wrong_state(operation);
+        *self = state;
+        error
+    }
+}
+
+impl<'a> TrackSerializer<'a> {
+    fn new(serializer: &'a mut dyn Serializer, track: &'a Track, capture: bool) -> Self {
+        TrackSerializer {
+            state: TrackState::Serializer(serializer),
+            track,
+            capture,
+        }
+    }
+
+    fn serializer(&mut self, operation: &'static str) -> SerializerResult<&'a mut dyn Serializer> {
+        match mem::take(&mut self.state) {
+            TrackState::Serializer(serializer) => Ok(serializer),
+            state => Err(self.state.restore(state, operation)),
+        }
+    }
+
+    fn capture(&self, key: &dyn fmt::Display) {
+        if self.capture {
+            self.track.capture(key);
+        }
+    }
+}
+
+impl Serializer for TrackSerializer<'_> {
+    fn dyn_serialize_bool(&mut self, v: bool) -> SerializerResult<()> {
+        self.capture(&v);
+        self.serializer("Serializer::dyn_serialize_bool")?
+            .dyn_serialize_bool(v)
+    }
+
+    fn dyn_serialize_i8(&mut self, v: i8) -> SerializerResult<()> {
+        self.capture(&v);
+        self.serializer("Serializer::dyn_serialize_i8")?
+            .dyn_serialize_i8(v)
+    }
+
+    fn dyn_serialize_i16(&mut self, v: i16) -> SerializerResult<()> {
+        self.capture(&v);
+        self.serializer("Serializer::dyn_serialize_i16")?
+            .dyn_serialize_i16(v)
+    }
+
+    fn dyn_serialize_i32(&mut self, v: i32) -> SerializerResult<()> {
+        self.capture(&v);
+        self.serializer("Serializer::dyn_serialize_i32")?
+            .dyn_serialize_i32(v)
+    }
+
+    fn dyn_serialize_i64(&mut self, v: i64) -> SerializerResult<()> {
+        self.capture(&v);
+        self.serializer("Serializer::dyn_serialize_i64")?
+            .dyn_serialize_i64(v)
+    }
+
+    fn dyn_serialize_i128(&mut self, v: i128) -> SerializerResult<()> {
+        self.capture(&v);
+        self.serializer("Serializer::dyn_serialize_i128")?
+            .dyn_serialize_i128(v)
+    }
+
+    fn dyn_serialize_u8(&mut self, v: u8) -> SerializerResult<()> {
+        self.capture(&v);
+        self.serializer("Serializer::dyn_serialize_u8")?
+            .dyn_serialize_u8(v)
+    }
+
+    fn dyn_serialize_u16(&mut self, v: u16) -> SerializerResult<()> {
+        self.capture(&v);
+        self.serializer("Serializer::dyn_serialize_u16")?
+            .dyn_serialize_u16(v)
+    }
+
+    fn dyn_serialize_u32(&mut self, v: u32) -> SerializerResult<()> {
+        self.capture(&v);
+        self.serializer("Serializer::dyn_serialize_u32")?
+            .dyn_serialize_u32(v)
+    }
+
+    fn dyn_serialize_u64(&mut self, v: u64) -> SerializerResult<()> {
+        self.capture(&v);
+        self.serializer("Serializer::dyn_serialize_u64")?
+            .dyn_serialize_u64(v)
+    }
+
+    fn dyn_serialize_u128(&mut self, v: u128) -> SerializerResult<()> {
+        self.capture(&v);
+        self.serializer("Serializer::dyn_serialize_u128")?
+            .dyn_serialize_u128(v)
+    }
+
+    fn dyn_serialize_f32(&mut self, v: f32) -> SerializerResult<()> {
+        self.capture(&v);
+        self.serializer("Serializer::dyn_serialize_f32")?
+            .dyn_serialize_f32(v)
+    }
+
+    fn dyn_serialize_f64(&mut self, v: f64) -> SerializerResult<()> {
+        self.capture(&v);
+        self.serializer("Serializer::dyn_serialize_f64")?
+            .dyn_serialize_f64(v)
+    }
+
+    fn dyn_serialize_char(&mut self, v: char) -> SerializerResult<()> {
+        self.capture(&v);
+        self.serializer("Serializer::dyn_serialize_char")?
+            .dyn_serialize_char(v)
+    }
+
+    fn dyn_serialize_str(&mut self, v: &str) -> SerializerResult<()> {
+        self.capture(&v);
+        self.serializer("Serializer::dyn_serialize_str")?
+            .dyn_serialize_str(v)
+    }
+
+    fn dyn_serialize_bytes(&mut self, v: &[u8]) -> SerializerResult<()> {
+        self.serializer("Serializer::dyn_serialize_bytes")?
+            .dyn_serialize_bytes(v)
+    }
+
+    fn dyn_serialize_none(&mut self) -> SerializerResult<()> {
+        self.serializer("Serializer::dyn_serialize_none")?
+            .dyn_serialize_none()
+    }
+
+    fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        let value = TrackSerialize::new(value, self.track, self.capture);
+        self.serializer("Serializer::dyn_serialize_some")?
+            .dyn_serialize_some(&value)
+    }
+
+    fn dyn_serialize_unit(&mut self) -> SerializerResult<()> {
+        self.serializer("Serializer::dyn_serialize_unit")?
+            .dyn_serialize_unit()
+    }
+
+    fn dyn_serialize_unit_struct(&mut self, name: &'static str) -> SerializerResult<()> {
+        self.serializer("Serializer::dyn_serialize_unit_struct")?
+            .dyn_serialize_unit_struct(name)
+    }
+
+    fn dyn_serialize_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> SerializerResult<()> {
+        self.capture(&variant);
+        self.serializer("Serializer::dyn_serialize_unit_variant")?
+            .dyn_serialize_unit_variant(name, variant_index, variant)
+    }
+
+    fn dyn_serialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        let value = TrackSerialize::new(value, self.track, self.capture);
+        self.serializer("Serializer::dyn_serialize_newtype_struct")?
+            .dyn_serialize_newtype_struct(name, &value)
+    }
+
+    fn dyn_serialize_newtype_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        let serializer = self.serializer("Serializer::dyn_serialize_newtype_variant")?;
+        let value = TrackSerialize::new(value, self.track, false);
+        let segment = Segment::Enum {
+            variant: variant.to_string(),
+        };
+        self.track.enter(segment, || {
+            serializer.dyn_serialize_newtype_variant(name, variant_index, variant, &value)
+        })
+    }
+
+    fn dyn_serialize_seq(&mut self, len: Option<usize>) -> SerializerResult<&mut dyn SerializeSeq> {
+        let serializer = self
+            .serializer("Serializer::dyn_serialize_seq")?
+            .dyn_serialize_seq(len)?;
+        self.state = TrackState::SerializeSeq(serializer, 0);
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple(&mut self, len: usize) -> SerializerResult<&mut dyn SerializeTuple> {
+        let serializer = self
+            .serializer("Serializer::dyn_serialize_tuple")?
+            .dyn_serialize_tuple(len)?;
+        self.state = TrackState::SerializeTuple(serializer, 0);
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeTupleStruct> {
+        let serializer = self
+            .serializer("Serializer::dyn_serialize_tuple_struct")?
+            .dyn_serialize_tuple_struct(name, len)?;
+        self.state = TrackState::SerializeTupleStruct(serializer, 0);
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeTupleVariant> {
+        let serializer = self
+            .serializer("Serializer::dyn_serialize_tuple_variant")?
+            .dyn_serialize_tuple_variant(name, variant_index, variant, len)?;
+        self.state = TrackState::SerializeTupleVariant(serializer, variant, 0);
+        Ok(self)
+    }
+
+    fn dyn_serialize_map(&mut self, len: Option<usize>) -> SerializerResult<&mut dyn SerializeMap> {
+        let serializer = self
+            .serializer("Serializer::dyn_serialize_map")?
+            .dyn_serialize_map(len)?;
+        self.state = TrackState::SerializeMap(serializer, None);
+        Ok(self)
+    }
+
+    fn dyn_serialize_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeStruct> {
+        let serializer = self
+            .serializer("Serializer::dyn_serialize_struct")?
+            .dyn_serialize_struct(name, len)?;
+        self.state = TrackState::SerializeStruct(serializer);
+        Ok(self)
+    }
+
+    fn dyn_serialize_struct_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeStructVariant> {
+        let serializer = self
+            .serializer("Serializer::dyn_serialize_struct_variant")?
+            .dyn_serialize_struct_variant(name, variant_index, variant, len)?;
+        self.state = TrackState::SerializeStructVariant(serializer, variant);
+        Ok(self)
+    }
+
+    fn dyn_collect_str(&mut self, value: &dyn fmt::Display) -> SerializerResult<()> {
+        self.capture(value);
+        self.serializer("Serializer::dyn_collect_str")?
+            .dyn_collect_str(value)
+    }
+
+    fn dyn_is_human_readable(&self) -> bool {
+        if let TrackState::Serializer(serializer) = &self.state {
+            serializer.dyn_is_human_readable()
+        } else {
+            true
+        }
+    }
+}
+
+impl SerializeSeq for TrackSerializer<'_> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        let track = self.track;
+        let TrackState::SerializeSeq(serializer, index) = &mut self.state else {
+            return Err(self
+                .state
+                .wrong_state("SerializeSeq::dyn_serialize_element"));
+        };
+        let value = TrackSerialize::new(value, track, false);
+        let segment = Segment::Seq { index: *index };
+        *index += 1;
+        track.enter(segment, || serializer.dyn_serialize_element(&value))
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        match mem::take(&mut self.state) {
+            TrackState::SerializeSeq(serializer, _) => serializer.dyn_end(),
+            state => Err(self.state.restore(state, "SerializeSeq::dyn_end")),
+        }
+    }
+}
+
+impl SerializeTuple for TrackSerializer<'_> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        let track = self.track;
+        let TrackState::SerializeTuple(serializer, index) = &mut self.state else {
+            return Err(self
+                .state
+                .wrong_state("SerializeTuple::dyn_serialize_element"));
+        };
+        let value = TrackSerialize::new(value, track, false);
+        let segment = Segment::Seq { index: *index };
+        *index += 1;
+        track.enter(segment, || serializer.dyn_serialize_element(&value))
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        match mem::take(&mut self.state) {
+            TrackState::SerializeTuple(serializer, _) => serializer.dyn_end(),
+            state => Err(self.state.restore(state, "SerializeTuple::dyn_end")),
+        }
+    }
+}
+
+impl SerializeTupleStruct for TrackSerializer<'_> {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        let track = self.track;
+        let TrackState::SerializeTupleStruct(serializer, index) = &mut self.state else {
+            return Err(self
+                .state
+                .wrong_state("SerializeTupleStruct::dyn_serialize_field"));
+        };
+        let value = TrackSerialize::new(value, track, false);
+        let segment = Segment::Seq { index: *index };
+        *index += 1;
+        track.enter(segment, || serializer.dyn_serialize_field(&value))
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        match mem::take(&mut self.state) {
+            TrackState::SerializeTupleStruct(serializer, _) => serializer.dyn_end(),
+            state => Err(self.state.restore(state, "SerializeTupleStruct::dyn_end")),
+        }
+    }
+}
+
+impl SerializeTupleVariant for TrackSerializer<'_> {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        let track = self.track;
+        let TrackState::SerializeTupleVariant(serializer, variant, index) = &mut self.state else {
+            return Err(self
+                .state
+                .wrong_state("SerializeTupleVariant::dyn_serialize_field"));
+        };
+        let value = TrackSerialize::new(value, track, false);
+        let variant = Segment::Enum {
+            variant: variant.to_string(),
+        };
+        let segment = Segment::Seq { index: *index };
+        *index += 1;
+        track.enter(variant, || {
+            track.enter(segment, || serializer.dyn_serialize_field(&value))
+        })
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        match mem::take(&mut self.state) {
+            TrackState::SerializeTupleVariant(serializer, _, _) => serializer.dyn_end(),
+            state => Err(self.state.restore(state, "SerializeTupleVariant::dyn_end")),
+        }
+    }
+}
+
+impl SerializeMap for TrackSerializer<'_> {
+    fn dyn_serialize_key(&mut self, key: &dyn Serialize) -> SerializerResult<()> {
+        let track = self.track;
+        let TrackState::SerializeMap(serializer, segment) = &mut self.state else {
+            return Err(self.state.wrong_state("SerializeMap::dyn_serialize_key"));
+        };
+        track.key.take();
+        let result = serializer.dyn_serialize_key(&TrackSerialize::new(key, track, true));
+        *segment = Some(track.segment(|key| Segment::Map { key }));
+        result
+    }
+
+    fn dyn_serialize_value(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        let track = self.track;
+        let TrackState::SerializeMap(serializer, segment) = &mut self.state else {
+            return Err(self.state.wrong_state("SerializeMap::dyn_serialize_value"));
+        };
+        let value = TrackSerialize::new(value, track, false);
+        let segment = segment.take().unwrap_or(Segment::Unknown);
+        track.enter(segment, || serializer.dyn_serialize_value(&value))
+    }
+
+    fn dyn_serialize_entry(
+        &mut self,
+        key: &dyn Serialize,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        // Split the entry, so that the key is known before the value is serialized.
+        self.dyn_serialize_key(key)?;
+        self.dyn_serialize_value(value)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        match mem::take(&mut self.state) {
+            TrackState::SerializeMap(serializer, _) => serializer.dyn_end(),
+            state => Err(self.state.restore(state, "SerializeMap::dyn_end")),
+        }
+    }
+}
+
+impl SerializeStruct for TrackSerializer<'_> {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        let track = self.track;
+        let TrackState::SerializeStruct(serializer) = &mut self.state else {
+            return Err(self
+                .state
+                .wrong_state("SerializeStruct::dyn_serialize_field"));
+        };
+        let value = TrackSerialize::new(value, track, false);
+        let segment = Segment::Map {
+            key: key.to_string(),
+        };
+        track.enter(segment, || serializer.dyn_serialize_field(key, &value))
+    }
+
+    fn dyn_skip_field(&mut self, key: &'static str) -> SerializerResult<()> {
+        match &mut self.state {
+            TrackState::SerializeStruct(serializer) => serializer.dyn_skip_field(key),
+            state => Err(state.wrong_state("SerializeStruct::dyn_skip_field")),
+        }
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        match mem::take(&mut self.state) {
+            TrackState::SerializeStruct(serializer) => serializer.dyn_end(),
+            state => Err(self.state.restore(state, "SerializeStruct::dyn_end")),
+        }
+    }
+}
+
+impl SerializeStructVariant for TrackSerializer<'_> {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        let track = self.track;
+        let TrackState::SerializeStructVariant(serializer, variant) = &mut self.state else {
+            return Err(self
+                .state
+                .wrong_state("SerializeStructVariant::dyn_serialize_field"));
+        };
+        let value = TrackSerialize::new(value, track, false);
+        let variant = Segment::Enum {
+            variant: variant.to_string(),
+        };
+        let segment = Segment::Map {
+            key: key.to_string(),
+        };
+        track.enter(variant, || {
+            track.enter(segment, || serializer.dyn_serialize_field(key, &value))
+        })
+    }
+
+    fn dyn_skip_field(&mut self, key: &'static str) -> SerializerResult<()> {
+        match &mut self.state {
+            TrackState::SerializeStructVariant(serializer, _) => serializer.dyn_skip_field(key),
+            state => Err(state.wrong_state("SerializeStructVariant::dyn_skip_field")),
+        }
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        match mem::take(&mut self.state) {
+            TrackState::SerializeStructVariant(serializer, _) => serializer.dyn_end(),
+            state => Err(self.state.restore(state, "SerializeStructVariant::dyn_end")),
+        }
+    }
+}
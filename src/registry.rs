@@ -0,0 +1,996 @@
+//! # Registry of Tagged Trait Objects.
+//!
+//! This module serializes trait objects, such as `Box<dyn Trait>`, together with a tag naming the
+//! concrete type, and deserializes them back by looking the tag up in a [`Registry`].
+//!
+//! The concrete types are registered explicitly in a static slice of [`Entry`], so that the
+//! registry needs neither `std` nor `alloc`, nor any linker tricks. An entry deserializes its type
+//! with a function, or with an [`EntrySeed`] carrying state.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde::Deserialize;
+//! use serde_dyn::registry::{Entry, Registry, Tagging};
+//!
+//! trait Stage: serde_dyn::Serialize {
+//!     fn tag(&self) -> &'static str;
+//!     fn run(&self, x: i32) -> i32;
+//! }
+//!
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Add {
+//!     n: i32,
+//! }
+//!
+//! impl Stage for Add {
+//!     fn tag(&self) -> &'static str {
+//!         "add"
+//!     }
+//!
+//!     fn run(&self, x: i32) -> i32 {
+//!         x + self.n
+//!     }
+//! }
+//!
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Neg;
+//!
+//! impl Stage for Neg {
+//!     fn tag(&self) -> &'static str {
+//!         "neg"
+//!     }
+//!
+//!     fn run(&self, x: i32) -> i32 {
+//!         -x
+//!     }
+//! }
+//!
+//! static STAGES: Registry<Box<dyn Stage>> = Registry::new(
+//!     "Stage",
+//!     Tagging::Internal { tag: "type" },
+//!     &["add", "neg"],
+//!     &[
+//!         Entry::new("add", |deserializer| Ok(Box::new(Add::deserialize(deserializer)?))),
+//!         Entry::new("neg", |deserializer| Ok(Box::new(Neg::deserialize(deserializer)?))),
+//!     ],
+//! );
+//!
+//! impl serde::Serialize for dyn Stage {
+//!     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+//!         STAGES.serialize(self.tag(), self, serializer)
+//!     }
+//! }
+//!
+//! impl<'de> serde::Deserialize<'de> for Box<dyn Stage> {
+//!     fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+//!         STAGES.deserialize(deserializer)
+//!     }
+//! }
+//!
+//! let json = r#"[{"type":"add","n":2},{"type":"neg"}]"#;
+//! let stages: Vec<Box<dyn Stage>> = serde_json::from_str(json).unwrap();
+//! assert_eq!(stages.iter().fold(1, |x, stage| stage.run(x)), -3);
+//! assert_eq!(serde_json::to_string(&stages).unwrap(), json);
+//! ```
+
+use core::{fmt, str};
+
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::vec::Vec;
+
+use serde::de::{DeserializeSeed, Error as _, IgnoredAny, MapAccess, Unexpected, Visitor};
+use serde::ser::{Error as _, Impossible, SerializeMap, SerializeStruct};
+
+use crate::de::{DeserializeResult, Deserializer};
+use crate::ser::Serialize;
+#[cfg(any(feature = "std", feature = "alloc"))]
+use crate::value::Value;
+
+/// A set of concrete types registered under their tags.
+///
+/// `T` is the deserialized value, typically a `Box<dyn Trait>`. The registry is meant to live in a
+/// `static` item, and to be called from the [`serde::Serialize`] and [`serde::Deserialize`]
+/// implementations of the trait object, as shown in the [module documentation](self).
+pub struct Registry<T: 'static> {
+    name: &'static str,
+    tagging: Tagging,
+    tags: &'static [&'static str],
+    entries: &'static [Entry<T>],
+}
+
+impl<T> Registry<T> {
+    /// Creates a registry named `name`, usually the name of the trait.
+    ///
+    /// The name shows up in error messages, and as the enum name for [`Tagging::External`]. The
+    /// `tags` are the tags of the `entries` in the same order, and are reported as the variants of
+    /// the enum, since a static list of them cannot be built from the entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tags` and the tags of `entries` differ, which fails the compilation of a
+    /// `static` item.
+    #[must_use]
+    pub const fn new(
+        name: &'static str,
+        tagging: Tagging,
+        tags: &'static [&'static str],
+        entries: &'static [Entry<T>],
+    ) -> Self {
+        assert!(
+            tags.len() == entries.len(),
+            "the tags must be the tags of the entries"
+        );
+        let mut i = 0;
+        while i < tags.len() {
+            assert!(
+                str_eq(tags[i], entries[i].tag),
+                "the tags must be the tags of the entries"
+            );
+            i += 1;
+        }
+
+        Registry {
+            name,
+            tagging,
+            tags,
+            entries,
+        }
+    }
+
+    /// Returns the name of the registry.
+    #[inline]
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns how the tag is represented.
+    #[inline]
+    #[must_use]
+    pub const fn tagging(&self) -> Tagging {
+        self.tagging
+    }
+
+    /// Returns the tags of all registered entries.
+    #[inline]
+    #[must_use]
+    pub const fn tags(&self) -> &'static [&'static str] {
+        self.tags
+    }
+
+    /// Returns all registered entries.
+    #[inline]
+    #[must_use]
+    pub const fn entries(&self) -> &'static [Entry<T>] {
+        self.entries
+    }
+
+    /// Returns the entry registered under `tag`.
+    #[must_use]
+    pub fn get(&self, tag: &str) -> Option<&'static Entry<T>> {
+        self.entries.iter().find(|entry| entry.tag == tag)
+    }
+
+    /// Serializes `value` tagged with `tag`, which must be registered.
+    ///
+    /// For [`Tagging::External`], the index of the entry is used as the variant index, so that
+    /// formats that are not self-describing work as well.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `tag` is not registered, or if the serialization of `value` fails.
+    pub fn serialize<S>(
+        &self,
+        tag: &str,
+        value: &dyn Serialize,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let Some((index, entry)) = self
+            .entries
+            .iter()
+            .enumerate()
+            .find(|(_, entry)| entry.tag == tag)
+        else {
+            return Err(S::Error::custom(format_args!(
+                "tag `{tag}` is not registered in {}",
+                self.name
+            )));
+        };
+
+        match self.tagging {
+            Tagging::External => {
+                serializer.serialize_newtype_variant(self.name, index as u32, entry.tag, value)
+            }
+            Tagging::Internal { tag } => serde::Serialize::serialize(
+                value,
+                TaggedSerializer {
+                    name: self.name,
+                    tag,
+                    variant: entry.tag,
+                    delegate: serializer,
+                },
+            ),
+            Tagging::Adjacent { tag, content } => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry(tag, entry.tag)?;
+                map.serialize_entry(content, value)?;
+                map.end()
+            }
+        }
+    }
+
+    /// Deserializes the tag, then the value with the registered entry.
+    ///
+    /// For [`Tagging::Internal`], the deserializer must be self-describing. The entries before the
+    /// tag are buffered, so without `alloc` the tag must be the first entry of the map. For [`Tagging::Adjacent`], the tag must come before the content,
+    /// which avoids buffering the content.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the tag is not registered, or if the deserialization fails.
+    pub fn deserialize<'de, D>(&self, deserializer: D) -> Result<T, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match self.tagging {
+            Tagging::External => {
+                deserializer.deserialize_enum(self.name, self.tags, ExternalVisitor(self))
+            }
+            Tagging::Internal { tag } => deserializer.deserialize_any(InternalVisitor {
+                registry: self,
+                tag,
+            }),
+            Tagging::Adjacent { tag, content } => deserializer.deserialize_map(AdjacentVisitor {
+                registry: self,
+                tag,
+                content,
+            }),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Registry<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Registry")
+            .field("name", &self.name)
+            .field("tagging", &self.tagging)
+            .field("entries", &self.entries)
+            .finish()
+    }
+}
+
+/// How the tag is represented, like the enum representations of `serde_derive`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Tagging {
+    /// `{"add": {"n": 2}}`, where the tag is the variant of a newtype variant.
+    External,
+    /// `{"type": "add", "n": 2}`, where the tag is inserted into the map or struct of the value.
+    Internal {
+        /// The key of the tag.
+        tag: &'static str,
+    },
+    /// `{"t": "add", "c": {"n": 2}}`, where the tag and the value are entries of a map.
+    Adjacent {
+        /// The key of the tag.
+        tag: &'static str,
+        /// The key of the value.
+        content: &'static str,
+    },
+}
+
+/// A concrete type registered under a tag.
+pub struct Entry<T: 'static> {
+    tag: &'static str,
+    seed: Seed<T>,
+}
+
+/// How an [`Entry`] deserializes its concrete type.
+enum Seed<T: 'static> {
+    Fn(for<'de> fn(&mut dyn Deserializer<'de>) -> DeserializeResult<T>),
+    Dyn(&'static dyn EntrySeed<T>),
+}
+
+impl<T> Entry<T> {
+    /// Registers `deserialize` under `tag`.
+    ///
+    /// The function deserializes the concrete type from the dynamic deserializer, and converts it
+    /// into `T`. Closures that capture nothing can be used as well.
+    #[must_use]
+    pub const fn new(
+        tag: &'static str,
+        deserialize: for<'de> fn(&mut dyn Deserializer<'de>) -> DeserializeResult<T>,
+    ) -> Self {
+        Entry {
+            tag,
+            seed: Seed::Fn(deserialize),
+        }
+    }
+
+    /// Registers `seed` under `tag`.
+    ///
+    /// Unlike [`Entry::new`], the seed can carry state, such as the configuration shared by all the
+    /// values of the concrete type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde::Deserialize;
+    /// # use serde_dyn::Deserializer;
+    /// # use serde_dyn::de::DeserializeResult;
+    /// # use serde_dyn::registry::{Entry, EntrySeed, Registry, Tagging};
+    /// struct Clamp {
+    ///     max: i32,
+    /// }
+    ///
+    /// impl EntrySeed<i32> for Clamp {
+    ///     fn dyn_deserialize(&self, deserializer: &mut dyn Deserializer<'_>) -> DeserializeResult<i32> {
+    ///         Ok(i32::deserialize(deserializer)?.min(self.max))
+    ///     }
+    /// }
+    ///
+    /// static LIMITS: Registry<i32> = Registry::new(
+    ///     "Limit",
+    ///     Tagging::External,
+    ///     &["clamp"],
+    ///     &[Entry::with_seed("clamp", &Clamp { max: 10 })],
+    /// );
+    ///
+    /// let mut deserializer = serde_json::Deserializer::from_str(r#"{"clamp":42}"#);
+    /// assert_eq!(LIMITS.deserialize(&mut deserializer).unwrap(), 10);
+    /// ```
+    #[must_use]
+    pub const fn with_seed(tag: &'static str, seed: &'static dyn EntrySeed<T>) -> Self {
+        Entry {
+            tag,
+            seed: Seed::Dyn(seed),
+        }
+    }
+
+    /// Returns the tag of the entry.
+    #[inline]
+    #[must_use]
+    pub const fn tag(&self) -> &'static str {
+        self.tag
+    }
+
+    /// Deserializes the value with the registered function or seed.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the registered function or seed fails.
+    pub fn deserialize<'de>(
+        &self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<T> {
+        match self.seed {
+            Seed::Fn(deserialize) => deserialize(deserializer),
+            Seed::Dyn(seed) => seed.dyn_deserialize(deserializer),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Entry<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Entry")
+            .field("tag", &self.tag)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A seed registered in an [`Entry`] with [`Entry::with_seed`].
+///
+/// The seed deserializes a concrete type from the dynamic deserializer, and converts it into `T`.
+/// Since the registry is shared, the seed is driven through a shared reference, unlike
+/// [`de::DeserializeSeed`](crate::de::DeserializeSeed), and must be [`Sync`].
+pub trait EntrySeed<T>: Sync {
+    /// Deserializes the concrete type with the dynamic deserializer, and converts it into `T`.
+    fn dyn_deserialize(&self, deserializer: &mut dyn Deserializer<'_>) -> DeserializeResult<T>;
+}
+
+/// Compares two strings in a `const` context.
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Formats the error of an unregistered tag, in the same way as an unknown variant.
+struct UnknownTag<'a, T: 'static> {
+    tag: &'a str,
+    entries: &'static [Entry<T>],
+}
+
+impl<T> fmt::Display for UnknownTag<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown variant `{}`, ", self.tag)?;
+        match self.entries {
+            [] => f.write_str("there are no variants"),
+            [a] => write!(f, "expected `{}`", a.tag),
+            [a, b] => write!(f, "expected `{}` or `{}`", a.tag, b.tag),
+            [first, rest @ ..] => {
+                write!(f, "expected one of `{}`", first.tag)?;
+                rest.iter()
+                    .try_for_each(|entry| write!(f, ", `{}`", entry.tag))
+            }
+        }
+    }
+}
+
+/// Deserializes the tag by its name or its index, and returns the registered entry.
+struct TagSeed<'a, T: 'static>(&'a Registry<T>);
+
+impl<'de, T> DeserializeSeed<'de> for TagSeed<'_, T> {
+    type Value = &'static Entry<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de, T> Visitor<'de> for TagSeed<'_, T> {
+    type Value = &'static Entry<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a tag of {}", self.0.name)
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        let entry = usize::try_from(v)
+            .ok()
+            .and_then(|index| self.0.entries.get(index));
+        match entry {
+            Some(entry) => Ok(entry),
+            None => Err(E::invalid_value(Unexpected::Unsigned(v), &self)),
+        }
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        self.0.get(v).ok_or_else(|| {
+            E::custom(UnknownTag {
+                tag: v,
+                entries: self.0.entries,
+            })
+        })
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        match str::from_utf8(v) {
+            Ok(v) => self.visit_str(v),
+            Err(_) => Err(E::invalid_value(Unexpected::Bytes(v), &self)),
+        }
+    }
+}
+
+/// Deserializes the value with the registered entry.
+struct ValueSeed<T: 'static>(&'static Entry<T>);
+
+impl<'de, T> DeserializeSeed<'de> for ValueSeed<T> {
+    type Value = T;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<T, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        crate::de::with_dyn(deserializer, |deserializer| {
+            self.0.deserialize(deserializer)
+        })
+    }
+}
+
+/// The keys of the internally and adjacently tagged maps.
+enum Field {
+    Tag,
+    Content,
+    Other,
+}
+
+/// Deserializes the keys of the internally and adjacently tagged maps.
+struct FieldSeed {
+    tag: &'static str,
+    content: Option<&'static str>,
+}
+
+impl<'de> DeserializeSeed<'de> for FieldSeed {
+    type Value = Field;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Field, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de> Visitor<'de> for FieldSeed {
+    type Value = Field;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "the key `{}`", self.tag)
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Field, E> {
+        if v == self.tag {
+            Ok(Field::Tag)
+        } else if Some(v) == self.content {
+            Ok(Field::Content)
+        } else {
+            Ok(Field::Other)
+        }
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Field, E> {
+        match str::from_utf8(v) {
+            Ok(v) => self.visit_str(v),
+            Err(_) => Ok(Field::Other),
+        }
+    }
+}
+
+/// Deserializes an externally tagged value.
+struct ExternalVisitor<'a, T: 'static>(&'a Registry<T>);
+
+impl<'de, T> Visitor<'de> for ExternalVisitor<'_, T> {
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a tagged {}", self.0.name)
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<T, A::Error>
+    where
+        A: serde::de::EnumAccess<'de>,
+    {
+        let (entry, variant) = data.variant_seed(TagSeed(self.0))?;
+        serde::de::VariantAccess::newtype_variant_seed(variant, ValueSeed(entry))
+    }
+}
+
+/// Deserializes an internally tagged value.
+struct InternalVisitor<'a, T: 'static> {
+    registry: &'a Registry<T>,
+    tag: &'static str,
+}
+
+impl<'de, T> Visitor<'de> for InternalVisitor<'_, T> {
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a tagged {}", self.registry.name)
+    }
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<T, A::Error> {
+        let mut entries = Vec::new();
+        let entry = loop {
+            match map.next_key::<Value>()? {
+                Some(Value::String(key)) if key == self.tag => {
+                    break map.next_value_seed(TagSeed(self.registry))?;
+                }
+                Some(Value::Bytes(key)) if key == self.tag.as_bytes() => {
+                    break map.next_value_seed(TagSeed(self.registry))?;
+                }
+                Some(key) => entries.push((key, map.next_value::<Value>()?)),
+                None => return Err(A::Error::missing_field(self.tag)),
+            }
+        };
+
+        let map = BufferedMap {
+            entries: entries.into_iter(),
+            value: None,
+            map,
+        };
+        ValueSeed(entry).deserialize(RestDeserializer(map))
+    }
+
+    #[cfg(not(any(feature = "std", feature = "alloc")))]
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<T, A::Error> {
+        let field = FieldSeed {
+            tag: self.tag,
+            content: None,
+        };
+        match map.next_key_seed(field)? {
+            Some(Field::Tag) => {}
+            Some(_) => {
+                return Err(A::Error::custom(format_args!(
+                    "expected the tag `{}` of {} as the first entry",
+                    self.tag, self.registry.name
+                )));
+            }
+            None => return Err(A::Error::missing_field(self.tag)),
+        }
+
+        let entry = map.next_value_seed(TagSeed(self.registry))?;
+        ValueSeed(entry).deserialize(RestDeserializer(map))
+    }
+}
+
+/// Replays the entries buffered before the tag of an internally tagged value, then the remaining
+/// entries of the map.
+#[cfg(any(feature = "std", feature = "alloc"))]
+struct BufferedMap<A> {
+    entries: <Vec<(Value, Value)> as IntoIterator>::IntoIter,
+    value: Option<Value>,
+    map: A,
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for BufferedMap<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(key).map(Some).map_err(A::Error::custom)
+            }
+            None => self.map.next_key_seed(seed),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, A::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match self.value.take() {
+            Some(value) => seed.deserialize(value).map_err(A::Error::custom),
+            None => self.map.next_value_seed(seed),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.map.size_hint().map(|len| len + self.entries.len())
+    }
+}
+
+/// Deserializes the entries after the tag of an internally tagged value.
+///
+/// Unit types accept the remaining entries and ignore them, so that `{"type":"neg"}` is a unit
+/// struct, as with `serde_derive`. Newtype structs are transparent and read the same entries as
+/// their content.
+struct RestDeserializer<A>(A);
+
+impl<'de, A: MapAccess<'de>> serde::Deserializer<'de> for RestDeserializer<A> {
+    type Error = A::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, A::Error> {
+        visitor.visit_map(self.0)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, A::Error> {
+        while self.0.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, A::Error> {
+        let _ = name;
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, A::Error> {
+        let _ = name;
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, A::Error> {
+        serde::de::value::MapAccessDeserializer::new(self.0)
+            .deserialize_enum(name, variants, visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Deserializes an adjacently tagged value.
+struct AdjacentVisitor<'a, T: 'static> {
+    registry: &'a Registry<T>,
+    tag: &'static str,
+    content: &'static str,
+}
+
+impl<'de, T> Visitor<'de> for AdjacentVisitor<'_, T> {
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a tagged {}", self.registry.name)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<T, A::Error> {
+        let mut entry = None;
+        let mut value = None;
+        loop {
+            let field = FieldSeed {
+                tag: self.tag,
+                content: Some(self.content),
+            };
+            match map.next_key_seed(field)? {
+                Some(Field::Tag) if entry.is_some() => {
+                    return Err(A::Error::duplicate_field(self.tag));
+                }
+                Some(Field::Tag) => entry = Some(map.next_value_seed(TagSeed(self.registry))?),
+                Some(Field::Content) if value.is_some() => {
+                    return Err(A::Error::duplicate_field(self.content));
+                }
+                Some(Field::Content) => {
+                    let Some(entry) = entry else {
+                        return Err(A::Error::custom(format_args!(
+                            "expected the tag `{}` of {} before `{}`",
+                            self.tag, self.registry.name, self.content
+                        )));
+                    };
+                    value = Some(map.next_value_seed(ValueSeed(entry))?);
+                }
+                Some(Field::Other) => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+                None => break,
+            }
+        }
+
+        match (entry, value) {
+            (_, Some(value)) => Ok(value),
+            (None, None) => Err(A::Error::missing_field(self.tag)),
+            (Some(_), None) => Err(A::Error::missing_field(self.content)),
+        }
+    }
+}
+
+/// Serializes the value of an internally tagged entry, with the tag inserted as the first field.
+struct TaggedSerializer<S> {
+    name: &'static str,
+    tag: &'static str,
+    variant: &'static str,
+    delegate: S,
+}
+
+impl<S: serde::Serializer> TaggedSerializer<S> {
+    fn bad_type(&self, what: &str) -> S::Error {
+        S::Error::custom(format_args!(
+            "cannot serialize tagged newtype variant {}::{} containing {what}",
+            self.name, self.variant
+        ))
+    }
+}
+
+impl<S: serde::Serializer> serde::Serializer for TaggedSerializer<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    type SerializeSeq = Impossible<S::Ok, S::Error>;
+    type SerializeTuple = Impossible<S::Ok, S::Error>;
+    type SerializeTupleStruct = Impossible<S::Ok, S::Error>;
+    type SerializeTupleVariant = Impossible<S::Ok, S::Error>;
+    type SerializeMap = S::SerializeMap;
+    type SerializeStruct = S::SerializeStruct;
+    type SerializeStructVariant = Impossible<S::Ok, S::Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<S::Ok, S::Error> {
+        let _ = v;
+        Err(self.bad_type("a boolean"))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<S::Ok, S::Error> {
+        let _ = v;
+        Err(self.bad_type("an integer"))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<S::Ok, S::Error> {
+        let _ = v;
+        Err(self.bad_type("an integer"))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<S::Ok, S::Error> {
+        let _ = v;
+        Err(self.bad_type("an integer"))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<S::Ok, S::Error> {
+        let _ = v;
+        Err(self.bad_type("an integer"))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<S::Ok, S::Error> {
+        let _ = v;
+        Err(self.bad_type("an integer"))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<S::Ok, S::Error> {
+        let _ = v;
+        Err(self.bad_type("an integer"))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<S::Ok, S::Error> {
+        let _ = v;
+        Err(self.bad_type("an integer"))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<S::Ok, S::Error> {
+        let _ = v;
+        Err(self.bad_type("an integer"))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<S::Ok, S::Error> {
+        let _ = v;
+        Err(self.bad_type("an integer"))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<S::Ok, S::Error> {
+        let _ = v;
+        Err(self.bad_type("an integer"))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<S::Ok, S::Error> {
+        let _ = v;
+        Err(self.bad_type("a float"))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<S::Ok, S::Error> {
+        let _ = v;
+        Err(self.bad_type("a float"))
+    }
+
+    fn serialize_char(self, v: char) -> Result<S::Ok, S::Error> {
+        let _ = v;
+        Err(self.bad_type("a char"))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<S::Ok, S::Error> {
+        let _ = v;
+        Err(self.bad_type("a string"))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<S::Ok, S::Error> {
+        let _ = v;
+        Err(self.bad_type("a byte array"))
+    }
+
+    fn serialize_none(self) -> Result<S::Ok, S::Error> {
+        Err(self.bad_type("an optional"))
+    }
+
+    fn serialize_some<T: ?Sized + serde::Serialize>(self, value: &T) -> Result<S::Ok, S::Error> {
+        let _ = value;
+        Err(self.bad_type("an optional"))
+    }
+
+    fn serialize_unit(self) -> Result<S::Ok, S::Error> {
+        let mut map = self.delegate.serialize_map(Some(1))?;
+        map.serialize_entry(self.tag, self.variant)?;
+        map.end()
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<S::Ok, S::Error> {
+        let _ = name;
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<S::Ok, S::Error> {
+        let _ = (name, variant_index, variant);
+        Err(self.bad_type("an enum"))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + serde::Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        let _ = name;
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + serde::Serialize>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        let _ = (name, variant_index, variant, value);
+        Err(self.bad_type("an enum"))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, S::Error> {
+        let _ = len;
+        Err(self.bad_type("a sequence"))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, S::Error> {
+        let _ = len;
+        Err(self.bad_type("a tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, S::Error> {
+        let _ = (name, len);
+        Err(self.bad_type("a tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, S::Error> {
+        let _ = (name, variant_index, variant, len);
+        Err(self.bad_type("an enum"))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, S::Error> {
+        let mut map = self.delegate.serialize_map(len.map(|len| len + 1))?;
+        map.serialize_entry(self.tag, self.variant)?;
+        Ok(map)
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, S::Error> {
+        let mut state = self.delegate.serialize_struct(name, len + 1)?;
+        state.serialize_field(self.tag, self.variant)?;
+        Ok(state)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, S::Error> {
+        let _ = (name, variant_index, variant, len);
+        Err(self.bad_type("an enum"))
+    }
+
+    fn collect_str<T: ?Sized + fmt::Display>(self, value: &T) -> Result<S::Ok, S::Error> {
+        let _ = value;
+        Err(self.bad_type("a string"))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.delegate.is_human_readable()
+    }
+}
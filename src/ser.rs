@@ -5,25 +5,64 @@
 //!
 //! - [`Serialize`]: the dyn-compatible version of [`serde::Serialize`].
 //! - [`Serializer`]: the dyn-compatible version of [`serde::Serializer`].
+//! - [`SimpleSerializer`]: a reduced version of [`Serializer`] for hand-written serializers.
+#![cfg_attr(
+    any(feature = "std", feature = "alloc"),
+    doc = "- [`Recorder`]: a [`Serializer`] recording every call as a \
+           [`Token`](crate::token::Token)."
+)]
+//! - [`Tee`]: a [`Serializer`] writing one value into several serializers.
+#![cfg_attr(
+    any(feature = "std", feature = "alloc"),
+    doc = "- [`Validating`]: a [`Serializer`] checking the calls coming from [`Serialize`] \
+           implementations."
+)]
 
+use core::cell::Cell;
 use core::{error, fmt, mem};
 
 #[cfg(any(feature = "std", feature = "alloc"))]
-use core::num::NonZeroUsize;
-#[cfg(any(feature = "std", feature = "alloc"))]
-use core::ptr::NonNull;
+use core::any::{self, Any};
 
 #[cfg(all(not(feature = "std"), feature = "alloc"))]
 use alloc::boxed::Box;
 #[cfg(all(not(feature = "std"), feature = "alloc"))]
 use alloc::string::{String, ToString};
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::sync::Arc;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+#[cfg(all(
+    not(any(feature = "std", feature = "alloc")),
+    feature = "heapless-errors"
+))]
+use crate::message::InlineMessage;
+#[cfg(any(feature = "std", feature = "alloc"))]
+use crate::path::Path;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod recorder;
+mod simple;
+mod tee;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod validating;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub use self::recorder::Recorder;
+pub use self::simple::{Compound, SimpleSerializer};
+pub use self::tee::Tee;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub use self::validating::{CompoundKind, Validating, ValidationError, ValidationState};
 
 /// A data structure that can be serialized with dynamic [`Serializer`].
 ///
 /// This trait mirrors the functionality of [`serde::Serialize`] but is dyn-compatible.
 ///
-/// The trait objects `dyn Serialize` also implement [`serde::Serialize`], which means they can
-/// be serialized just like common data structures.
+/// The trait objects `dyn Serialize`, as well as `dyn Serialize + Send`, `dyn Serialize + Sync` and
+/// `dyn Serialize + Send + Sync`, also implement [`serde::Serialize`], which means they can be
+/// serialized just like common data structures. Note that `Arc<dyn Serialize + Send + Sync>`
+/// requires the "rc" feature of `serde`.
 ///
 /// # Implementation
 ///
@@ -71,18 +110,114 @@ impl<T: ?Sized + serde::Serialize> Serialize for T {
 impl serde::Serialize for dyn Serialize + '_ {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         // We can serialize `dyn Serialize` with `dyn Serializer`.
-        let mut serializer = InplaceSerializer::Serializer(serializer);
-        let result = self.dyn_serialize(&mut serializer);
-        match serializer {
-            // `InplaceSerializer::Ok(_)` implies `result` is `Ok(_)`.
-            InplaceSerializer::Ok(ok) => Ok(ok),
-            // `InplaceSerializer::Err(_)` implies `result` is `Err(SerializerError::Error)`.
-            InplaceSerializer::Error(error) => Err(error),
-            _ => match result {
-                Ok(_) => unreachable!("`result` is `Ok(_)` if and only if `serializer` is `Ok(_)`"),
-                Err(error) => Err(error.into_ser_error()),
-            },
-        }
+        with_dyn(serializer, |serializer| self.dyn_serialize(serializer))
+    }
+}
+
+impl serde::Serialize for dyn Serialize + Send + '_ {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        with_dyn(serializer, |serializer| self.dyn_serialize(serializer))
+    }
+}
+
+impl serde::Serialize for dyn Serialize + Sync + '_ {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        with_dyn(serializer, |serializer| self.dyn_serialize(serializer))
+    }
+}
+
+impl serde::Serialize for dyn Serialize + Send + Sync + '_ {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        with_dyn(serializer, |serializer| self.dyn_serialize(serializer))
+    }
+}
+
+/// A [`Serialize`] that can be cloned into a new box.
+///
+/// Since `Clone` is not dyn-compatible, `Box<dyn Serialize>` cannot be cloned. Use
+/// `Box<dyn SerializeClone>` instead, which implements both [`Clone`] and [`serde::Serialize`], as
+/// do `Box<dyn SerializeClone + Send>` and `Box<dyn SerializeClone + Send + Sync>`, whose clones
+/// can be sent to other threads. Every `T: Clone + serde::Serialize + 'static` implements this
+/// trait automatically.
+///
+/// To share a value across threads without cloning it, prefer `Arc<dyn Serialize + Send + Sync>`.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub trait SerializeClone: Serialize {
+    /// Clones this value into a new box.
+    fn dyn_clone_box(&self) -> Box<dyn SerializeClone>;
+
+    /// Clones this value into a new box that is [`Send`].
+    fn dyn_clone_box_send(&self) -> Box<dyn SerializeClone + Send>
+    where
+        Self: Send;
+
+    /// Clones this value into a new box that is [`Send`] and [`Sync`].
+    fn dyn_clone_box_send_sync(&self) -> Box<dyn SerializeClone + Send + Sync>
+    where
+        Self: Send + Sync;
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<T: Clone + Serialize + 'static> SerializeClone for T {
+    fn dyn_clone_box(&self) -> Box<dyn SerializeClone> {
+        Box::new(self.clone())
+    }
+
+    fn dyn_clone_box_send(&self) -> Box<dyn SerializeClone + Send>
+    where
+        Self: Send,
+    {
+        Box::new(self.clone())
+    }
+
+    fn dyn_clone_box_send_sync(&self) -> Box<dyn SerializeClone + Send + Sync>
+    where
+        Self: Send + Sync,
+    {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Clone for Box<dyn SerializeClone> {
+    fn clone(&self) -> Self {
+        // Clone the inner value, rather than the box which implements `SerializeClone` as well.
+        (**self).dyn_clone_box()
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Clone for Box<dyn SerializeClone + Send> {
+    fn clone(&self) -> Self {
+        (**self).dyn_clone_box_send()
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Clone for Box<dyn SerializeClone + Send + Sync> {
+    fn clone(&self) -> Self {
+        (**self).dyn_clone_box_send_sync()
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl serde::Serialize for dyn SerializeClone + '_ {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        with_dyn(serializer, |serializer| self.dyn_serialize(serializer))
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl serde::Serialize for dyn SerializeClone + Send + '_ {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        with_dyn(serializer, |serializer| self.dyn_serialize(serializer))
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl serde::Serialize for dyn SerializeClone + Send + Sync + '_ {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        with_dyn(serializer, |serializer| self.dyn_serialize(serializer))
     }
 }
 
@@ -90,16 +225,19 @@ impl serde::Serialize for dyn Serialize + '_ {
 ///
 /// This trait mirrors the functionality of [`serde::Serializer`] but is dyn-compatible. The
 /// mirrored functions always start with "dyn_", for example, `dyn_serialize_bool` in `Serializer`
-/// vs. `serialize_bool` in `serde::Serializer`. But these functions are not supported:
+/// vs. `serialize_bool` in `serde::Serializer`.
 ///
-/// - [`serde::Serializer::collect_seq`].
-/// - [`serde::Serializer::collect_map`].
+/// The items of [`Serializer::dyn_collect_seq`] and [`Serializer::dyn_collect_map`] are borrowed,
+/// so [`serde::Serializer::collect_seq`] and [`serde::Serializer::collect_map`] of
+/// `&mut dyn Serializer`, whose items are owned, lend them one at a time without buffering. Each
+/// item must be serialized before the next one is taken, otherwise the serialization fails.
 ///
 /// # Implementation
 ///
 /// Alhrough its legal to implement this trait directly, it's more recommended to use the provided
 /// function `<dyn Serializer>::new` to automatically convert [`serde::Serializer`] into dynamic
-/// [`Serializer`].
+/// [`Serializer`]. For a hand-written serializer, implement [`SimpleSerializer`] instead, which
+/// requires much fewer methods.
 ///
 /// # Examples
 ///
@@ -348,6 +486,42 @@ pub trait Serializer {
         len: usize,
     ) -> SerializerResult<&mut dyn SerializeStructVariant>;
 
+    /// Collect an iterator as a sequence.
+    ///
+    /// By default, the items are serialized one by one with `dyn_serialize_seq`, and the length is
+    /// given if the iterator knows it exactly. [`InplaceSerializer`] makes use of the optimized
+    /// `collect_seq` of the concrete serializer instead.
+    ///
+    /// Also see [`serde::Serializer::collect_seq`]
+    fn dyn_collect_seq(
+        &mut self,
+        iter: &mut dyn Iterator<Item = &dyn Serialize>,
+    ) -> SerializerResult<()> {
+        let serializer = self.dyn_serialize_seq(exact_len(iter))?;
+        for item in iter {
+            serializer.dyn_serialize_element(item)?;
+        }
+        serializer.dyn_end()
+    }
+
+    /// Collect an iterator as a map.
+    ///
+    /// By default, the entries are serialized one by one with `dyn_serialize_map`, and the length
+    /// is given if the iterator knows it exactly. [`InplaceSerializer`] makes use of the optimized
+    /// `collect_map` of the concrete serializer instead.
+    ///
+    /// Also see [`serde::Serializer::collect_map`]
+    fn dyn_collect_map(
+        &mut self,
+        iter: &mut dyn Iterator<Item = (&dyn Serialize, &dyn Serialize)>,
+    ) -> SerializerResult<()> {
+        let serializer = self.dyn_serialize_map(exact_len(iter))?;
+        for (key, value) in iter {
+            serializer.dyn_serialize_entry(key, value)?;
+        }
+        serializer.dyn_end()
+    }
+
     /// Serialize a string produced by an implementation of `Display`.
     ///
     /// Also see [`serde::Serializer::collect_str`]
@@ -383,6 +557,115 @@ impl dyn Serializer {
     }
 }
 
+/// Runs `f` with the dynamic version of `serializer`, then recovers the concrete result.
+///
+/// The closure receives `&mut dyn Serializer` and is expected to serialize exactly one value.
+/// Afterwards, the [`InplaceSerializer`] is inspected so that `S::Ok` is returned on success, and
+/// the original `S::Error` is returned on failure instead of a flattened [`SerializeError`].
+///
+/// # Examples
+///
+/// ```
+/// # use serde_dyn::Serializer;
+/// let serializer = serde_json::value::Serializer;
+/// let value = serde_dyn::ser::with_dyn(serializer, |serializer: &mut dyn Serializer| {
+///     serializer.dyn_serialize_str("Hello, world!")?;
+///     Ok(())
+/// });
+/// assert_eq!(value.unwrap(), serde_json::json!("Hello, world!"));
+/// ```
+pub fn with_dyn<S, F>(serializer: S, f: F) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+    F: FnOnce(&mut dyn Serializer) -> SerializeResult<()>,
+{
+    let mut serializer = InplaceSerializer::Serializer(serializer);
+    let result = f(&mut serializer);
+    match (serializer, result) {
+        // The concrete error is always more precise than the dynamic one.
+        (InplaceSerializer::Error(error), _) => Err(error),
+        (_, Err(error)) => Err(error.into_ser_error()),
+        (InplaceSerializer::Ok(ok), Ok(())) => Ok(ok),
+        // The closure returns successfully without finishing the serialization.
+        (serializer, Ok(())) => {
+            Err(SerializeError::from(serializer.wrong_state("ser::with_dyn")).into_ser_error())
+        }
+    }
+}
+
+/// An owned item lent to [`Serializer::dyn_collect_seq`] or [`Serializer::dyn_collect_map`].
+///
+/// The item is moved out when it's serialized, and replaced when the next item is taken.
+struct Lent<T>(Cell<Option<T>>);
+
+impl<T: serde::Serialize> serde::Serialize for Lent<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0.take() {
+            Some(item) => item.serialize(serializer),
+            None => Err(serde::ser::Error::custom(
+                "the item of the iterator was serialized already or replaced by the next one",
+            )),
+        }
+    }
+}
+
+/// Lends the items of `iter` one at a time, all of them through `item`.
+struct LendSeq<'a, I: Iterator> {
+    iter: I,
+    item: &'a Lent<I::Item>,
+}
+
+impl<'a, I> Iterator for LendSeq<'a, I>
+where
+    I: Iterator<Item: serde::Serialize + 'a>,
+{
+    type Item = &'a dyn Serialize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.item.0.set(Some(self.iter.next()?));
+        Some(self.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// Lends the entries of `iter` one at a time, all of them through `key` and `value`.
+struct LendMap<'a, I, K, V> {
+    iter: I,
+    key: &'a Lent<K>,
+    value: &'a Lent<V>,
+}
+
+impl<'a, I, K, V> Iterator for LendMap<'a, I, K, V>
+where
+    I: Iterator<Item = (K, V)>,
+    K: serde::Serialize + 'a,
+    V: serde::Serialize + 'a,
+{
+    type Item = (&'a dyn Serialize, &'a dyn Serialize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self.iter.next()?;
+        self.key.0.set(Some(key));
+        self.value.0.set(Some(value));
+        Some((self.key, self.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// Returns the length of `iter` if it's known exactly.
+fn exact_len(iter: &dyn Iterator<Item = impl Sized>) -> Option<usize> {
+    match iter.size_hint() {
+        (lo, Some(hi)) if lo == hi => Some(lo),
+        _ => None,
+    }
+}
+
 impl<'a> serde::Serializer for &'a mut (dyn Serializer + '_) {
     type Ok = ();
     type Error = SerializeError;
@@ -598,16 +881,13 @@ impl<'a> serde::Serializer for &'a mut (dyn Serializer + '_) {
         I: IntoIterator,
         <I as IntoIterator>::Item: serde::Serialize,
     {
-        let iter = iter.into_iter();
-        let len = match iter.size_hint() {
-            (lo, Some(hi)) if lo == hi => Some(lo),
-            _ => None,
+        let item = Lent(Cell::new(None));
+        let mut iter = LendSeq {
+            iter: iter.into_iter(),
+            item: &item,
         };
-        let serializer = self.dyn_serialize_seq(len)?;
-        for item in iter {
-            serializer.dyn_serialize_element(&item)?;
-        }
-        serializer.dyn_end().map_err(SerializeError::from)
+        self.dyn_collect_seq(&mut iter)
+            .map_err(SerializeError::from)
     }
 
     fn collect_map<K, V, I>(self, iter: I) -> SerializeResult<()>
@@ -616,16 +896,14 @@ impl<'a> serde::Serializer for &'a mut (dyn Serializer + '_) {
         V: serde::Serialize,
         I: IntoIterator<Item = (K, V)>,
     {
-        let iter = iter.into_iter();
-        let len = match iter.size_hint() {
-            (lo, Some(hi)) if lo == hi => Some(lo),
-            _ => None,
+        let (key, value) = (Lent(Cell::new(None)), Lent(Cell::new(None)));
+        let mut iter = LendMap {
+            iter: iter.into_iter(),
+            key: &key,
+            value: &value,
         };
-        let serializer = self.dyn_serialize_map(len)?;
-        for (key, value) in iter {
-            serializer.dyn_serialize_entry(&key, &value)?;
-        }
-        serializer.dyn_end().map_err(SerializeError::from)
+        self.dyn_collect_map(&mut iter)
+            .map_err(SerializeError::from)
     }
 
     #[inline]
@@ -954,52 +1232,78 @@ pub enum SerializerError {
     /// An error occurred during serialization.
     #[default]
     Error,
+    /// The operation is called on a serializer in the wrong state.
+    WrongState {
+        /// The attempted operation, such as `"Serializer::dyn_serialize_str"`.
+        operation: &'static str,
+        /// The state that the serializer is found in.
+        found: SerializerState,
+    },
+}
+
+impl fmt::Display for SerializerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerializerError::Error => f.write_str("an error occurred during the serialization"),
+            SerializerError::WrongState { operation, found } => {
+                write!(f, "`{operation}` is called, but the serializer {found}")
+            }
+        }
+    }
+}
+
+impl error::Error for SerializerError {}
+
+/// The state of an [`InplaceSerializer`], reported by [`SerializerError::WrongState`].
+///
+/// It is displayed as a predicate of "the serializer", for example "has already produced `Ok`".
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SerializerState {
     /// The serializer is not ready.
+    None,
+    /// The serialization has done successfully.
+    Ok,
+    /// The serialization has done unsuccessfully.
+    Error,
+    /// The serializer is ready.
     Serializer,
-    /// The serializer is not ready to serialize the sequence.
+    /// The serializer is ready to serialize a sequence.
     SerializeSeq,
-    /// The serializer is not ready to serialize the tuple.
+    /// The serializer is ready to serialize a tuple.
     SerializeTuple,
-    /// The serializer is not ready to serialize the tuple struct.
+    /// The serializer is ready to serialize a tuple struct.
     SerializeTupleStruct,
-    /// The serializer is not ready to serialize the tuple variant.
+    /// The serializer is ready to serialize a tuple variant.
     SerializeTupleVariant,
-    /// The serializer is not ready to serialize the map.
+    /// The serializer is ready to serialize a map.
     SerializeMap,
-    /// The serializer is not ready to serialize the struct.
+    /// The serializer has serialized a map key, and is ready to serialize its value.
+    SerializeMapValue,
+    /// The serializer is ready to serialize a struct.
     SerializeStruct,
-    /// The serializer is not ready to serialize the struct variant.
+    /// The serializer is ready to serialize a struct variant.
     SerializeStructVariant,
 }
 
-impl fmt::Display for SerializerError {
+impl fmt::Display for SerializerState {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(match self {
-            SerializerError::Error => "an error occurred during the serialization",
-            SerializerError::Serializer => "the serializer is not ready",
-            SerializerError::SerializeSeq => {
-                "the serializer is not ready to serialize the sequence"
-            }
-            SerializerError::SerializeTuple => "the serializer is not ready to serialize the tuple",
-            SerializerError::SerializeTupleStruct => {
-                "the serializer is not ready to serialize the tuple struct"
-            }
-            SerializerError::SerializeTupleVariant => {
-                "the serializer is not ready to serialize the tuple variant"
-            }
-            SerializerError::SerializeMap => "the serializer is not ready to serialize the map",
-            SerializerError::SerializeStruct => {
-                "the serializer is not ready to serialize the struct"
-            }
-            SerializerError::SerializeStructVariant => {
-                "the serializer is not ready to serialize the struct variant"
-            }
+            SerializerState::None => "is not ready",
+            SerializerState::Ok => "has already produced `Ok`",
+            SerializerState::Error => "has already failed",
+            SerializerState::Serializer => "is ready for a value",
+            SerializerState::SerializeSeq => "is serializing a sequence",
+            SerializerState::SerializeTuple => "is serializing a tuple",
+            SerializerState::SerializeTupleStruct => "is serializing a tuple struct",
+            SerializerState::SerializeTupleVariant => "is serializing a tuple variant",
+            SerializerState::SerializeMap => "is serializing a map",
+            SerializerState::SerializeMapValue => "is serializing a map and expects a value",
+            SerializerState::SerializeStruct => "is serializing a struct",
+            SerializerState::SerializeStructVariant => "is serializing a struct variant",
         })
     }
 }
 
-impl error::Error for SerializerError {}
-
 /// Aliased to [`Result`] type with error type [`SerializeError`].
 pub type SerializeResult<T> = Result<T, SerializeError>;
 
@@ -1007,19 +1311,92 @@ pub type SerializeResult<T> = Result<T, SerializeError>;
 ///
 /// This error tells why the dynamic serialization failed and is returned by
 /// [`&mut dyn Serializer`](crate::ser::Serializer) as [`serde::Serializer`].
+///
+/// With `std` or `alloc`, the error is as large as a pointer. It is always [`Send`], [`Sync`] and
+/// [`Clone`], so that it can cross threads and async tasks.
+///
+/// Without `std` or `alloc`, the message is discarded, unless the `heapless-errors` feature keeps
+/// it in an inline buffer of 64 bytes, which the error then carries by value.
 #[repr(transparent)]
-#[cfg_attr(not(any(feature = "std", feature = "alloc")), derive(Clone, Copy))]
+#[derive(Clone)]
+#[cfg_attr(not(any(feature = "std", feature = "alloc")), derive(Copy))]
 pub struct SerializeError(
-    #[cfg(any(feature = "std", feature = "alloc"))] NonNull<String>,
-    #[cfg(not(any(feature = "std", feature = "alloc")))] SerializerError,
+    /// `None` stands for [`SerializerError::Error`], the most common error, so it never allocates.
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    Option<Box<ErrorImpl>>,
+    #[cfg(all(
+        not(any(feature = "std", feature = "alloc")),
+        not(feature = "heapless-errors")
+    ))]
+    SerializerError,
+    /// The message rendered inline, see the `heapless-errors` feature.
+    #[cfg(all(
+        not(any(feature = "std", feature = "alloc")),
+        feature = "heapless-errors"
+    ))]
+    InlineMessage,
 );
 
 impl SerializeError {
+    /// Creates an error that wraps the original error transparently.
+    ///
+    /// The message of the new error is the message of `error`, and its [`source`] is the source
+    /// of `error`, so that the chain of errors does not repeat the message. The original error is
+    /// returned by [`downcast_ref`](Self::downcast_ref). An error of this type is returned as is.
+    ///
+    /// [`source`]: error::Error::source
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde_dyn::ser::SerializeError;
+    /// let error = SerializeError::from_source(std::io::Error::other("disk full"));
+    /// assert_eq!(error.to_string(), "disk full");
+    /// assert!(error.downcast_ref::<std::io::Error>().is_some());
+    /// // The message is not repeated by the source.
+    /// assert!(std::error::Error::source(&error).is_none());
+    /// ```
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[cold]
+    pub fn from_source<E>(error: E) -> Self
+    where
+        E: error::Error + Send + Sync + 'static,
+    {
+        // Wrapping the error in itself would hide its source.
+        if let Some(error) = (&error as &dyn Any).downcast_ref::<Self>() {
+            return error.clone();
+        }
+
+        SerializeError::new(ErrorImpl {
+            message: error.to_string(),
+            path: None,
+            source: Some(Arc::new(error)),
+        })
+    }
+
+    /// Returns the original error if it's of type `E`.
+    ///
+    /// Also see [`SerializeError::from_source`].
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[must_use]
+    pub fn downcast_ref<E: error::Error + 'static>(&self) -> Option<&E> {
+        self.as_inner().ok()?.source.as_deref()?.downcast_ref()
+    }
+
+    /// Returns the path to the value where the serialization failed.
+    ///
+    /// The path is only recorded by [`path::serialize`](crate::path::serialize).
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[must_use]
+    pub fn path(&self) -> Option<&Path> {
+        self.as_inner().ok()?.path.as_ref()
+    }
+
     #[cold]
     #[must_use]
     fn into_ser_error<E: serde::ser::Error>(self) -> E {
         #[cfg(any(feature = "std", feature = "alloc"))]
-        match self.into_string() {
+        match self.into_inner() {
             Err(error) => E::custom(error),
             Ok(error) => E::custom(error),
         }
@@ -1033,75 +1410,35 @@ impl SerializeError {
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 impl SerializeError {
-    const fn encode(error: SerializerError) -> NonZeroUsize {
-        const ALIGN: usize = mem::align_of::<String>();
-        NonZeroUsize::new(((error as usize) << ALIGN.trailing_zeros()) | ALIGN.strict_sub(1))
-            .unwrap()
-    }
-
-    const fn decode(error: NonZeroUsize) -> Option<SerializerError> {
-        const SERIALIZE_ERROR_ERROR: NonZeroUsize = SerializeError::encode(SerializerError::Error);
-        const SERIALIZE_ERROR_SERIALIZER: NonZeroUsize =
-            SerializeError::encode(SerializerError::Serializer);
-        const SERIALIZE_ERROR_SERIALIZE_SEQ: NonZeroUsize =
-            SerializeError::encode(SerializerError::SerializeSeq);
-        const SERIALIZE_ERROR_SERIALIZE_TUPLE: NonZeroUsize =
-            SerializeError::encode(SerializerError::SerializeTuple);
-        const SERIALIZE_ERROR_SERIALIZE_TUPLE_STRUCT: NonZeroUsize =
-            SerializeError::encode(SerializerError::SerializeTupleStruct);
-        const SERIALIZE_ERROR_SERIALIZE_TUPLE_VARIANT: NonZeroUsize =
-            SerializeError::encode(SerializerError::SerializeTupleVariant);
-        const SERIALIZE_ERROR_SERIALIZE_MAP: NonZeroUsize =
-            SerializeError::encode(SerializerError::SerializeMap);
-        const SERIALIZE_ERROR_SERIALIZE_STRUCT: NonZeroUsize =
-            SerializeError::encode(SerializerError::SerializeStruct);
-        const SERIALIZE_ERROR_SERIALIZE_STRUCT_VARIANT: NonZeroUsize =
-            SerializeError::encode(SerializerError::SerializeStructVariant);
-
-        match error {
-            SERIALIZE_ERROR_ERROR => Some(SerializerError::Error),
-            SERIALIZE_ERROR_SERIALIZER => Some(SerializerError::Serializer),
-            SERIALIZE_ERROR_SERIALIZE_SEQ => Some(SerializerError::SerializeSeq),
-            SERIALIZE_ERROR_SERIALIZE_TUPLE => Some(SerializerError::SerializeTuple),
-            SERIALIZE_ERROR_SERIALIZE_TUPLE_STRUCT => Some(SerializerError::SerializeTupleStruct),
-            SERIALIZE_ERROR_SERIALIZE_TUPLE_VARIANT => Some(SerializerError::SerializeTupleVariant),
-            SERIALIZE_ERROR_SERIALIZE_MAP => Some(SerializerError::SerializeMap),
-            SERIALIZE_ERROR_SERIALIZE_STRUCT => Some(SerializerError::SerializeStruct),
-            SERIALIZE_ERROR_SERIALIZE_STRUCT_VARIANT => {
-                Some(SerializerError::SerializeStructVariant)
-            }
-            _ => None,
-        }
+    fn new(error: ErrorImpl) -> Self {
+        SerializeError(Some(Box::new(error)))
     }
 
-    fn into_string(self) -> SerializerResult<String> {
-        let this = mem::ManuallyDrop::new(self);
-
-        match SerializeError::decode(this.0.expose_provenance()) {
-            Some(error) => Err(error),
-            // TODO: Replace `Box::from_raw` with `Box::from_non_null` once it's stablized.
-            // SAFETY: We have handled the `SerializerError` case.
-            None => Ok(*unsafe { Box::from_raw(this.0.as_ptr()) }),
-        }
+    #[cold]
+    pub(crate) fn with_path(self, path: Path) -> Self {
+        let error = match self.into_inner() {
+            Ok(error) => error,
+            Err(error) => ErrorImpl {
+                message: error.to_string(),
+                path: None,
+                source: None,
+            },
+        };
+        SerializeError::new(ErrorImpl {
+            path: Some(path),
+            ..error
+        })
     }
 
-    fn as_string(&self) -> SerializerResult<&String> {
-        match SerializeError::decode(self.0.expose_provenance()) {
-            Some(error) => Err(error),
-            // SAFETY: We have handled the `SerializerError` case.
-            None => Ok(unsafe { self.0.as_ref() }),
+    fn into_inner(self) -> SerializerResult<ErrorImpl> {
+        match self.0 {
+            Some(error) => Ok(*error),
+            None => Err(SerializerError::Error),
         }
     }
-}
-
-#[cfg(any(feature = "std", feature = "alloc"))]
-impl Drop for SerializeError {
-    fn drop(&mut self) {
-        const REPLACEMENT: SerializeError = SerializeError(NonNull::without_provenance(
-            SerializeError::encode(SerializerError::Error),
-        ));
 
-        let _ = mem::replace(self, REPLACEMENT).into_string();
+    fn as_inner(&self) -> SerializerResult<&ErrorImpl> {
+        self.0.as_deref().ok_or(SerializerError::Error)
     }
 }
 
@@ -1110,13 +1447,29 @@ impl From<SerializerError> for SerializeError {
     #[inline(never)]
     fn from(value: SerializerError) -> Self {
         #[cfg(any(feature = "std", feature = "alloc"))]
+        match value {
+            SerializerError::Error => SerializeError(None),
+            // The other errors only happen when the dynamic traits are misused.
+            error => SerializeError::new(ErrorImpl {
+                message: error.to_string(),
+                path: None,
+                source: None,
+            }),
+        }
+
+        #[cfg(all(
+            not(any(feature = "std", feature = "alloc")),
+            not(feature = "heapless-errors")
+        ))]
         {
-            SerializeError(NonNull::without_provenance(SerializeError::encode(value)))
+            SerializeError(value)
         }
-
-        #[cfg(not(any(feature = "std", feature = "alloc")))]
+        #[cfg(all(
+            not(any(feature = "std", feature = "alloc")),
+            feature = "heapless-errors"
+        ))]
         {
-            SerializeError(value)
+            SerializeError(InlineMessage::new(&value))
         }
     }
 }
@@ -1127,26 +1480,36 @@ impl serde::ser::Error for SerializeError {
     fn custom<T: fmt::Display>(msg: T) -> Self {
         #[cfg(any(feature = "std", feature = "alloc"))]
         {
-            // TODO: Replace `Box::into_raw` with `Box::into_non_null` once it's stablized.
-            SerializeError(
-                NonNull::new(Box::into_raw(Box::new(msg.to_string())))
-                    .expect("`Box::into_raw` never returns null pointer"),
-            )
+            SerializeError::new(ErrorImpl {
+                message: msg.to_string(),
+                path: None,
+                source: None,
+            })
         }
 
-        #[cfg(not(any(feature = "std", feature = "alloc")))]
+        #[cfg(all(
+            not(any(feature = "std", feature = "alloc")),
+            not(feature = "heapless-errors")
+        ))]
         {
             let _ = msg;
             SerializeError(SerializerError::Error)
         }
+        #[cfg(all(
+            not(any(feature = "std", feature = "alloc")),
+            feature = "heapless-errors"
+        ))]
+        {
+            SerializeError(InlineMessage::new(&msg))
+        }
     }
 }
 
 impl fmt::Debug for SerializeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         #[cfg(any(feature = "std", feature = "alloc"))]
-        match self.as_string() {
-            Ok(error) => f.write_str(error),
+        match self.as_inner() {
+            Ok(error) => fmt::Display::fmt(error, f),
             Err(error) => error.fmt(f),
         }
 
@@ -1160,8 +1523,8 @@ impl fmt::Debug for SerializeError {
 impl fmt::Display for SerializeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         #[cfg(any(feature = "std", feature = "alloc"))]
-        match self.as_string() {
-            Ok(error) => f.write_str(error),
+        match self.as_inner() {
+            Ok(error) => fmt::Display::fmt(error, f),
             Err(error) => error.fmt(f),
         }
 
@@ -1172,7 +1535,42 @@ impl fmt::Display for SerializeError {
     }
 }
 
-impl error::Error for SerializeError {}
+impl error::Error for SerializeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        #[cfg(any(feature = "std", feature = "alloc"))]
+        match self.as_inner() {
+            // The wrapped error has the same message, see `from_source`.
+            Ok(error) => error.source.as_deref()?.source(),
+            Err(_) => None,
+        }
+
+        #[cfg(not(any(feature = "std", feature = "alloc")))]
+        {
+            None
+        }
+    }
+}
+
+/// The heap-allocated payload of [`SerializeError`].
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[derive(Clone)]
+struct ErrorImpl {
+    message: String,
+    path: Option<Path>,
+    source: Option<Arc<dyn error::Error + Send + Sync>>,
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl fmt::Display for ErrorImpl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(path) = &self.path
+            && !path.is_empty()
+        {
+            write!(f, "{path}: ")?;
+        }
+        f.write_str(&self.message)
+    }
+}
 
 /// An implementation of the [`Serializer`] trait, which serves as a bridge between dynamic and
 /// static serialization.
@@ -1199,6 +1597,8 @@ pub enum InplaceSerializer<S: serde::Serializer> {
     SerializeTupleVariant(S::SerializeTupleVariant),
     /// The serializer is ready to serialize a map.
     SerializeMap(S::SerializeMap),
+    /// The serializer has serialized a map key, and is ready to serialize its value.
+    SerializeMapValue(S::SerializeMap),
     /// The serializer is ready to serialize a struct.
     SerializeStruct(S::SerializeStruct),
     /// The serializer is ready to serialize a struct variant.
@@ -1206,11 +1606,96 @@ pub enum InplaceSerializer<S: serde::Serializer> {
 }
 
 impl<S: serde::Serializer> InplaceSerializer<S> {
+    /// Returns the current state of the serializer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde_dyn::Serializer;
+    /// # use serde_dyn::ser::SerializerState;
+    /// let mut json = serde_json::Serializer::new(Vec::new());
+    /// let mut serializer = <dyn Serializer>::new(&mut json);
+    /// assert_eq!(serializer.state(), SerializerState::Serializer);
+    /// serializer.dyn_serialize_unit().unwrap();
+    /// assert_eq!(serializer.state(), SerializerState::Ok);
+    /// ```
+    #[must_use]
+    pub fn state(&self) -> SerializerState {
+        match self {
+            InplaceSerializer::None => SerializerState::None,
+            InplaceSerializer::Ok(_) => SerializerState::Ok,
+            InplaceSerializer::Error(_) => SerializerState::Error,
+            InplaceSerializer::Serializer(_) => SerializerState::Serializer,
+            InplaceSerializer::SerializeSeq(_) => SerializerState::SerializeSeq,
+            InplaceSerializer::SerializeTuple(_) => SerializerState::SerializeTuple,
+            InplaceSerializer::SerializeTupleStruct(_) => SerializerState::SerializeTupleStruct,
+            InplaceSerializer::SerializeTupleVariant(_) => SerializerState::SerializeTupleVariant,
+            InplaceSerializer::SerializeMap(_) => SerializerState::SerializeMap,
+            InplaceSerializer::SerializeMapValue(_) => SerializerState::SerializeMapValue,
+            InplaceSerializer::SerializeStruct(_) => SerializerState::SerializeStruct,
+            InplaceSerializer::SerializeStructVariant(_) => SerializerState::SerializeStructVariant,
+        }
+    }
+
+    #[cold]
+    fn wrong_state(&self, operation: &'static str) -> SerializerError {
+        SerializerError::WrongState {
+            operation,
+            found: self.state(),
+        }
+    }
+
+    /// Puts back the `state` taken out for `operation`, and returns the error of the misuse.
+    #[cold]
+    fn restore(&mut self, state: Self, operation: &'static str) -> SerializerError {
+        let error = state.wrong_state(operation);
+        *self = state;
+        error
+    }
+
     fn write_error(&mut self, error: S::Error) -> SerializerError {
         *self = InplaceSerializer::Error(error);
         SerializerError::Error
     }
 
+    /// Consumes the serializer after `result` of the dynamic serialization, and returns the
+    /// output of the concrete serializer.
+    ///
+    /// This works like [`with_dyn`], except that the error is a [`SerializeError`]. If the
+    /// concrete serializer failed, its error is kept as the source, see
+    /// [`SerializeError::downcast_ref`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the recorded concrete error, the error of `result`, or a
+    /// [`SerializerError::WrongState`] if the serialization has not been finished.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde_dyn::{Serialize, Serializer};
+    /// let mut serializer = <dyn Serializer>::new(serde_json::value::Serializer);
+    /// let result = (&[(vec![1], 2)].into_iter().collect::<std::collections::BTreeMap<_, _>>()
+    ///     as &dyn Serialize)
+    ///     .dyn_serialize(&mut serializer);
+    /// let error = serializer.into_result(result).unwrap_err();
+    /// assert!(error.downcast_ref::<serde_json::Error>().is_some());
+    /// ```
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    pub fn into_result(self, result: SerializeResult<()>) -> SerializeResult<S::Ok>
+    where
+        S::Error: error::Error + Send + Sync + 'static,
+    {
+        match (self, result) {
+            (InplaceSerializer::Error(error), _) => Err(SerializeError::from_source(error)),
+            (_, Err(error)) => Err(error),
+            (InplaceSerializer::Ok(ok), Ok(())) => Ok(ok),
+            (serializer, Ok(())) => Err(SerializeError::from(
+                serializer.wrong_state("InplaceSerializer::into_result"),
+            )),
+        }
+    }
+
     fn write_with<T, F>(&mut self, f: F, r: Result<T, S::Error>) -> SerializerResult<()>
     where
         F: FnOnce(T) -> Self,
@@ -1225,162 +1710,182 @@ impl<S: serde::Serializer> InplaceSerializer<S> {
 
 impl<S: serde::Serializer> Serializer for InplaceSerializer<S> {
     fn dyn_serialize_bool(&mut self, v: bool) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_bool(v))
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_bool(v))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_bool")),
         }
     }
 
     fn dyn_serialize_i8(&mut self, v: i8) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_i8(v))
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_i8(v))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_i8")),
         }
     }
 
     fn dyn_serialize_i16(&mut self, v: i16) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_i16(v))
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_i16(v))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_i16")),
         }
     }
 
     fn dyn_serialize_i32(&mut self, v: i32) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_i32(v))
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_i32(v))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_i32")),
         }
     }
 
     fn dyn_serialize_i64(&mut self, v: i64) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_i64(v))
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_i64(v))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_i64")),
         }
     }
 
     fn dyn_serialize_i128(&mut self, v: i128) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_i128(v))
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_i128(v))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_i128")),
         }
     }
 
     fn dyn_serialize_u8(&mut self, v: u8) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_u8(v))
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_u8(v))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_u8")),
         }
     }
 
     fn dyn_serialize_u16(&mut self, v: u16) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_u16(v))
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_u16(v))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_u16")),
         }
     }
 
     fn dyn_serialize_u32(&mut self, v: u32) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_u32(v))
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_u32(v))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_u32")),
         }
     }
 
     fn dyn_serialize_u64(&mut self, v: u64) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_u64(v))
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_u64(v))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_u64")),
         }
     }
 
     fn dyn_serialize_u128(&mut self, v: u128) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_u128(v))
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_u128(v))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_u128")),
         }
     }
 
     fn dyn_serialize_f32(&mut self, v: f32) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_f32(v))
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_f32(v))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_f32")),
         }
     }
 
     fn dyn_serialize_f64(&mut self, v: f64) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_f64(v))
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_f64(v))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_f64")),
         }
     }
 
     fn dyn_serialize_char(&mut self, v: char) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_char(v))
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_char(v))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_char")),
         }
     }
 
     fn dyn_serialize_str(&mut self, v: &str) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_str(v))
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_str(v))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_str")),
         }
     }
 
     fn dyn_serialize_bytes(&mut self, v: &[u8]) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_bytes(v))
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_bytes(v))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_bytes")),
         }
     }
 
     fn dyn_serialize_none(&mut self) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_none())
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_none())
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_none")),
         }
     }
 
     fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_some(value))
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_some(value))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_some")),
         }
     }
 
     fn dyn_serialize_unit(&mut self) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_unit())
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_unit())
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_unit")),
         }
     }
 
     fn dyn_serialize_unit_struct(&mut self, name: &'static str) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.serialize_unit_struct(name))
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.serialize_unit_struct(name))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_unit_struct")),
         }
     }
 
@@ -1390,13 +1895,12 @@ impl<S: serde::Serializer> Serializer for InplaceSerializer<S> {
         variant_index: u32,
         variant: &'static str,
     ) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => self.write_with(
                 InplaceSerializer::Ok,
                 ser.serialize_unit_variant(name, variant_index, variant),
-            )
-        } else {
-            Err(SerializerError::Serializer)
+            ),
+            state => Err(self.restore(state, "Serializer::dyn_serialize_unit_variant")),
         }
     }
 
@@ -1405,13 +1909,12 @@ impl<S: serde::Serializer> Serializer for InplaceSerializer<S> {
         name: &'static str,
         value: &dyn Serialize,
     ) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => self.write_with(
                 InplaceSerializer::Ok,
                 ser.serialize_newtype_struct(name, value),
-            )
-        } else {
-            Err(SerializerError::Serializer)
+            ),
+            state => Err(self.restore(state, "Serializer::dyn_serialize_newtype_struct")),
         }
     }
 
@@ -1422,31 +1925,32 @@ impl<S: serde::Serializer> Serializer for InplaceSerializer<S> {
         variant: &'static str,
         value: &dyn Serialize,
     ) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => self.write_with(
                 InplaceSerializer::Ok,
                 ser.serialize_newtype_variant(name, variant_index, variant, value),
-            )
-        } else {
-            Err(SerializerError::Serializer)
+            ),
+            state => Err(self.restore(state, "Serializer::dyn_serialize_newtype_variant")),
         }
     }
 
     fn dyn_serialize_seq(&mut self, len: Option<usize>) -> SerializerResult<&mut dyn SerializeSeq> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::SerializeSeq, ser.serialize_seq(len))?;
-            Ok(self)
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::SerializeSeq, ser.serialize_seq(len))?;
+                Ok(self)
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_seq")),
         }
     }
 
     fn dyn_serialize_tuple(&mut self, len: usize) -> SerializerResult<&mut dyn SerializeTuple> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::SerializeTuple, ser.serialize_tuple(len))?;
-            Ok(self)
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::SerializeTuple, ser.serialize_tuple(len))?;
+                Ok(self)
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_tuple")),
         }
     }
 
@@ -1455,14 +1959,15 @@ impl<S: serde::Serializer> Serializer for InplaceSerializer<S> {
         name: &'static str,
         len: usize,
     ) -> SerializerResult<&mut dyn SerializeTupleStruct> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(
-                InplaceSerializer::SerializeTupleStruct,
-                ser.serialize_tuple_struct(name, len),
-            )?;
-            Ok(self)
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(
+                    InplaceSerializer::SerializeTupleStruct,
+                    ser.serialize_tuple_struct(name, len),
+                )?;
+                Ok(self)
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_tuple_struct")),
         }
     }
 
@@ -1473,23 +1978,25 @@ impl<S: serde::Serializer> Serializer for InplaceSerializer<S> {
         variant: &'static str,
         len: usize,
     ) -> SerializerResult<&mut dyn SerializeTupleVariant> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(
-                InplaceSerializer::SerializeTupleVariant,
-                ser.serialize_tuple_variant(name, variant_index, variant, len),
-            )?;
-            Ok(self)
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(
+                    InplaceSerializer::SerializeTupleVariant,
+                    ser.serialize_tuple_variant(name, variant_index, variant, len),
+                )?;
+                Ok(self)
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_tuple_variant")),
         }
     }
 
     fn dyn_serialize_map(&mut self, len: Option<usize>) -> SerializerResult<&mut dyn SerializeMap> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::SerializeMap, ser.serialize_map(len))?;
-            Ok(self)
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::SerializeMap, ser.serialize_map(len))?;
+                Ok(self)
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_map")),
         }
     }
 
@@ -1498,14 +2005,15 @@ impl<S: serde::Serializer> Serializer for InplaceSerializer<S> {
         name: &'static str,
         len: usize,
     ) -> SerializerResult<&mut dyn SerializeStruct> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(
-                InplaceSerializer::SerializeStruct,
-                ser.serialize_struct(name, len),
-            )?;
-            Ok(self)
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(
+                    InplaceSerializer::SerializeStruct,
+                    ser.serialize_struct(name, len),
+                )?;
+                Ok(self)
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_struct")),
         }
     }
 
@@ -1516,22 +2024,48 @@ impl<S: serde::Serializer> Serializer for InplaceSerializer<S> {
         variant: &'static str,
         len: usize,
     ) -> SerializerResult<&mut dyn SerializeStructVariant> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(
-                InplaceSerializer::SerializeStructVariant,
-                ser.serialize_struct_variant(name, variant_index, variant, len),
-            )?;
-            Ok(self)
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(
+                    InplaceSerializer::SerializeStructVariant,
+                    ser.serialize_struct_variant(name, variant_index, variant, len),
+                )?;
+                Ok(self)
+            }
+            state => Err(self.restore(state, "Serializer::dyn_serialize_struct_variant")),
+        }
+    }
+
+    fn dyn_collect_seq(
+        &mut self,
+        iter: &mut dyn Iterator<Item = &dyn Serialize>,
+    ) -> SerializerResult<()> {
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.collect_seq(iter))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_collect_seq")),
+        }
+    }
+
+    fn dyn_collect_map(
+        &mut self,
+        iter: &mut dyn Iterator<Item = (&dyn Serialize, &dyn Serialize)>,
+    ) -> SerializerResult<()> {
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.collect_map(iter))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_collect_map")),
         }
     }
 
     fn dyn_collect_str(&mut self, value: &dyn fmt::Display) -> SerializerResult<()> {
-        if let InplaceSerializer::Serializer(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.collect_str(value))
-        } else {
-            Err(SerializerError::Serializer)
+        match mem::take(self) {
+            InplaceSerializer::Serializer(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.collect_str(value))
+            }
+            state => Err(self.restore(state, "Serializer::dyn_collect_str")),
         }
     }
 
@@ -1552,17 +2086,18 @@ impl<S: serde::Serializer> SerializeSeq for InplaceSerializer<S> {
             ser.serialize_element(value)
                 .map_err(|error| self.write_error(error))
         } else {
-            Err(SerializerError::SerializeSeq)
+            Err(self.wrong_state("SerializeSeq::dyn_serialize_element"))
         }
     }
 
     fn dyn_end(&mut self) -> SerializerResult<()> {
         use serde::ser::SerializeSeq;
 
-        if let InplaceSerializer::SerializeSeq(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.end())
-        } else {
-            Err(SerializerError::SerializeSeq)
+        match mem::take(self) {
+            InplaceSerializer::SerializeSeq(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.end())
+            }
+            state => Err(self.restore(state, "SerializeSeq::dyn_end")),
         }
     }
 }
@@ -1575,17 +2110,18 @@ impl<S: serde::Serializer> SerializeTuple for InplaceSerializer<S> {
             ser.serialize_element(value)
                 .map_err(|error| self.write_error(error))
         } else {
-            Err(SerializerError::SerializeTuple)
+            Err(self.wrong_state("SerializeTuple::dyn_serialize_element"))
         }
     }
 
     fn dyn_end(&mut self) -> SerializerResult<()> {
         use serde::ser::SerializeTuple;
 
-        if let InplaceSerializer::SerializeTuple(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.end())
-        } else {
-            Err(SerializerError::SerializeTuple)
+        match mem::take(self) {
+            InplaceSerializer::SerializeTuple(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.end())
+            }
+            state => Err(self.restore(state, "SerializeTuple::dyn_end")),
         }
     }
 }
@@ -1598,17 +2134,18 @@ impl<S: serde::Serializer> SerializeTupleStruct for InplaceSerializer<S> {
             ser.serialize_field(value)
                 .map_err(|error| self.write_error(error))
         } else {
-            Err(SerializerError::SerializeTupleStruct)
+            Err(self.wrong_state("SerializeTupleStruct::dyn_serialize_field"))
         }
     }
 
     fn dyn_end(&mut self) -> SerializerResult<()> {
         use serde::ser::SerializeTupleStruct;
 
-        if let InplaceSerializer::SerializeTupleStruct(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.end())
-        } else {
-            Err(SerializerError::SerializeTupleStruct)
+        match mem::take(self) {
+            InplaceSerializer::SerializeTupleStruct(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.end())
+            }
+            state => Err(self.restore(state, "SerializeTupleStruct::dyn_end")),
         }
     }
 }
@@ -1621,17 +2158,18 @@ impl<S: serde::Serializer> SerializeTupleVariant for InplaceSerializer<S> {
             ser.serialize_field(value)
                 .map_err(|error| self.write_error(error))
         } else {
-            Err(SerializerError::SerializeTupleVariant)
+            Err(self.wrong_state("SerializeTupleVariant::dyn_serialize_field"))
         }
     }
 
     fn dyn_end(&mut self) -> SerializerResult<()> {
         use serde::ser::SerializeTupleVariant;
 
-        if let InplaceSerializer::SerializeTupleVariant(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.end())
-        } else {
-            Err(SerializerError::SerializeTupleVariant)
+        match mem::take(self) {
+            InplaceSerializer::SerializeTupleVariant(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.end())
+            }
+            state => Err(self.restore(state, "SerializeTupleVariant::dyn_end")),
         }
     }
 }
@@ -1640,22 +2178,24 @@ impl<S: serde::Serializer> SerializeMap for InplaceSerializer<S> {
     fn dyn_serialize_key(&mut self, key: &dyn Serialize) -> SerializerResult<()> {
         use serde::ser::SerializeMap;
 
-        if let InplaceSerializer::SerializeMap(ser) = self {
-            ser.serialize_key(key)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(SerializerError::SerializeMap)
+        match mem::take(self) {
+            InplaceSerializer::SerializeMap(mut ser) => {
+                let result = ser.serialize_key(key);
+                self.write_with(|()| InplaceSerializer::SerializeMapValue(ser), result)
+            }
+            state => Err(self.restore(state, "SerializeMap::dyn_serialize_key")),
         }
     }
 
     fn dyn_serialize_value(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
         use serde::ser::SerializeMap;
 
-        if let InplaceSerializer::SerializeMap(ser) = self {
-            ser.serialize_value(value)
-                .map_err(|error| self.write_error(error))
-        } else {
-            Err(SerializerError::SerializeMap)
+        match mem::take(self) {
+            InplaceSerializer::SerializeMapValue(mut ser) => {
+                let result = ser.serialize_value(value);
+                self.write_with(|()| InplaceSerializer::SerializeMap(ser), result)
+            }
+            state => Err(self.restore(state, "SerializeMap::dyn_serialize_value")),
         }
     }
 
@@ -1670,17 +2210,18 @@ impl<S: serde::Serializer> SerializeMap for InplaceSerializer<S> {
             ser.serialize_entry(key, value)
                 .map_err(|error| self.write_error(error))
         } else {
-            Err(SerializerError::SerializeMap)
+            Err(self.wrong_state("SerializeMap::dyn_serialize_entry"))
         }
     }
 
     fn dyn_end(&mut self) -> SerializerResult<()> {
         use serde::ser::SerializeMap;
 
-        if let InplaceSerializer::SerializeMap(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.end())
-        } else {
-            Err(SerializerError::SerializeMap)
+        match mem::take(self) {
+            InplaceSerializer::SerializeMap(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.end())
+            }
+            state => Err(self.restore(state, "SerializeMap::dyn_end")),
         }
     }
 }
@@ -1697,7 +2238,7 @@ impl<S: serde::Serializer> SerializeStruct for InplaceSerializer<S> {
             ser.serialize_field(key, value)
                 .map_err(|error| self.write_error(error))
         } else {
-            Err(SerializerError::SerializeStruct)
+            Err(self.wrong_state("SerializeStruct::dyn_serialize_field"))
         }
     }
 
@@ -1707,17 +2248,18 @@ impl<S: serde::Serializer> SerializeStruct for InplaceSerializer<S> {
         if let InplaceSerializer::SerializeStruct(ser) = self {
             ser.skip_field(key).map_err(|error| self.write_error(error))
         } else {
-            Err(SerializerError::SerializeStruct)
+            Err(self.wrong_state("SerializeStruct::dyn_skip_field"))
         }
     }
 
     fn dyn_end(&mut self) -> SerializerResult<()> {
         use serde::ser::SerializeStruct;
 
-        if let InplaceSerializer::SerializeStruct(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.end())
-        } else {
-            Err(SerializerError::SerializeStruct)
+        match mem::take(self) {
+            InplaceSerializer::SerializeStruct(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.end())
+            }
+            state => Err(self.restore(state, "SerializeStruct::dyn_end")),
         }
     }
 }
@@ -1734,7 +2276,7 @@ impl<S: serde::Serializer> SerializeStructVariant for InplaceSerializer<S> {
             ser.serialize_field(key, value)
                 .map_err(|error| self.write_error(error))
         } else {
-            Err(SerializerError::SerializeStructVariant)
+            Err(self.wrong_state("SerializeStructVariant::dyn_serialize_field"))
         }
     }
 
@@ -1744,17 +2286,304 @@ impl<S: serde::Serializer> SerializeStructVariant for InplaceSerializer<S> {
         if let InplaceSerializer::SerializeStructVariant(ser) = self {
             ser.skip_field(key).map_err(|error| self.write_error(error))
         } else {
-            Err(SerializerError::SerializeStructVariant)
+            Err(self.wrong_state("SerializeStructVariant::dyn_skip_field"))
         }
     }
 
     fn dyn_end(&mut self) -> SerializerResult<()> {
         use serde::ser::SerializeStructVariant;
 
-        if let InplaceSerializer::SerializeStructVariant(ser) = mem::take(self) {
-            self.write_with(InplaceSerializer::Ok, ser.end())
-        } else {
-            Err(SerializerError::SerializeStructVariant)
+        match mem::take(self) {
+            InplaceSerializer::SerializeStructVariant(ser) => {
+                self.write_with(InplaceSerializer::Ok, ser.end())
+            }
+            state => Err(self.restore(state, "SerializeStructVariant::dyn_end")),
+        }
+    }
+}
+
+/// Wraps `serializer` into an owned [`BoxSerializer`].
+///
+/// Unlike `<dyn Serializer>::new` whose output has to be taken back by matching
+/// [`InplaceSerializer::Ok`], the output of the returned serializer is erased into
+/// `Box<dyn Any>`, so that a serializer chosen at runtime can still hand its output back.
+///
+/// The error of `serializer` has to implement [`Error`](error::Error), which is always the case
+/// with `std`.
+///
+/// # Examples
+///
+/// ```
+/// # use serde_dyn::Serializer;
+/// # use serde_dyn::ser::{self, BoxSerializer};
+/// fn open(format: &str) -> BoxSerializer<'static> {
+///     match format {
+///         "json" => ser::boxed(serde_json::value::Serializer),
+///         _ => unimplemented!(),
+///     }
+/// }
+///
+/// let mut serializer = open("json");
+/// let data = ["Hello", "World"];
+/// serde::Serialize::serialize(&data, &mut serializer as &mut dyn Serializer).unwrap();
+///
+/// let value: serde_json::Value = serializer.downcast().unwrap();
+/// assert_eq!(value, serde_json::json!(["Hello", "World"]));
+/// ```
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn boxed<'a, S>(serializer: S) -> BoxSerializer<'a>
+where
+    S: serde::Serializer + 'a,
+    S::Ok: 'static,
+    S::Error: error::Error + 'static,
+{
+    BoxSerializer(Box::new(InplaceSerializer::Serializer(serializer)))
+}
+
+/// An owned [`Serializer`] wrapping any [`serde::Serializer`], created by [`boxed`].
+///
+/// Once a value has been serialized, the output of the concrete serializer is taken back by
+/// [`BoxSerializer::finish`] or [`BoxSerializer::downcast`].
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub struct BoxSerializer<'a>(Box<dyn ErasedSerializer + 'a>);
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl BoxSerializer<'_> {
+    /// Consumes the serializer, and returns the output of the concrete serializer.
+    ///
+    /// # Errors
+    ///
+    /// Returns the original error if the concrete serializer failed, or a [`SerializerError`] if
+    /// the serialization has not been finished.
+    pub fn finish(self) -> Result<Box<dyn Any>, Box<dyn error::Error>> {
+        self.0.dyn_finish()
+    }
+
+    /// Consumes the serializer, and returns the output of the concrete serializer as `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`BoxSerializer::finish`], or a [`SerializeError`] if the output
+    /// is not of type `T`.
+    pub fn downcast<T: 'static>(self) -> Result<T, Box<dyn error::Error>> {
+        match self.finish()?.downcast() {
+            Ok(ok) => Ok(*ok),
+            Err(_) => Err(Box::new(<SerializeError as serde::ser::Error>::custom(
+                format_args!("the output is not of type `{}`", any::type_name::<T>()),
+            ))),
+        }
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Serializer for BoxSerializer<'_> {
+    fn dyn_serialize_bool(&mut self, v: bool) -> SerializerResult<()> {
+        self.0.dyn_serialize_bool(v)
+    }
+
+    fn dyn_serialize_i8(&mut self, v: i8) -> SerializerResult<()> {
+        self.0.dyn_serialize_i8(v)
+    }
+
+    fn dyn_serialize_i16(&mut self, v: i16) -> SerializerResult<()> {
+        self.0.dyn_serialize_i16(v)
+    }
+
+    fn dyn_serialize_i32(&mut self, v: i32) -> SerializerResult<()> {
+        self.0.dyn_serialize_i32(v)
+    }
+
+    fn dyn_serialize_i64(&mut self, v: i64) -> SerializerResult<()> {
+        self.0.dyn_serialize_i64(v)
+    }
+
+    fn dyn_serialize_i128(&mut self, v: i128) -> SerializerResult<()> {
+        self.0.dyn_serialize_i128(v)
+    }
+
+    fn dyn_serialize_u8(&mut self, v: u8) -> SerializerResult<()> {
+        self.0.dyn_serialize_u8(v)
+    }
+
+    fn dyn_serialize_u16(&mut self, v: u16) -> SerializerResult<()> {
+        self.0.dyn_serialize_u16(v)
+    }
+
+    fn dyn_serialize_u32(&mut self, v: u32) -> SerializerResult<()> {
+        self.0.dyn_serialize_u32(v)
+    }
+
+    fn dyn_serialize_u64(&mut self, v: u64) -> SerializerResult<()> {
+        self.0.dyn_serialize_u64(v)
+    }
+
+    fn dyn_serialize_u128(&mut self, v: u128) -> SerializerResult<()> {
+        self.0.dyn_serialize_u128(v)
+    }
+
+    fn dyn_serialize_f32(&mut self, v: f32) -> SerializerResult<()> {
+        self.0.dyn_serialize_f32(v)
+    }
+
+    fn dyn_serialize_f64(&mut self, v: f64) -> SerializerResult<()> {
+        self.0.dyn_serialize_f64(v)
+    }
+
+    fn dyn_serialize_char(&mut self, v: char) -> SerializerResult<()> {
+        self.0.dyn_serialize_char(v)
+    }
+
+    fn dyn_serialize_str(&mut self, v: &str) -> SerializerResult<()> {
+        self.0.dyn_serialize_str(v)
+    }
+
+    fn dyn_serialize_bytes(&mut self, v: &[u8]) -> SerializerResult<()> {
+        self.0.dyn_serialize_bytes(v)
+    }
+
+    fn dyn_serialize_none(&mut self) -> SerializerResult<()> {
+        self.0.dyn_serialize_none()
+    }
+
+    fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.0.dyn_serialize_some(value)
+    }
+
+    fn dyn_serialize_unit(&mut self) -> SerializerResult<()> {
+        self.0.dyn_serialize_unit()
+    }
+
+    fn dyn_serialize_unit_struct(&mut self, name: &'static str) -> SerializerResult<()> {
+        self.0.dyn_serialize_unit_struct(name)
+    }
+
+    fn dyn_serialize_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> SerializerResult<()> {
+        self.0
+            .dyn_serialize_unit_variant(name, variant_index, variant)
+    }
+
+    fn dyn_serialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        self.0.dyn_serialize_newtype_struct(name, value)
+    }
+
+    fn dyn_serialize_newtype_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        self.0
+            .dyn_serialize_newtype_variant(name, variant_index, variant, value)
+    }
+
+    fn dyn_serialize_seq(&mut self, len: Option<usize>) -> SerializerResult<&mut dyn SerializeSeq> {
+        self.0.dyn_serialize_seq(len)
+    }
+
+    fn dyn_serialize_tuple(&mut self, len: usize) -> SerializerResult<&mut dyn SerializeTuple> {
+        self.0.dyn_serialize_tuple(len)
+    }
+
+    fn dyn_serialize_tuple_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeTupleStruct> {
+        self.0.dyn_serialize_tuple_struct(name, len)
+    }
+
+    fn dyn_serialize_tuple_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeTupleVariant> {
+        self.0
+            .dyn_serialize_tuple_variant(name, variant_index, variant, len)
+    }
+
+    fn dyn_serialize_map(&mut self, len: Option<usize>) -> SerializerResult<&mut dyn SerializeMap> {
+        self.0.dyn_serialize_map(len)
+    }
+
+    fn dyn_serialize_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeStruct> {
+        self.0.dyn_serialize_struct(name, len)
+    }
+
+    fn dyn_serialize_struct_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeStructVariant> {
+        self.0
+            .dyn_serialize_struct_variant(name, variant_index, variant, len)
+    }
+
+    fn dyn_collect_seq(
+        &mut self,
+        iter: &mut dyn Iterator<Item = &dyn Serialize>,
+    ) -> SerializerResult<()> {
+        self.0.dyn_collect_seq(iter)
+    }
+
+    fn dyn_collect_map(
+        &mut self,
+        iter: &mut dyn Iterator<Item = (&dyn Serialize, &dyn Serialize)>,
+    ) -> SerializerResult<()> {
+        self.0.dyn_collect_map(iter)
+    }
+
+    fn dyn_collect_str(&mut self, value: &dyn fmt::Display) -> SerializerResult<()> {
+        self.0.dyn_collect_str(value)
+    }
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.0.dyn_is_human_readable()
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl fmt::Debug for BoxSerializer<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BoxSerializer").finish_non_exhaustive()
+    }
+}
+
+/// The object-safe part of the serializers kept by [`BoxSerializer`].
+#[cfg(any(feature = "std", feature = "alloc"))]
+trait ErasedSerializer: Serializer {
+    fn dyn_finish(self: Box<Self>) -> Result<Box<dyn Any>, Box<dyn error::Error>>;
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<S> ErasedSerializer for InplaceSerializer<S>
+where
+    S: serde::Serializer,
+    S::Ok: 'static,
+    S::Error: error::Error + 'static,
+{
+    fn dyn_finish(self: Box<Self>) -> Result<Box<dyn Any>, Box<dyn error::Error>> {
+        match *self {
+            InplaceSerializer::Ok(ok) => Ok(Box::new(ok)),
+            InplaceSerializer::Error(error) => Err(Box::new(error)),
+            ref serializer => Err(Box::new(serializer.wrong_state("BoxSerializer::finish"))),
         }
     }
 }
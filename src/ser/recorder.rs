@@ -0,0 +1,586 @@
+use core::fmt;
+
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::string::ToString;
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::vec::Vec;
+
+use super::{
+    Serialize, SerializeError, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+    SerializeTuple, SerializeTupleStruct, SerializeTupleVariant, Serializer, SerializerError,
+    SerializerResult, SerializerState,
+};
+use crate::token::Token;
+
+/// A [`Serializer`] that records every call as a [`Token`].
+///
+/// The recorder checks the order of the calls like
+/// [`InplaceSerializer`](super::InplaceSerializer), and fails with
+/// [`SerializerError::WrongState`] on a misplaced call, so the tokens show what a [`Serialize`]
+/// implementation does up to its first mistake. The tokens can be replayed by
+/// [`Tokens`](crate::de::Tokens).
+///
+/// # Examples
+///
+/// ```
+/// # use serde_dyn::Serialize;
+/// # use serde_dyn::ser::Recorder;
+/// # use serde_dyn::token::Token;
+/// let mut recorder = Recorder::new();
+/// Some("hello").dyn_serialize(&mut recorder).unwrap();
+/// assert_eq!(recorder.tokens(), [Token::Some, Token::Str("hello".into())]);
+/// ```
+#[derive(Debug)]
+pub struct Recorder {
+    tokens: Vec<Token>,
+    /// The open compounds, innermost last.
+    compounds: Vec<SerializerState>,
+    /// Whether a value is expected.
+    ready: bool,
+    is_human_readable: bool,
+    error: Option<SerializeError>,
+}
+
+impl Recorder {
+    /// Creates a human-readable recorder.
+    #[must_use]
+    pub fn new() -> Self {
+        Recorder {
+            tokens: Vec::new(),
+            compounds: Vec::new(),
+            ready: true,
+            is_human_readable: true,
+            error: None,
+        }
+    }
+
+    /// Sets whether the recorder asks for the human-readable form, see
+    /// [`Serializer::dyn_is_human_readable`].
+    #[must_use]
+    pub fn with_human_readable(mut self, is_human_readable: bool) -> Self {
+        self.is_human_readable = is_human_readable;
+        self
+    }
+
+    /// Returns the recorded tokens.
+    #[must_use]
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// Consumes the recorder, and returns the recorded tokens.
+    #[must_use]
+    pub fn into_tokens(self) -> Vec<Token> {
+        self.tokens
+    }
+
+    /// Returns the first error of the nested values.
+    ///
+    /// A nested value that fails to serialize only reports [`SerializerError::Error`] to its
+    /// parent, so its original error is kept here.
+    #[must_use]
+    pub fn error(&self) -> Option<&SerializeError> {
+        self.error.as_ref()
+    }
+
+    fn push(&mut self, token: Token) -> SerializerResult<()> {
+        self.tokens.push(token);
+        Ok(())
+    }
+
+    /// Returns the state of the recorder, as if it were an
+    /// [`InplaceSerializer`](super::InplaceSerializer).
+    fn state(&self) -> SerializerState {
+        match self.compounds.last() {
+            _ if self.ready => SerializerState::Serializer,
+            Some(&compound) => compound,
+            None => SerializerState::Ok,
+        }
+    }
+
+    #[cold]
+    fn wrong_state(&self, operation: &'static str) -> SerializerError {
+        SerializerError::WrongState {
+            operation,
+            found: self.state(),
+        }
+    }
+
+    /// Records `token` of a value, which must be expected.
+    fn value(&mut self, operation: &'static str, token: Token) -> SerializerResult<()> {
+        if !self.ready {
+            return Err(self.wrong_state(operation));
+        }
+        self.ready = false;
+        self.push(token)
+    }
+
+    /// Records `token` of a value, and opens `compound`.
+    fn begin(
+        &mut self,
+        operation: &'static str,
+        token: Token,
+        compound: SerializerState,
+    ) -> SerializerResult<()> {
+        self.value(operation, token)?;
+        self.compounds.push(compound);
+        Ok(())
+    }
+
+    /// Checks that the innermost open compound is `compound`.
+    fn enter(&self, operation: &'static str, compound: SerializerState) -> SerializerResult<()> {
+        if self.state() == compound {
+            Ok(())
+        } else {
+            Err(self.wrong_state(operation))
+        }
+    }
+
+    /// Moves the innermost compound to `compound`.
+    fn set_top(&mut self, compound: SerializerState) {
+        if let Some(top) = self.compounds.last_mut() {
+            *top = compound;
+        }
+    }
+
+    /// Closes `compound`, and records `token`.
+    fn end(
+        &mut self,
+        operation: &'static str,
+        compound: SerializerState,
+        token: Token,
+    ) -> SerializerResult<()> {
+        self.enter(operation, compound)?;
+        self.compounds.pop();
+        self.push(token)
+    }
+
+    /// Records a nested value.
+    fn nested(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.ready = true;
+        let result = value
+            .dyn_serialize(self)
+            .map_err(|error| self.write_error(error));
+        self.ready = false;
+        result
+    }
+
+    fn write_error(&mut self, error: SerializeError) -> SerializerError {
+        self.error.get_or_insert(error);
+        SerializerError::Error
+    }
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Recorder::new()
+    }
+}
+
+impl Serializer for Recorder {
+    fn dyn_serialize_bool(&mut self, v: bool) -> SerializerResult<()> {
+        self.value("Serializer::dyn_serialize_bool", Token::Bool(v))
+    }
+
+    fn dyn_serialize_i8(&mut self, v: i8) -> SerializerResult<()> {
+        self.value("Serializer::dyn_serialize_i8", Token::I8(v))
+    }
+
+    fn dyn_serialize_i16(&mut self, v: i16) -> SerializerResult<()> {
+        self.value("Serializer::dyn_serialize_i16", Token::I16(v))
+    }
+
+    fn dyn_serialize_i32(&mut self, v: i32) -> SerializerResult<()> {
+        self.value("Serializer::dyn_serialize_i32", Token::I32(v))
+    }
+
+    fn dyn_serialize_i64(&mut self, v: i64) -> SerializerResult<()> {
+        self.value("Serializer::dyn_serialize_i64", Token::I64(v))
+    }
+
+    fn dyn_serialize_i128(&mut self, v: i128) -> SerializerResult<()> {
+        self.value("Serializer::dyn_serialize_i128", Token::I128(v))
+    }
+
+    fn dyn_serialize_u8(&mut self, v: u8) -> SerializerResult<()> {
+        self.value("Serializer::dyn_serialize_u8", Token::U8(v))
+    }
+
+    fn dyn_serialize_u16(&mut self, v: u16) -> SerializerResult<()> {
+        self.value("Serializer::dyn_serialize_u16", Token::U16(v))
+    }
+
+    fn dyn_serialize_u32(&mut self, v: u32) -> SerializerResult<()> {
+        self.value("Serializer::dyn_serialize_u32", Token::U32(v))
+    }
+
+    fn dyn_serialize_u64(&mut self, v: u64) -> SerializerResult<()> {
+        self.value("Serializer::dyn_serialize_u64", Token::U64(v))
+    }
+
+    fn dyn_serialize_u128(&mut self, v: u128) -> SerializerResult<()> {
+        self.value("Serializer::dyn_serialize_u128", Token::U128(v))
+    }
+
+    fn dyn_serialize_f32(&mut self, v: f32) -> SerializerResult<()> {
+        self.value("Serializer::dyn_serialize_f32", Token::F32(v))
+    }
+
+    fn dyn_serialize_f64(&mut self, v: f64) -> SerializerResult<()> {
+        self.value("Serializer::dyn_serialize_f64", Token::F64(v))
+    }
+
+    fn dyn_serialize_char(&mut self, v: char) -> SerializerResult<()> {
+        self.value("Serializer::dyn_serialize_char", Token::Char(v))
+    }
+
+    fn dyn_serialize_str(&mut self, v: &str) -> SerializerResult<()> {
+        self.value("Serializer::dyn_serialize_str", Token::Str(v.to_string()))
+    }
+
+    fn dyn_serialize_bytes(&mut self, v: &[u8]) -> SerializerResult<()> {
+        self.value("Serializer::dyn_serialize_bytes", Token::Bytes(v.to_vec()))
+    }
+
+    fn dyn_serialize_none(&mut self) -> SerializerResult<()> {
+        self.value("Serializer::dyn_serialize_none", Token::None)
+    }
+
+    fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.value("Serializer::dyn_serialize_some", Token::Some)?;
+        self.nested(value)
+    }
+
+    fn dyn_serialize_unit(&mut self) -> SerializerResult<()> {
+        self.value("Serializer::dyn_serialize_unit", Token::Unit)
+    }
+
+    fn dyn_serialize_unit_struct(&mut self, name: &'static str) -> SerializerResult<()> {
+        self.value(
+            "Serializer::dyn_serialize_unit_struct",
+            Token::UnitStruct(name),
+        )
+    }
+
+    fn dyn_serialize_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> SerializerResult<()> {
+        self.value(
+            "Serializer::dyn_serialize_unit_variant",
+            Token::UnitVariant {
+                name,
+                variant_index,
+                variant,
+            },
+        )
+    }
+
+    fn dyn_serialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        self.value(
+            "Serializer::dyn_serialize_newtype_struct",
+            Token::NewtypeStruct { name },
+        )?;
+        self.nested(value)
+    }
+
+    fn dyn_serialize_newtype_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        self.value(
+            "Serializer::dyn_serialize_newtype_variant",
+            Token::NewtypeVariant {
+                name,
+                variant_index,
+                variant,
+            },
+        )?;
+        self.nested(value)
+    }
+
+    fn dyn_serialize_seq(&mut self, len: Option<usize>) -> SerializerResult<&mut dyn SerializeSeq> {
+        self.begin(
+            "Serializer::dyn_serialize_seq",
+            Token::Seq { len },
+            SerializerState::SerializeSeq,
+        )?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple(&mut self, len: usize) -> SerializerResult<&mut dyn SerializeTuple> {
+        self.begin(
+            "Serializer::dyn_serialize_tuple",
+            Token::Tuple { len },
+            SerializerState::SerializeTuple,
+        )?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeTupleStruct> {
+        self.begin(
+            "Serializer::dyn_serialize_tuple_struct",
+            Token::TupleStruct { name, len },
+            SerializerState::SerializeTupleStruct,
+        )?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeTupleVariant> {
+        self.begin(
+            "Serializer::dyn_serialize_tuple_variant",
+            Token::TupleVariant {
+                name,
+                variant_index,
+                variant,
+                len,
+            },
+            SerializerState::SerializeTupleVariant,
+        )?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_map(&mut self, len: Option<usize>) -> SerializerResult<&mut dyn SerializeMap> {
+        self.begin(
+            "Serializer::dyn_serialize_map",
+            Token::Map { len },
+            SerializerState::SerializeMap,
+        )?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeStruct> {
+        self.begin(
+            "Serializer::dyn_serialize_struct",
+            Token::Struct { name, len },
+            SerializerState::SerializeStruct,
+        )?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_struct_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeStructVariant> {
+        self.begin(
+            "Serializer::dyn_serialize_struct_variant",
+            Token::StructVariant {
+                name,
+                variant_index,
+                variant,
+                len,
+            },
+            SerializerState::SerializeStructVariant,
+        )?;
+        Ok(self)
+    }
+
+    fn dyn_collect_str(&mut self, value: &dyn fmt::Display) -> SerializerResult<()> {
+        self.value("Serializer::dyn_collect_str", Token::Str(value.to_string()))
+    }
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.is_human_readable
+    }
+}
+
+impl SerializeSeq for Recorder {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.enter(
+            "SerializeSeq::dyn_serialize_element",
+            SerializerState::SerializeSeq,
+        )?;
+        self.nested(value)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.end(
+            "SerializeSeq::dyn_end",
+            SerializerState::SerializeSeq,
+            Token::SeqEnd,
+        )
+    }
+}
+
+impl SerializeTuple for Recorder {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.enter(
+            "SerializeTuple::dyn_serialize_element",
+            SerializerState::SerializeTuple,
+        )?;
+        self.nested(value)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.end(
+            "SerializeTuple::dyn_end",
+            SerializerState::SerializeTuple,
+            Token::TupleEnd,
+        )
+    }
+}
+
+impl SerializeTupleStruct for Recorder {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.enter(
+            "SerializeTupleStruct::dyn_serialize_field",
+            SerializerState::SerializeTupleStruct,
+        )?;
+        self.nested(value)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.end(
+            "SerializeTupleStruct::dyn_end",
+            SerializerState::SerializeTupleStruct,
+            Token::TupleStructEnd,
+        )
+    }
+}
+
+impl SerializeTupleVariant for Recorder {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.enter(
+            "SerializeTupleVariant::dyn_serialize_field",
+            SerializerState::SerializeTupleVariant,
+        )?;
+        self.nested(value)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.end(
+            "SerializeTupleVariant::dyn_end",
+            SerializerState::SerializeTupleVariant,
+            Token::TupleVariantEnd,
+        )
+    }
+}
+
+impl SerializeMap for Recorder {
+    fn dyn_serialize_key(&mut self, key: &dyn Serialize) -> SerializerResult<()> {
+        self.enter(
+            "SerializeMap::dyn_serialize_key",
+            SerializerState::SerializeMap,
+        )?;
+        self.set_top(SerializerState::SerializeMapValue);
+        self.nested(key)
+    }
+
+    fn dyn_serialize_value(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.enter(
+            "SerializeMap::dyn_serialize_value",
+            SerializerState::SerializeMapValue,
+        )?;
+        self.set_top(SerializerState::SerializeMap);
+        self.nested(value)
+    }
+
+    fn dyn_serialize_entry(
+        &mut self,
+        key: &dyn Serialize,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        self.enter(
+            "SerializeMap::dyn_serialize_entry",
+            SerializerState::SerializeMap,
+        )?;
+        self.nested(key)?;
+        self.nested(value)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.end(
+            "SerializeMap::dyn_end",
+            SerializerState::SerializeMap,
+            Token::MapEnd,
+        )
+    }
+}
+
+impl SerializeStruct for Recorder {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        self.enter(
+            "SerializeStruct::dyn_serialize_field",
+            SerializerState::SerializeStruct,
+        )?;
+        self.push(Token::Field(key))?;
+        self.nested(value)
+    }
+
+    fn dyn_skip_field(&mut self, key: &'static str) -> SerializerResult<()> {
+        self.enter(
+            "SerializeStruct::dyn_skip_field",
+            SerializerState::SerializeStruct,
+        )?;
+        self.push(Token::SkippedField(key))
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.end(
+            "SerializeStruct::dyn_end",
+            SerializerState::SerializeStruct,
+            Token::StructEnd,
+        )
+    }
+}
+
+impl SerializeStructVariant for Recorder {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        self.enter(
+            "SerializeStructVariant::dyn_serialize_field",
+            SerializerState::SerializeStructVariant,
+        )?;
+        self.push(Token::Field(key))?;
+        self.nested(value)
+    }
+
+    fn dyn_skip_field(&mut self, key: &'static str) -> SerializerResult<()> {
+        self.enter(
+            "SerializeStructVariant::dyn_skip_field",
+            SerializerState::SerializeStructVariant,
+        )?;
+        self.push(Token::SkippedField(key))
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.end(
+            "SerializeStructVariant::dyn_end",
+            SerializerState::SerializeStructVariant,
+            Token::StructVariantEnd,
+        )
+    }
+}
@@ -0,0 +1,704 @@
+use core::fmt;
+
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::string::ToString;
+
+use super::{
+    Serialize, SerializeError, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+    SerializeTuple, SerializeTupleStruct, SerializeTupleVariant, Serializer, SerializerError,
+    SerializerResult,
+};
+
+/// A compound value started by [`SimpleSerializer::begin`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compound {
+    /// A variably sized sequence, see [`Serializer::dyn_serialize_seq`].
+    Seq {
+        /// The number of elements, if known.
+        len: Option<usize>,
+    },
+    /// A tuple, see [`Serializer::dyn_serialize_tuple`].
+    Tuple {
+        /// The number of elements.
+        len: usize,
+    },
+    /// A tuple struct, see [`Serializer::dyn_serialize_tuple_struct`].
+    TupleStruct {
+        /// The name of the struct.
+        name: &'static str,
+        /// The number of fields.
+        len: usize,
+    },
+    /// A tuple variant, see [`Serializer::dyn_serialize_tuple_variant`].
+    TupleVariant {
+        /// The name of the enum.
+        name: &'static str,
+        /// The index of the variant.
+        variant_index: u32,
+        /// The name of the variant.
+        variant: &'static str,
+        /// The number of fields.
+        len: usize,
+    },
+    /// A newtype variant holding exactly one element, see
+    /// [`Serializer::dyn_serialize_newtype_variant`].
+    NewtypeVariant {
+        /// The name of the enum.
+        name: &'static str,
+        /// The index of the variant.
+        variant_index: u32,
+        /// The name of the variant.
+        variant: &'static str,
+    },
+    /// A variably sized map, see [`Serializer::dyn_serialize_map`].
+    Map {
+        /// The number of entries, if known.
+        len: Option<usize>,
+    },
+    /// A struct, see [`Serializer::dyn_serialize_struct`].
+    Struct {
+        /// The name of the struct.
+        name: &'static str,
+        /// The number of serialized fields.
+        len: usize,
+    },
+    /// A struct variant, see [`Serializer::dyn_serialize_struct_variant`].
+    StructVariant {
+        /// The name of the enum.
+        name: &'static str,
+        /// The index of the variant.
+        variant_index: u32,
+        /// The name of the variant.
+        variant: &'static str,
+        /// The number of serialized fields.
+        len: usize,
+    },
+}
+
+/// A reduced version of [`Serializer`] for hand-written serializers.
+///
+/// Every type implementing this trait implements [`Serializer`] as well. Only a few methods are
+/// required, and the others forward to them by default:
+///
+/// - the integers are widened to `i128` or `u128`, and `f32` to `f64`;
+/// - `char` is serialized as a string, bytes as a sequence of `u8`;
+/// - `None`, unit structs are serialized as unit, unit variants as the name of the variant;
+/// - `Some` and newtype structs are transparent, newtype variants are compounds of one element.
+///
+/// Compound values are reported as a flat list of events: [`begin`](Self::begin), one of
+/// [`element`](Self::element), [`key`](Self::key), [`value`](Self::value) or
+/// [`field`](Self::field) before each nested value, and finally [`end`](Self::end).
+///
+/// # Examples
+///
+/// ```
+/// # use serde_dyn::Serialize;
+/// # use serde_dyn::ser::{Compound, SerializerResult, SimpleSerializer};
+/// #[derive(Default)]
+/// struct Flatten {
+///     path: Vec<&'static str>,
+///     lines: Vec<String>,
+/// }
+///
+/// impl Flatten {
+///     fn write(&mut self, v: impl std::fmt::Display) -> SerializerResult<()> {
+///         self.lines.push(format!("{}={v}", self.path.join(".")));
+///         Ok(())
+///     }
+/// }
+///
+/// impl SimpleSerializer for Flatten {
+///     fn serialize_bool(&mut self, v: bool) -> SerializerResult<()> {
+///         self.write(v)
+///     }
+///
+///     fn serialize_i128(&mut self, v: i128) -> SerializerResult<()> {
+///         self.write(v)
+///     }
+///
+///     fn serialize_u128(&mut self, v: u128) -> SerializerResult<()> {
+///         self.write(v)
+///     }
+///
+///     fn serialize_f64(&mut self, v: f64) -> SerializerResult<()> {
+///         self.write(v)
+///     }
+///
+///     fn serialize_str(&mut self, v: &str) -> SerializerResult<()> {
+///         self.write(v)
+///     }
+///
+///     fn serialize_unit(&mut self) -> SerializerResult<()> {
+///         self.write("null")
+///     }
+///
+///     fn collect_str(&mut self, value: &dyn std::fmt::Display) -> SerializerResult<()> {
+///         self.write(value)
+///     }
+///
+///     fn begin(&mut self, _: Compound) -> SerializerResult<()> {
+///         self.path.push("");
+///         Ok(())
+///     }
+///
+///     fn field(&mut self, key: &'static str) -> SerializerResult<()> {
+///         *self.path.last_mut().unwrap() = key;
+///         Ok(())
+///     }
+///
+///     fn end(&mut self) -> SerializerResult<()> {
+///         self.path.pop();
+///         Ok(())
+///     }
+/// }
+///
+/// #[derive(serde::Serialize)]
+/// struct Tls {
+///     enabled: bool,
+/// }
+///
+/// #[derive(serde::Serialize)]
+/// struct Server {
+///     port: u16,
+///     tls: Tls,
+/// }
+///
+/// let mut flatten = Flatten::default();
+/// let server = Server { port: 443, tls: Tls { enabled: true } };
+/// server.dyn_serialize(&mut flatten).unwrap();
+/// assert_eq!(flatten.lines, ["port=443", "tls.enabled=true"]);
+/// ```
+pub trait SimpleSerializer {
+    /// Serialize a `bool` value.
+    fn serialize_bool(&mut self, v: bool) -> SerializerResult<()>;
+
+    /// Serialize an `i8` value.
+    ///
+    /// Forwards to [`serialize_i128`](Self::serialize_i128) by default.
+    fn serialize_i8(&mut self, v: i8) -> SerializerResult<()> {
+        self.serialize_i128(v.into())
+    }
+
+    /// Serialize an `i16` value.
+    ///
+    /// Forwards to [`serialize_i128`](Self::serialize_i128) by default.
+    fn serialize_i16(&mut self, v: i16) -> SerializerResult<()> {
+        self.serialize_i128(v.into())
+    }
+
+    /// Serialize an `i32` value.
+    ///
+    /// Forwards to [`serialize_i128`](Self::serialize_i128) by default.
+    fn serialize_i32(&mut self, v: i32) -> SerializerResult<()> {
+        self.serialize_i128(v.into())
+    }
+
+    /// Serialize an `i64` value.
+    ///
+    /// Forwards to [`serialize_i128`](Self::serialize_i128) by default.
+    fn serialize_i64(&mut self, v: i64) -> SerializerResult<()> {
+        self.serialize_i128(v.into())
+    }
+
+    /// Serialize an `i128` value, as well as the smaller signed integers by default.
+    fn serialize_i128(&mut self, v: i128) -> SerializerResult<()>;
+
+    /// Serialize a `u8` value.
+    ///
+    /// Forwards to [`serialize_u128`](Self::serialize_u128) by default.
+    fn serialize_u8(&mut self, v: u8) -> SerializerResult<()> {
+        self.serialize_u128(v.into())
+    }
+
+    /// Serialize a `u16` value.
+    ///
+    /// Forwards to [`serialize_u128`](Self::serialize_u128) by default.
+    fn serialize_u16(&mut self, v: u16) -> SerializerResult<()> {
+        self.serialize_u128(v.into())
+    }
+
+    /// Serialize a `u32` value.
+    ///
+    /// Forwards to [`serialize_u128`](Self::serialize_u128) by default.
+    fn serialize_u32(&mut self, v: u32) -> SerializerResult<()> {
+        self.serialize_u128(v.into())
+    }
+
+    /// Serialize a `u64` value.
+    ///
+    /// Forwards to [`serialize_u128`](Self::serialize_u128) by default.
+    fn serialize_u64(&mut self, v: u64) -> SerializerResult<()> {
+        self.serialize_u128(v.into())
+    }
+
+    /// Serialize a `u128` value, as well as the smaller unsigned integers by default.
+    fn serialize_u128(&mut self, v: u128) -> SerializerResult<()>;
+
+    /// Serialize an `f32` value.
+    ///
+    /// Forwards to [`serialize_f64`](Self::serialize_f64) by default.
+    fn serialize_f32(&mut self, v: f32) -> SerializerResult<()> {
+        self.serialize_f64(v.into())
+    }
+
+    /// Serialize an `f64` value, as well as `f32` by default.
+    fn serialize_f64(&mut self, v: f64) -> SerializerResult<()>;
+
+    /// Serialize a character.
+    ///
+    /// Forwards to [`serialize_str`](Self::serialize_str) by default.
+    fn serialize_char(&mut self, v: char) -> SerializerResult<()> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Serialize a string, as well as characters and unit variants by default.
+    fn serialize_str(&mut self, v: &str) -> SerializerResult<()>;
+
+    /// Serialize a chunk of raw byte data.
+    ///
+    /// Serializes a [`Compound::Seq`] of `u8` by default.
+    fn serialize_bytes(&mut self, v: &[u8]) -> SerializerResult<()> {
+        self.begin(Compound::Seq { len: Some(v.len()) })?;
+        for &byte in v {
+            self.element()?;
+            self.serialize_u8(byte)?;
+        }
+        self.end()
+    }
+
+    /// Serialize a `None` value.
+    ///
+    /// Forwards to [`serialize_unit`](Self::serialize_unit) by default.
+    fn serialize_none(&mut self) -> SerializerResult<()> {
+        self.serialize_unit()
+    }
+
+    /// Serialize a `Some(T)` value.
+    ///
+    /// Serializes `value` transparently by default. An override serializes `value` with
+    /// [`Serialize::dyn_serialize`], and reports its error through
+    /// [`nested_error`](Self::nested_error).
+    fn serialize_some(&mut self, value: &dyn Serialize) -> SerializerResult<()>
+    where
+        Self: Sized,
+    {
+        serialize(self, value)
+    }
+
+    /// Serialize a `()` value, as well as `None` and unit structs by default.
+    fn serialize_unit(&mut self) -> SerializerResult<()>;
+
+    /// Serialize a unit struct like `struct Unit`.
+    ///
+    /// Forwards to [`serialize_unit`](Self::serialize_unit) by default.
+    fn serialize_unit_struct(&mut self, name: &'static str) -> SerializerResult<()> {
+        let _ = name;
+        self.serialize_unit()
+    }
+
+    /// Serialize a unit variant like `E::A` in `enum E { A, B }`.
+    ///
+    /// Forwards the name of the variant to [`serialize_str`](Self::serialize_str) by default.
+    fn serialize_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> SerializerResult<()> {
+        let _ = (name, variant_index);
+        self.serialize_str(variant)
+    }
+
+    /// Serialize a newtype struct like `struct Millimeters(u8)`.
+    ///
+    /// Serializes `value` transparently by default.
+    fn serialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()>
+    where
+        Self: Sized,
+    {
+        let _ = name;
+        serialize(self, value)
+    }
+
+    /// Begin to serialize a compound value.
+    fn begin(&mut self, compound: Compound) -> SerializerResult<()>;
+
+    /// Called before each element of a sequence, a tuple, a tuple struct, a tuple variant or a
+    /// newtype variant.
+    ///
+    /// Does nothing by default.
+    fn element(&mut self) -> SerializerResult<()> {
+        Ok(())
+    }
+
+    /// Called before each key of a map.
+    ///
+    /// Does nothing by default.
+    fn key(&mut self) -> SerializerResult<()> {
+        Ok(())
+    }
+
+    /// Called before each value of a map.
+    ///
+    /// Does nothing by default.
+    fn value(&mut self) -> SerializerResult<()> {
+        Ok(())
+    }
+
+    /// Called before each field of a struct or a struct variant.
+    ///
+    /// Does nothing by default.
+    fn field(&mut self, key: &'static str) -> SerializerResult<()> {
+        let _ = key;
+        Ok(())
+    }
+
+    /// Called for each skipped field of a struct or a struct variant.
+    ///
+    /// Does nothing by default.
+    fn skip_field(&mut self, key: &'static str) -> SerializerResult<()> {
+        let _ = key;
+        Ok(())
+    }
+
+    /// Finish serializing the compound value started by the last unfinished
+    /// [`begin`](Self::begin).
+    fn end(&mut self) -> SerializerResult<()>;
+
+    /// Serialize a string produced by an implementation of `Display`.
+    ///
+    /// Forwards to [`serialize_str`](Self::serialize_str) by default.
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn collect_str(&mut self, value: &dyn fmt::Display) -> SerializerResult<()> {
+        self.serialize_str(&value.to_string())
+    }
+
+    /// Serialize a string produced by an implementation of `Display`.
+    #[cfg(not(any(feature = "std", feature = "alloc")))]
+    fn collect_str(&mut self, value: &dyn fmt::Display) -> SerializerResult<()>;
+
+    /// Receives the error of a nested value, and returns the error reported to its parent.
+    ///
+    /// Returns [`SerializerError::Error`] and discards the error by default. Override it to keep
+    /// the error, e.g. by storing it in the serializer.
+    fn nested_error(&mut self, error: SerializeError) -> SerializerError {
+        let _ = error;
+        SerializerError::Error
+    }
+
+    /// Determine whether `Serialize` implementations should serialize in human-readable form.
+    ///
+    /// Returns `true` by default.
+    fn is_human_readable(&self) -> bool {
+        true
+    }
+}
+
+fn serialize<S: SimpleSerializer>(
+    serializer: &mut S,
+    value: &dyn Serialize,
+) -> SerializerResult<()> {
+    value
+        .dyn_serialize(serializer)
+        .map_err(|error| serializer.nested_error(error))
+}
+
+impl<S: SimpleSerializer> Serializer for S {
+    fn dyn_serialize_bool(&mut self, v: bool) -> SerializerResult<()> {
+        self.serialize_bool(v)
+    }
+
+    fn dyn_serialize_i8(&mut self, v: i8) -> SerializerResult<()> {
+        self.serialize_i8(v)
+    }
+
+    fn dyn_serialize_i16(&mut self, v: i16) -> SerializerResult<()> {
+        self.serialize_i16(v)
+    }
+
+    fn dyn_serialize_i32(&mut self, v: i32) -> SerializerResult<()> {
+        self.serialize_i32(v)
+    }
+
+    fn dyn_serialize_i64(&mut self, v: i64) -> SerializerResult<()> {
+        self.serialize_i64(v)
+    }
+
+    fn dyn_serialize_i128(&mut self, v: i128) -> SerializerResult<()> {
+        self.serialize_i128(v)
+    }
+
+    fn dyn_serialize_u8(&mut self, v: u8) -> SerializerResult<()> {
+        self.serialize_u8(v)
+    }
+
+    fn dyn_serialize_u16(&mut self, v: u16) -> SerializerResult<()> {
+        self.serialize_u16(v)
+    }
+
+    fn dyn_serialize_u32(&mut self, v: u32) -> SerializerResult<()> {
+        self.serialize_u32(v)
+    }
+
+    fn dyn_serialize_u64(&mut self, v: u64) -> SerializerResult<()> {
+        self.serialize_u64(v)
+    }
+
+    fn dyn_serialize_u128(&mut self, v: u128) -> SerializerResult<()> {
+        self.serialize_u128(v)
+    }
+
+    fn dyn_serialize_f32(&mut self, v: f32) -> SerializerResult<()> {
+        self.serialize_f32(v)
+    }
+
+    fn dyn_serialize_f64(&mut self, v: f64) -> SerializerResult<()> {
+        self.serialize_f64(v)
+    }
+
+    fn dyn_serialize_char(&mut self, v: char) -> SerializerResult<()> {
+        self.serialize_char(v)
+    }
+
+    fn dyn_serialize_str(&mut self, v: &str) -> SerializerResult<()> {
+        self.serialize_str(v)
+    }
+
+    fn dyn_serialize_bytes(&mut self, v: &[u8]) -> SerializerResult<()> {
+        self.serialize_bytes(v)
+    }
+
+    fn dyn_serialize_none(&mut self) -> SerializerResult<()> {
+        self.serialize_none()
+    }
+
+    fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.serialize_some(value)
+    }
+
+    fn dyn_serialize_unit(&mut self) -> SerializerResult<()> {
+        self.serialize_unit()
+    }
+
+    fn dyn_serialize_unit_struct(&mut self, name: &'static str) -> SerializerResult<()> {
+        self.serialize_unit_struct(name)
+    }
+
+    fn dyn_serialize_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> SerializerResult<()> {
+        self.serialize_unit_variant(name, variant_index, variant)
+    }
+
+    fn dyn_serialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        self.serialize_newtype_struct(name, value)
+    }
+
+    fn dyn_serialize_newtype_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        self.begin(Compound::NewtypeVariant {
+            name,
+            variant_index,
+            variant,
+        })?;
+        self.element()?;
+        serialize(self, value)?;
+        self.end()
+    }
+
+    fn dyn_serialize_seq(&mut self, len: Option<usize>) -> SerializerResult<&mut dyn SerializeSeq> {
+        self.begin(Compound::Seq { len })?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple(&mut self, len: usize) -> SerializerResult<&mut dyn SerializeTuple> {
+        self.begin(Compound::Tuple { len })?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeTupleStruct> {
+        self.begin(Compound::TupleStruct { name, len })?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeTupleVariant> {
+        self.begin(Compound::TupleVariant {
+            name,
+            variant_index,
+            variant,
+            len,
+        })?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_map(&mut self, len: Option<usize>) -> SerializerResult<&mut dyn SerializeMap> {
+        self.begin(Compound::Map { len })?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeStruct> {
+        self.begin(Compound::Struct { name, len })?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_struct_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeStructVariant> {
+        self.begin(Compound::StructVariant {
+            name,
+            variant_index,
+            variant,
+            len,
+        })?;
+        Ok(self)
+    }
+
+    fn dyn_collect_str(&mut self, value: &dyn fmt::Display) -> SerializerResult<()> {
+        self.collect_str(value)
+    }
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.is_human_readable()
+    }
+}
+
+impl<S: SimpleSerializer> SerializeSeq for S {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.element()?;
+        serialize(self, value)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.end()
+    }
+}
+
+impl<S: SimpleSerializer> SerializeTuple for S {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.element()?;
+        serialize(self, value)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.end()
+    }
+}
+
+impl<S: SimpleSerializer> SerializeTupleStruct for S {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.element()?;
+        serialize(self, value)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.end()
+    }
+}
+
+impl<S: SimpleSerializer> SerializeTupleVariant for S {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.element()?;
+        serialize(self, value)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.end()
+    }
+}
+
+impl<S: SimpleSerializer> SerializeMap for S {
+    fn dyn_serialize_key(&mut self, key: &dyn Serialize) -> SerializerResult<()> {
+        self.key()?;
+        serialize(self, key)
+    }
+
+    fn dyn_serialize_value(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.value()?;
+        serialize(self, value)
+    }
+
+    fn dyn_serialize_entry(
+        &mut self,
+        key: &dyn Serialize,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        self.dyn_serialize_key(key)?;
+        self.dyn_serialize_value(value)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.end()
+    }
+}
+
+impl<S: SimpleSerializer> SerializeStruct for S {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        self.field(key)?;
+        serialize(self, value)
+    }
+
+    fn dyn_skip_field(&mut self, key: &'static str) -> SerializerResult<()> {
+        self.skip_field(key)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.end()
+    }
+}
+
+impl<S: SimpleSerializer> SerializeStructVariant for S {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        self.field(key)?;
+        serialize(self, value)
+    }
+
+    fn dyn_skip_field(&mut self, key: &'static str) -> SerializerResult<()> {
+        self.skip_field(key)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.end()
+    }
+}
@@ -0,0 +1,814 @@
+use core::cell::Cell;
+use core::{array, fmt, mem};
+
+use super::{
+    Serialize, SerializeError, SerializeMap, SerializeResult, SerializeSeq, SerializeStruct,
+    SerializeStructVariant, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+    Serializer, SerializerError, SerializerResult, SerializerState,
+};
+
+/// A [`Serializer`] that writes one value into several dynamic serializers in a single pass.
+///
+/// Every call, including the ones on the returned `SerializeSeq`, `SerializeMap`, etc., is
+/// forwarded to each of the `N` branches in order. A nested value is handed to every branch, and
+/// once all of them are ready to serialize it, it's serialized only once into a tee of their
+/// nested serializers. So a one-shot value, such as an iterator behind a `RefCell`, is only
+/// consumed once at any depth.
+///
+/// Map entries are forwarded as a key followed by a value, so that each of them is serialized
+/// once.
+///
+/// # Failure policy
+///
+/// A branch that fails is detached, and the remaining branches go on with the serialization. A
+/// call on the tee only fails when every branch has failed, so that one broken output doesn't
+/// lose the others. Check [`Tee::errors`] afterwards to find out which branches are incomplete,
+/// and inspect their [`InplaceSerializer`](super::InplaceSerializer) for the original errors.
+///
+/// # Human-readable form
+///
+/// The tee is human-readable only if every branch is. The nested values observe the same, since
+/// they are serialized into a tee as well.
+///
+/// # Examples
+///
+/// ```
+/// # use serde_dyn::Serializer;
+/// # use serde_dyn::ser::Tee;
+/// let mut json = Vec::new();
+/// let mut json_serializer = serde_json::Serializer::new(&mut json);
+/// let mut json_serializer = <dyn Serializer>::new(&mut json_serializer);
+///
+/// let mut cbor = Vec::new();
+/// let mut cbor_serializer = serde_cbor::Serializer::new(&mut cbor);
+/// let mut cbor_serializer = <dyn Serializer>::new(&mut cbor_serializer);
+///
+/// let mut tee = Tee::new([&mut json_serializer, &mut cbor_serializer]);
+/// let event = ("login", 42_u8);
+/// serde::Serialize::serialize(&event, &mut tee as &mut dyn Serializer).unwrap();
+/// assert!(tee.errors().iter().all(Option::is_none));
+///
+/// assert_eq!(json, b"[\"login\",42]");
+/// assert_eq!(cbor, b"\x82\x65login\x18\x2a");
+/// ```
+pub struct Tee<'a, const N: usize> {
+    branches: [Branch<'a>; N],
+}
+
+/// The state of a branch of [`Tee`].
+#[derive(Default)]
+enum Branch<'a> {
+    /// The branch has finished successfully.
+    #[default]
+    Done,
+    /// The branch has failed and is detached.
+    Failed(SerializerError),
+    Serializer(&'a mut dyn Serializer),
+    SerializeSeq(&'a mut dyn SerializeSeq),
+    SerializeTuple(&'a mut dyn SerializeTuple),
+    SerializeTupleStruct(&'a mut dyn SerializeTupleStruct),
+    SerializeTupleVariant(&'a mut dyn SerializeTupleVariant),
+    SerializeMap(&'a mut dyn SerializeMap),
+    SerializeStruct(&'a mut dyn SerializeStruct),
+    SerializeStructVariant(&'a mut dyn SerializeStructVariant),
+}
+
+impl<'a, const N: usize> Tee<'a, N> {
+    /// Creates a tee writing into every serializer of `serializers`.
+    #[must_use]
+    pub fn new(serializers: [&'a mut dyn Serializer; N]) -> Self {
+        Tee {
+            branches: serializers.map(Branch::Serializer),
+        }
+    }
+
+    /// Returns the error of each branch, or `None` if the branch hasn't failed.
+    #[must_use]
+    pub fn errors(&self) -> [Option<SerializerError>; N] {
+        self.branches.each_ref().map(|branch| match branch {
+            Branch::Failed(error) => Some(*error),
+            _ => None,
+        })
+    }
+
+    /// Runs `f` on every branch that hasn't failed, and detaches the ones `f` fails on.
+    ///
+    /// `f` is given the branch and `operation`, the name of the forwarded method.
+    fn forward<F>(&mut self, operation: &'static str, mut f: F) -> SerializerResult<()>
+    where
+        F: FnMut(&mut Branch<'a>, &'static str) -> SerializerResult<()>,
+    {
+        for branch in &mut self.branches {
+            if matches!(branch, Branch::Failed(_)) {
+                continue;
+            }
+            if let Err(error) = f(branch, operation) {
+                *branch = Branch::Failed(error);
+            }
+        }
+        self.result(operation)
+    }
+
+    /// Runs `f` on every branch that hasn't failed with a stand-in of `value`, and detaches the
+    /// ones `f` fails on. `value` is serialized once into every branch that serializes the
+    /// stand-in.
+    fn nested<F>(
+        &mut self,
+        operation: &'static str,
+        value: &dyn Serialize,
+        f: F,
+    ) -> SerializerResult<()>
+    where
+        F: Fn(&mut Branch<'a>, &dyn Serialize) -> SerializerResult<()>,
+    {
+        let nested = Nested { value, f: &f };
+        nested.enter::<N>(&mut self.branches, array::from_fn(|_| None));
+        self.result(operation)
+    }
+
+    /// Returns the result of a forwarded call, which only fails when every branch has failed.
+    fn result(&self, operation: &'static str) -> SerializerResult<()> {
+        // A tee without branches is never ready.
+        let mut result = Err(SerializerError::WrongState {
+            operation,
+            found: SerializerState::None,
+        });
+        for branch in &self.branches {
+            match branch {
+                Branch::Failed(error) => result = result.or(Err(*error)),
+                _ => return Ok(()),
+            }
+        }
+        result
+    }
+
+    /// Runs `f` on every branch that is ready to serialize a value.
+    fn serialize<F>(&mut self, operation: &'static str, mut f: F) -> SerializerResult<()>
+    where
+        F: FnMut(&mut dyn Serializer) -> SerializerResult<()>,
+    {
+        self.forward(operation, |branch, operation| match mem::take(branch) {
+            Branch::Serializer(serializer) => f(serializer),
+            other => Err(other.wrong_state(operation)),
+        })
+    }
+}
+
+impl Branch<'_> {
+    /// Returns the state of the branch.
+    fn state(&self) -> SerializerState {
+        match self {
+            Branch::Done => SerializerState::Ok,
+            Branch::Failed(_) => SerializerState::Error,
+            Branch::Serializer(_) => SerializerState::Serializer,
+            Branch::SerializeSeq(_) => SerializerState::SerializeSeq,
+            Branch::SerializeTuple(_) => SerializerState::SerializeTuple,
+            Branch::SerializeTupleStruct(_) => SerializerState::SerializeTupleStruct,
+            Branch::SerializeTupleVariant(_) => SerializerState::SerializeTupleVariant,
+            Branch::SerializeMap(_) => SerializerState::SerializeMap,
+            Branch::SerializeStruct(_) => SerializerState::SerializeStruct,
+            Branch::SerializeStructVariant(_) => SerializerState::SerializeStructVariant,
+        }
+    }
+
+    #[cold]
+    fn wrong_state(&self, operation: &'static str) -> SerializerError {
+        SerializerError::WrongState {
+            operation,
+            found: self.state(),
+        }
+    }
+}
+
+/// A nested value forwarded to the branches of [`Tee`].
+struct Nested<'n, 'a> {
+    value: &'n dyn Serialize,
+    /// Forwards the stand-in of the value to a branch.
+    f: &'n dyn Fn(&mut Branch<'a>, &dyn Serialize) -> SerializerResult<()>,
+}
+
+impl<'a> Nested<'_, 'a> {
+    /// Forwards the value to the first branch of `branches` that hasn't failed, and the rest of
+    /// the branches are entered once the branch serializes the stand-in.
+    ///
+    /// `inner` holds the nested serializers of the branches entered so far, indexed by the
+    /// position of the branch in the tee, which ends with `branches`. Returns the result of the
+    /// value in each nested serializer.
+    fn enter<const N: usize>(
+        &self,
+        branches: &mut [Branch<'a>],
+        inner: [Option<&mut dyn Serializer>; N],
+    ) -> [SerializeResult<()>; N] {
+        let offset = N - branches.len();
+        let Some(index) = branches
+            .iter()
+            .position(|branch| !matches!(branch, Branch::Failed(_)))
+        else {
+            return self.serialize(inner);
+        };
+        let (branch, rest) = branches[index..]
+            .split_first_mut()
+            .expect("the branch is found");
+        let enter = Enter {
+            nested: self,
+            index: offset + index,
+            rest: Cell::new(Some(rest)),
+            inner: Cell::new(Some(inner)),
+            results: Cell::new(None),
+        };
+        if let Err(error) = (self.f)(branch, &enter) {
+            *branch = Branch::Failed(error);
+        }
+
+        match (
+            enter.results.into_inner(),
+            enter.rest.into_inner(),
+            enter.inner.into_inner(),
+        ) {
+            (Some(results), _, _) => results,
+            // The branch didn't serialize the stand-in, so it's left out.
+            (None, Some(rest), Some(inner)) => self.enter(rest, inner),
+            (None, _, _) => unreachable!("the rest is only taken along with the results"),
+        }
+    }
+
+    /// Serializes the value once into every nested serializer of `inner`.
+    fn serialize<const N: usize>(
+        &self,
+        inner: [Option<&mut dyn Serializer>; N],
+    ) -> [SerializeResult<()>; N] {
+        // The results of the branches left out are not used.
+        let mut tee = Tee {
+            branches: inner.map(|serializer| serializer.map_or(Branch::Done, Branch::Serializer)),
+        };
+        let result = self.value.dyn_serialize(&mut tee);
+        tee.branches.map(|branch| match branch {
+            Branch::Failed(error) => Err(SerializeError::from(error)),
+            // The error is only `Copy` without `alloc`.
+            _ => Clone::clone(&result),
+        })
+    }
+}
+
+/// The stand-in of a nested value, given to the branch at `index`.
+struct Enter<'e, 'n, 'a, 'i, const N: usize> {
+    nested: &'e Nested<'n, 'a>,
+    index: usize,
+    rest: Cell<Option<&'e mut [Branch<'a>]>>,
+    inner: Cell<Option<[Option<&'i mut dyn Serializer>; N]>>,
+    results: Cell<Option<[SerializeResult<()>; N]>>,
+}
+
+impl<const N: usize> Serialize for Enter<'_, '_, '_, '_, N> {
+    fn dyn_serialize(&self, serializer: &mut dyn Serializer) -> SerializeResult<()> {
+        let (Some(rest), Some(inner)) = (self.rest.take(), self.inner.take()) else {
+            // The branch serializes the stand-in again, which is left to the branch alone.
+            return self.nested.value.dyn_serialize(serializer);
+        };
+        let mut inner = inner.map(|inner| inner.map(|inner| inner as &mut dyn Serializer));
+        inner[self.index] = Some(serializer);
+        let mut results = self.nested.enter(rest, inner);
+        // Each result is only taken by the stand-in of its branch.
+        let result = mem::replace(&mut results[self.index], Ok(()));
+        self.results.set(Some(results));
+        result
+    }
+}
+
+impl<const N: usize> Serializer for Tee<'_, N> {
+    fn dyn_serialize_bool(&mut self, v: bool) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_bool", |serializer| {
+            serializer.dyn_serialize_bool(v)
+        })
+    }
+
+    fn dyn_serialize_i8(&mut self, v: i8) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_i8", |serializer| {
+            serializer.dyn_serialize_i8(v)
+        })
+    }
+
+    fn dyn_serialize_i16(&mut self, v: i16) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_i16", |serializer| {
+            serializer.dyn_serialize_i16(v)
+        })
+    }
+
+    fn dyn_serialize_i32(&mut self, v: i32) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_i32", |serializer| {
+            serializer.dyn_serialize_i32(v)
+        })
+    }
+
+    fn dyn_serialize_i64(&mut self, v: i64) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_i64", |serializer| {
+            serializer.dyn_serialize_i64(v)
+        })
+    }
+
+    fn dyn_serialize_i128(&mut self, v: i128) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_i128", |serializer| {
+            serializer.dyn_serialize_i128(v)
+        })
+    }
+
+    fn dyn_serialize_u8(&mut self, v: u8) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_u8", |serializer| {
+            serializer.dyn_serialize_u8(v)
+        })
+    }
+
+    fn dyn_serialize_u16(&mut self, v: u16) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_u16", |serializer| {
+            serializer.dyn_serialize_u16(v)
+        })
+    }
+
+    fn dyn_serialize_u32(&mut self, v: u32) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_u32", |serializer| {
+            serializer.dyn_serialize_u32(v)
+        })
+    }
+
+    fn dyn_serialize_u64(&mut self, v: u64) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_u64", |serializer| {
+            serializer.dyn_serialize_u64(v)
+        })
+    }
+
+    fn dyn_serialize_u128(&mut self, v: u128) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_u128", |serializer| {
+            serializer.dyn_serialize_u128(v)
+        })
+    }
+
+    fn dyn_serialize_f32(&mut self, v: f32) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_f32", |serializer| {
+            serializer.dyn_serialize_f32(v)
+        })
+    }
+
+    fn dyn_serialize_f64(&mut self, v: f64) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_f64", |serializer| {
+            serializer.dyn_serialize_f64(v)
+        })
+    }
+
+    fn dyn_serialize_char(&mut self, v: char) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_char", |serializer| {
+            serializer.dyn_serialize_char(v)
+        })
+    }
+
+    fn dyn_serialize_str(&mut self, v: &str) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_str", |serializer| {
+            serializer.dyn_serialize_str(v)
+        })
+    }
+
+    fn dyn_serialize_bytes(&mut self, v: &[u8]) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_bytes", |serializer| {
+            serializer.dyn_serialize_bytes(v)
+        })
+    }
+
+    fn dyn_serialize_none(&mut self) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_none", |serializer| {
+            serializer.dyn_serialize_none()
+        })
+    }
+
+    fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.nested(
+            "Serializer::dyn_serialize_some",
+            value,
+            |branch, value| match mem::take(branch) {
+                Branch::Serializer(serializer) => serializer.dyn_serialize_some(value),
+                other => Err(other.wrong_state("Serializer::dyn_serialize_some")),
+            },
+        )
+    }
+
+    fn dyn_serialize_unit(&mut self) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_unit", |serializer| {
+            serializer.dyn_serialize_unit()
+        })
+    }
+
+    fn dyn_serialize_unit_struct(&mut self, name: &'static str) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_unit_struct", |serializer| {
+            serializer.dyn_serialize_unit_struct(name)
+        })
+    }
+
+    fn dyn_serialize_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_serialize_unit_variant", |serializer| {
+            serializer.dyn_serialize_unit_variant(name, variant_index, variant)
+        })
+    }
+
+    fn dyn_serialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        self.nested(
+            "Serializer::dyn_serialize_newtype_struct",
+            value,
+            |branch, value| match mem::take(branch) {
+                Branch::Serializer(serializer) => {
+                    serializer.dyn_serialize_newtype_struct(name, value)
+                }
+                other => Err(other.wrong_state("Serializer::dyn_serialize_newtype_struct")),
+            },
+        )
+    }
+
+    fn dyn_serialize_newtype_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        self.nested(
+            "Serializer::dyn_serialize_newtype_variant",
+            value,
+            |branch, value| match mem::take(branch) {
+                Branch::Serializer(serializer) => {
+                    serializer.dyn_serialize_newtype_variant(name, variant_index, variant, value)
+                }
+                other => Err(other.wrong_state("Serializer::dyn_serialize_newtype_variant")),
+            },
+        )
+    }
+
+    fn dyn_serialize_seq(&mut self, len: Option<usize>) -> SerializerResult<&mut dyn SerializeSeq> {
+        self.forward(
+            "Serializer::dyn_serialize_seq",
+            |branch, operation| match mem::take(branch) {
+                Branch::Serializer(serializer) => {
+                    *branch = Branch::SerializeSeq(serializer.dyn_serialize_seq(len)?);
+                    Ok(())
+                }
+                other => Err(other.wrong_state(operation)),
+            },
+        )?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple(&mut self, len: usize) -> SerializerResult<&mut dyn SerializeTuple> {
+        self.forward(
+            "Serializer::dyn_serialize_tuple",
+            |branch, operation| match mem::take(branch) {
+                Branch::Serializer(serializer) => {
+                    *branch = Branch::SerializeTuple(serializer.dyn_serialize_tuple(len)?);
+                    Ok(())
+                }
+                other => Err(other.wrong_state(operation)),
+            },
+        )?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeTupleStruct> {
+        self.forward(
+            "Serializer::dyn_serialize_tuple_struct",
+            |branch, operation| match mem::take(branch) {
+                Branch::Serializer(serializer) => {
+                    *branch = Branch::SerializeTupleStruct(
+                        serializer.dyn_serialize_tuple_struct(name, len)?,
+                    );
+                    Ok(())
+                }
+                other => Err(other.wrong_state(operation)),
+            },
+        )?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeTupleVariant> {
+        self.forward(
+            "Serializer::dyn_serialize_tuple_variant",
+            |branch, operation| match mem::take(branch) {
+                Branch::Serializer(serializer) => {
+                    *branch =
+                        Branch::SerializeTupleVariant(serializer.dyn_serialize_tuple_variant(
+                            name,
+                            variant_index,
+                            variant,
+                            len,
+                        )?);
+                    Ok(())
+                }
+                other => Err(other.wrong_state(operation)),
+            },
+        )?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_map(&mut self, len: Option<usize>) -> SerializerResult<&mut dyn SerializeMap> {
+        self.forward(
+            "Serializer::dyn_serialize_map",
+            |branch, operation| match mem::take(branch) {
+                Branch::Serializer(serializer) => {
+                    *branch = Branch::SerializeMap(serializer.dyn_serialize_map(len)?);
+                    Ok(())
+                }
+                other => Err(other.wrong_state(operation)),
+            },
+        )?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeStruct> {
+        self.forward(
+            "Serializer::dyn_serialize_struct",
+            |branch, operation| match mem::take(branch) {
+                Branch::Serializer(serializer) => {
+                    *branch = Branch::SerializeStruct(serializer.dyn_serialize_struct(name, len)?);
+                    Ok(())
+                }
+                other => Err(other.wrong_state(operation)),
+            },
+        )?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_struct_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeStructVariant> {
+        self.forward(
+            "Serializer::dyn_serialize_struct_variant",
+            |branch, operation| match mem::take(branch) {
+                Branch::Serializer(serializer) => {
+                    *branch =
+                        Branch::SerializeStructVariant(serializer.dyn_serialize_struct_variant(
+                            name,
+                            variant_index,
+                            variant,
+                            len,
+                        )?);
+                    Ok(())
+                }
+                other => Err(other.wrong_state(operation)),
+            },
+        )?;
+        Ok(self)
+    }
+
+    fn dyn_collect_str(&mut self, value: &dyn fmt::Display) -> SerializerResult<()> {
+        self.serialize("Serializer::dyn_collect_str", |serializer| {
+            serializer.dyn_collect_str(value)
+        })
+    }
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.branches.iter().all(|branch| match branch {
+            Branch::Serializer(serializer) => serializer.dyn_is_human_readable(),
+            _ => true,
+        })
+    }
+}
+
+impl<const N: usize> SerializeSeq for Tee<'_, N> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.nested(
+            "SerializeSeq::dyn_serialize_element",
+            value,
+            |branch, value| match branch {
+                Branch::SerializeSeq(serializer) => serializer.dyn_serialize_element(value),
+                other => Err(other.wrong_state("SerializeSeq::dyn_serialize_element")),
+            },
+        )
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.forward(
+            "SerializeSeq::dyn_end",
+            |branch, operation| match mem::take(branch) {
+                Branch::SerializeSeq(serializer) => serializer.dyn_end(),
+                other => Err(other.wrong_state(operation)),
+            },
+        )
+    }
+}
+
+impl<const N: usize> SerializeTuple for Tee<'_, N> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.nested(
+            "SerializeTuple::dyn_serialize_element",
+            value,
+            |branch, value| match branch {
+                Branch::SerializeTuple(serializer) => serializer.dyn_serialize_element(value),
+                other => Err(other.wrong_state("SerializeTuple::dyn_serialize_element")),
+            },
+        )
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.forward(
+            "SerializeTuple::dyn_end",
+            |branch, operation| match mem::take(branch) {
+                Branch::SerializeTuple(serializer) => serializer.dyn_end(),
+                other => Err(other.wrong_state(operation)),
+            },
+        )
+    }
+}
+
+impl<const N: usize> SerializeTupleStruct for Tee<'_, N> {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.nested(
+            "SerializeTupleStruct::dyn_serialize_field",
+            value,
+            |branch, value| match branch {
+                Branch::SerializeTupleStruct(serializer) => serializer.dyn_serialize_field(value),
+                other => Err(other.wrong_state("SerializeTupleStruct::dyn_serialize_field")),
+            },
+        )
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.forward(
+            "SerializeTupleStruct::dyn_end",
+            |branch, operation| match mem::take(branch) {
+                Branch::SerializeTupleStruct(serializer) => serializer.dyn_end(),
+                other => Err(other.wrong_state(operation)),
+            },
+        )
+    }
+}
+
+impl<const N: usize> SerializeTupleVariant for Tee<'_, N> {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.nested(
+            "SerializeTupleVariant::dyn_serialize_field",
+            value,
+            |branch, value| match branch {
+                Branch::SerializeTupleVariant(serializer) => serializer.dyn_serialize_field(value),
+                other => Err(other.wrong_state("SerializeTupleVariant::dyn_serialize_field")),
+            },
+        )
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.forward(
+            "SerializeTupleVariant::dyn_end",
+            |branch, operation| match mem::take(branch) {
+                Branch::SerializeTupleVariant(serializer) => serializer.dyn_end(),
+                other => Err(other.wrong_state(operation)),
+            },
+        )
+    }
+}
+
+impl<const N: usize> SerializeMap for Tee<'_, N> {
+    fn dyn_serialize_key(&mut self, key: &dyn Serialize) -> SerializerResult<()> {
+        self.nested(
+            "SerializeMap::dyn_serialize_key",
+            key,
+            |branch, key| match branch {
+                Branch::SerializeMap(serializer) => serializer.dyn_serialize_key(key),
+                other => Err(other.wrong_state("SerializeMap::dyn_serialize_key")),
+            },
+        )
+    }
+
+    fn dyn_serialize_value(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        self.nested(
+            "SerializeMap::dyn_serialize_value",
+            value,
+            |branch, value| match branch {
+                Branch::SerializeMap(serializer) => serializer.dyn_serialize_value(value),
+                other => Err(other.wrong_state("SerializeMap::dyn_serialize_value")),
+            },
+        )
+    }
+
+    fn dyn_serialize_entry(
+        &mut self,
+        key: &dyn Serialize,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        self.dyn_serialize_key(key)?;
+        self.dyn_serialize_value(value)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.forward(
+            "SerializeMap::dyn_end",
+            |branch, operation| match mem::take(branch) {
+                Branch::SerializeMap(serializer) => serializer.dyn_end(),
+                other => Err(other.wrong_state(operation)),
+            },
+        )
+    }
+}
+
+impl<const N: usize> SerializeStruct for Tee<'_, N> {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        self.nested(
+            "SerializeStruct::dyn_serialize_field",
+            value,
+            |branch, value| match branch {
+                Branch::SerializeStruct(serializer) => serializer.dyn_serialize_field(key, value),
+                other => Err(other.wrong_state("SerializeStruct::dyn_serialize_field")),
+            },
+        )
+    }
+
+    fn dyn_skip_field(&mut self, key: &'static str) -> SerializerResult<()> {
+        self.forward(
+            "SerializeStruct::dyn_skip_field",
+            |branch, operation| match branch {
+                Branch::SerializeStruct(serializer) => serializer.dyn_skip_field(key),
+                other => Err(other.wrong_state(operation)),
+            },
+        )
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.forward(
+            "SerializeStruct::dyn_end",
+            |branch, operation| match mem::take(branch) {
+                Branch::SerializeStruct(serializer) => serializer.dyn_end(),
+                other => Err(other.wrong_state(operation)),
+            },
+        )
+    }
+}
+
+impl<const N: usize> SerializeStructVariant for Tee<'_, N> {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        self.nested(
+            "SerializeStructVariant::dyn_serialize_field",
+            value,
+            |branch, value| match branch {
+                Branch::SerializeStructVariant(serializer) => {
+                    serializer.dyn_serialize_field(key, value)
+                }
+                other => Err(other.wrong_state("SerializeStructVariant::dyn_serialize_field")),
+            },
+        )
+    }
+
+    fn dyn_skip_field(&mut self, key: &'static str) -> SerializerResult<()> {
+        self.forward(
+            "SerializeStructVariant::dyn_skip_field",
+            |branch, operation| match branch {
+                Branch::SerializeStructVariant(serializer) => serializer.dyn_skip_field(key),
+                other => Err(other.wrong_state(operation)),
+            },
+        )
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        self.forward(
+            "SerializeStructVariant::dyn_end",
+            |branch, operation| match mem::take(branch) {
+                Branch::SerializeStructVariant(serializer) => serializer.dyn_end(),
+                other => Err(other.wrong_state(operation)),
+            },
+        )
+    }
+}
+
+impl<const N: usize> fmt::Debug for Tee<'_, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tee")
+            .field("errors", &self.errors())
+            .finish_non_exhaustive()
+    }
+}
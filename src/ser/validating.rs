@@ -0,0 +1,946 @@
+use core::cell::Cell;
+use core::{error, fmt, mem};
+
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::vec::Vec;
+
+use super::{
+    Serialize, SerializeError, SerializeMap, SerializeResult, SerializeSeq, SerializeStruct,
+    SerializeStructVariant, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+    Serializer, SerializerError, SerializerResult, SerializerState,
+};
+
+/// A [`Serializer`] that checks the calls coming from [`Serialize`] implementations, before
+/// forwarding them to the wrapped serializer.
+///
+/// The following mistakes are caught and reported as a [`ValidationError`]:
+///
+/// - a call that the current state doesn't accept, such as a map value without a preceding key,
+///   a second value, or a call after `dyn_end`;
+/// - a compound whose number of items differs from the announced length, which corrupts the
+///   output of length-prefixed formats;
+/// - a duplicate field in a struct or a struct variant.
+///
+/// A violation is not forwarded. The nested values are validated as well, and their violations are
+/// returned as a [`SerializeError`] whose source is the [`ValidationError`], and recorded as the
+/// violation of the parent.
+///
+/// # Examples
+///
+/// ```
+/// # use serde_dyn::Serializer;
+/// # use serde_dyn::ser::{ValidationError, ValidationState, Validating};
+/// let mut json = serde_json::Serializer::new(Vec::new());
+/// let mut json = <dyn Serializer>::new(&mut json);
+/// let mut validating = Validating::new(&mut json);
+///
+/// let map = validating.dyn_serialize_map(Some(1)).unwrap();
+/// assert!(map.dyn_serialize_value(&1).is_err());
+/// assert_eq!(
+///     validating.finish(),
+///     Err(ValidationError::UnexpectedCall {
+///         call: "dyn_serialize_value",
+///         expected: ValidationState::MapValue,
+///         found: ValidationState::MapKey,
+///     })
+/// );
+/// ```
+pub struct Validating<'a> {
+    state: State<'a>,
+    is_human_readable: bool,
+    error: Option<ValidationError>,
+    /// The first violation of the nested values, recorded by [`Validate`].
+    nested: Cell<Option<ValidationError>>,
+}
+
+/// The state of [`Validating`], together with the wrapped serializer.
+#[derive(Default)]
+enum State<'a> {
+    #[default]
+    Failed,
+    Done,
+    Serializer(&'a mut dyn Serializer),
+    SerializeSeq(&'a mut dyn SerializeSeq, Count),
+    SerializeTuple(&'a mut dyn SerializeTuple, Count),
+    SerializeTupleStruct(&'a mut dyn SerializeTupleStruct, Count),
+    SerializeTupleVariant(&'a mut dyn SerializeTupleVariant, Count),
+    /// The flag tells whether a key is waiting for its value.
+    SerializeMap(&'a mut dyn SerializeMap, Count, bool),
+    SerializeStruct(&'a mut dyn SerializeStruct, Count, Vec<&'static str>),
+    SerializeStructVariant(&'a mut dyn SerializeStructVariant, Count, Vec<&'static str>),
+}
+
+impl State<'_> {
+    fn count(&self) -> Option<&Count> {
+        match self {
+            State::SerializeSeq(_, count)
+            | State::SerializeTuple(_, count)
+            | State::SerializeTupleStruct(_, count)
+            | State::SerializeTupleVariant(_, count)
+            | State::SerializeMap(_, count, _)
+            | State::SerializeStruct(_, count, _)
+            | State::SerializeStructVariant(_, count, _) => Some(count),
+            State::Failed | State::Done | State::Serializer(_) => None,
+        }
+    }
+}
+
+/// The number of the items serialized into a compound, and the announced length.
+struct Count {
+    len: Option<usize>,
+    count: usize,
+}
+
+impl Count {
+    fn new(len: Option<usize>) -> Self {
+        Count { len, count: 0 }
+    }
+
+    fn next(&mut self, kind: CompoundKind) -> Result<(), ValidationError> {
+        match self.len {
+            Some(len) if self.count >= len => Err(ValidationError::LengthMismatch {
+                kind,
+                expected: len,
+                found: self.count + 1,
+            }),
+            _ => {
+                self.count += 1;
+                Ok(())
+            }
+        }
+    }
+
+    fn end(&self, kind: CompoundKind) -> Result<(), ValidationError> {
+        match self.len {
+            Some(len) if self.count != len => Err(ValidationError::LengthMismatch {
+                kind,
+                expected: len,
+                found: self.count,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<'a> Validating<'a> {
+    /// Wraps `serializer`.
+    #[must_use]
+    pub fn new(serializer: &'a mut dyn Serializer) -> Self {
+        Validating {
+            is_human_readable: serializer.dyn_is_human_readable(),
+            state: State::Serializer(serializer),
+            error: None,
+            nested: Cell::new(None),
+        }
+    }
+
+    /// Serializes `value` into `serializer` while validating the calls.
+    ///
+    /// A violation is preferred over the error it causes, and is returned as a [`SerializeError`]
+    /// whose source is the [`ValidationError`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde_dyn::ser::{self, ValidationError, Validating};
+    /// struct Short;
+    ///
+    /// impl serde::Serialize for Short {
+    ///     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    ///         use serde::ser::SerializeSeq;
+    ///         let mut seq = serializer.serialize_seq(Some(2))?;
+    ///         seq.serialize_element(&1)?;
+    ///         seq.end()
+    ///     }
+    /// }
+    ///
+    /// let mut json = serde_json::Serializer::new(Vec::new());
+    /// let error = ser::with_dyn(&mut json, |serializer| {
+    ///     Validating::serialize(serializer, &Short).map_err(serde::ser::Error::custom)
+    /// })
+    /// .unwrap_err();
+    /// assert_eq!(error.to_string(), "expected a sequence of length 2, found length 1");
+    /// ```
+    pub fn serialize(
+        serializer: &mut dyn Serializer,
+        value: &dyn Serialize,
+    ) -> SerializeResult<()> {
+        Validating::validate(serializer, value).1
+    }
+
+    /// Serializes `value` into `serializer` while validating the calls, and returns the first
+    /// violation together with the result.
+    fn validate(
+        serializer: &mut dyn Serializer,
+        value: &dyn Serialize,
+    ) -> (Option<ValidationError>, SerializeResult<()>) {
+        let mut validating = Validating::new(serializer);
+        let result = value.dyn_serialize(&mut validating);
+        let violation = match (validating.error, result) {
+            (Some(error), _) => error,
+            (None, Err(error)) => return (None, Err(error)),
+            (None, Ok(())) => match validating.finish() {
+                Ok(()) => return (None, Ok(())),
+                Err(error) => error,
+            },
+        };
+        (Some(violation), Err(SerializeError::from_source(violation)))
+    }
+
+    /// Returns the current state.
+    #[must_use]
+    pub fn state(&self) -> ValidationState {
+        match &self.state {
+            State::Failed => ValidationState::Failed,
+            State::Done => ValidationState::Done,
+            State::Serializer(_) => ValidationState::Ready,
+            State::SerializeSeq(..) => ValidationState::Seq,
+            State::SerializeTuple(..) => ValidationState::Tuple,
+            State::SerializeTupleStruct(..) => ValidationState::TupleStruct,
+            State::SerializeTupleVariant(..) => ValidationState::TupleVariant,
+            State::SerializeMap(_, _, false) => ValidationState::MapKey,
+            State::SerializeMap(_, _, true) => ValidationState::MapValue,
+            State::SerializeStruct(..) => ValidationState::Struct,
+            State::SerializeStructVariant(..) => ValidationState::StructVariant,
+        }
+    }
+
+    /// Returns the first violation.
+    #[must_use]
+    pub fn error(&self) -> Option<ValidationError> {
+        self.error
+    }
+
+    /// Consumes the validator, and returns the first violation.
+    ///
+    /// If nothing is violated, but the value is neither produced nor failed, a
+    /// [`ValidationError::Unfinished`] is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first violation, or [`ValidationError::Unfinished`].
+    pub fn finish(self) -> Result<(), ValidationError> {
+        match (self.error, self.state()) {
+            (Some(error), _) => Err(error),
+            (None, ValidationState::Done | ValidationState::Failed) => Ok(()),
+            (None, found) => Err(ValidationError::Unfinished { found }),
+        }
+    }
+
+    fn violate(&mut self, error: ValidationError, call: &'static str) -> SerializerError {
+        self.error.get_or_insert(error);
+        SerializerError::WrongState {
+            operation: call,
+            found: self.state().serializer_state(),
+        }
+    }
+
+    fn unexpected(&mut self, call: &'static str, expected: ValidationState) -> SerializerError {
+        let error = ValidationError::UnexpectedCall {
+            call,
+            expected,
+            found: self.state(),
+        };
+        self.violate(error, call)
+    }
+
+    fn serializer(&mut self, call: &'static str) -> SerializerResult<&'a mut dyn Serializer> {
+        match mem::take(&mut self.state) {
+            State::Serializer(serializer) => Ok(serializer),
+            state => {
+                self.state = state;
+                Err(self.unexpected(call, ValidationState::Ready))
+            }
+        }
+    }
+
+    /// Takes the state of a compound for `dyn_end`, after checking its length.
+    fn end(&mut self, expected: ValidationState) -> SerializerResult<State<'a>> {
+        if self.state() != expected {
+            return Err(self.unexpected("dyn_end", expected));
+        }
+        if let (Some(count), Some(kind)) = (self.state.count(), expected.compound())
+            && let Err(error) = count.end(kind)
+        {
+            return Err(self.violate(error, "dyn_end"));
+        }
+        Ok(mem::take(&mut self.state))
+    }
+
+    /// Updates the state after the value is produced or failed.
+    fn complete(&mut self, result: SerializerResult<()>) -> SerializerResult<()> {
+        self.take_nested();
+        self.state = if result.is_ok() {
+            State::Done
+        } else {
+            State::Failed
+        };
+        result
+    }
+
+    /// Updates the state after a nested value failed.
+    fn check(&mut self, result: SerializerResult<()>) -> SerializerResult<()> {
+        self.take_nested();
+        if result.is_err() {
+            self.state = State::Failed;
+        }
+        result
+    }
+
+    /// Records the violation of a nested value as the violation of this value.
+    fn take_nested(&mut self) {
+        if let Some(error) = self.nested.take() {
+            self.error.get_or_insert(error);
+        }
+    }
+}
+
+impl fmt::Debug for Validating<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Validating")
+            .field("state", &self.state())
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Validates a nested value with a fresh [`Validating`], and records its first violation for the
+/// parent.
+struct Validate<'a>(&'a dyn Serialize, &'a Cell<Option<ValidationError>>);
+
+impl Serialize for Validate<'_> {
+    fn dyn_serialize(&self, serializer: &mut dyn Serializer) -> SerializeResult<()> {
+        let (violation, result) = Validating::validate(serializer, self.0);
+        if let Some(violation) = violation {
+            self.1.set(Some(self.1.get().unwrap_or(violation)));
+        }
+        result
+    }
+}
+
+impl Serializer for Validating<'_> {
+    fn dyn_serialize_bool(&mut self, v: bool) -> SerializerResult<()> {
+        let result = self.serializer("dyn_serialize_bool")?.dyn_serialize_bool(v);
+        self.complete(result)
+    }
+
+    fn dyn_serialize_i8(&mut self, v: i8) -> SerializerResult<()> {
+        let result = self.serializer("dyn_serialize_i8")?.dyn_serialize_i8(v);
+        self.complete(result)
+    }
+
+    fn dyn_serialize_i16(&mut self, v: i16) -> SerializerResult<()> {
+        let result = self.serializer("dyn_serialize_i16")?.dyn_serialize_i16(v);
+        self.complete(result)
+    }
+
+    fn dyn_serialize_i32(&mut self, v: i32) -> SerializerResult<()> {
+        let result = self.serializer("dyn_serialize_i32")?.dyn_serialize_i32(v);
+        self.complete(result)
+    }
+
+    fn dyn_serialize_i64(&mut self, v: i64) -> SerializerResult<()> {
+        let result = self.serializer("dyn_serialize_i64")?.dyn_serialize_i64(v);
+        self.complete(result)
+    }
+
+    fn dyn_serialize_i128(&mut self, v: i128) -> SerializerResult<()> {
+        let result = self.serializer("dyn_serialize_i128")?.dyn_serialize_i128(v);
+        self.complete(result)
+    }
+
+    fn dyn_serialize_u8(&mut self, v: u8) -> SerializerResult<()> {
+        let result = self.serializer("dyn_serialize_u8")?.dyn_serialize_u8(v);
+        self.complete(result)
+    }
+
+    fn dyn_serialize_u16(&mut self, v: u16) -> SerializerResult<()> {
+        let result = self.serializer("dyn_serialize_u16")?.dyn_serialize_u16(v);
+        self.complete(result)
+    }
+
+    fn dyn_serialize_u32(&mut self, v: u32) -> SerializerResult<()> {
+        let result = self.serializer("dyn_serialize_u32")?.dyn_serialize_u32(v);
+        self.complete(result)
+    }
+
+    fn dyn_serialize_u64(&mut self, v: u64) -> SerializerResult<()> {
+        let result = self.serializer("dyn_serialize_u64")?.dyn_serialize_u64(v);
+        self.complete(result)
+    }
+
+    fn dyn_serialize_u128(&mut self, v: u128) -> SerializerResult<()> {
+        let result = self.serializer("dyn_serialize_u128")?.dyn_serialize_u128(v);
+        self.complete(result)
+    }
+
+    fn dyn_serialize_f32(&mut self, v: f32) -> SerializerResult<()> {
+        let result = self.serializer("dyn_serialize_f32")?.dyn_serialize_f32(v);
+        self.complete(result)
+    }
+
+    fn dyn_serialize_f64(&mut self, v: f64) -> SerializerResult<()> {
+        let result = self.serializer("dyn_serialize_f64")?.dyn_serialize_f64(v);
+        self.complete(result)
+    }
+
+    fn dyn_serialize_char(&mut self, v: char) -> SerializerResult<()> {
+        let result = self.serializer("dyn_serialize_char")?.dyn_serialize_char(v);
+        self.complete(result)
+    }
+
+    fn dyn_serialize_str(&mut self, v: &str) -> SerializerResult<()> {
+        let result = self.serializer("dyn_serialize_str")?.dyn_serialize_str(v);
+        self.complete(result)
+    }
+
+    fn dyn_serialize_bytes(&mut self, v: &[u8]) -> SerializerResult<()> {
+        let result = self
+            .serializer("dyn_serialize_bytes")?
+            .dyn_serialize_bytes(v);
+        self.complete(result)
+    }
+
+    fn dyn_serialize_none(&mut self) -> SerializerResult<()> {
+        let result = self.serializer("dyn_serialize_none")?.dyn_serialize_none();
+        self.complete(result)
+    }
+
+    fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        let result = self
+            .serializer("dyn_serialize_some")?
+            .dyn_serialize_some(&Validate(value, &self.nested));
+        self.complete(result)
+    }
+
+    fn dyn_serialize_unit(&mut self) -> SerializerResult<()> {
+        let result = self.serializer("dyn_serialize_unit")?.dyn_serialize_unit();
+        self.complete(result)
+    }
+
+    fn dyn_serialize_unit_struct(&mut self, name: &'static str) -> SerializerResult<()> {
+        let result = self
+            .serializer("dyn_serialize_unit_struct")?
+            .dyn_serialize_unit_struct(name);
+        self.complete(result)
+    }
+
+    fn dyn_serialize_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> SerializerResult<()> {
+        let result = self
+            .serializer("dyn_serialize_unit_variant")?
+            .dyn_serialize_unit_variant(name, variant_index, variant);
+        self.complete(result)
+    }
+
+    fn dyn_serialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        let result = self
+            .serializer("dyn_serialize_newtype_struct")?
+            .dyn_serialize_newtype_struct(name, &Validate(value, &self.nested));
+        self.complete(result)
+    }
+
+    fn dyn_serialize_newtype_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        let result = self
+            .serializer("dyn_serialize_newtype_variant")?
+            .dyn_serialize_newtype_variant(
+                name,
+                variant_index,
+                variant,
+                &Validate(value, &self.nested),
+            );
+        self.complete(result)
+    }
+
+    fn dyn_collect_str(&mut self, value: &dyn fmt::Display) -> SerializerResult<()> {
+        let result = self.serializer("dyn_collect_str")?.dyn_collect_str(value);
+        self.complete(result)
+    }
+
+    fn dyn_serialize_seq(&mut self, len: Option<usize>) -> SerializerResult<&mut dyn SerializeSeq> {
+        // The state is left as failed if the wrapped serializer fails.
+        let serializer = self
+            .serializer("dyn_serialize_seq")?
+            .dyn_serialize_seq(len)?;
+        self.state = State::SerializeSeq(serializer, Count::new(len));
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple(&mut self, len: usize) -> SerializerResult<&mut dyn SerializeTuple> {
+        let serializer = self
+            .serializer("dyn_serialize_tuple")?
+            .dyn_serialize_tuple(len)?;
+        self.state = State::SerializeTuple(serializer, Count::new(Some(len)));
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeTupleStruct> {
+        let serializer = self
+            .serializer("dyn_serialize_tuple_struct")?
+            .dyn_serialize_tuple_struct(name, len)?;
+        self.state = State::SerializeTupleStruct(serializer, Count::new(Some(len)));
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeTupleVariant> {
+        let serializer = self
+            .serializer("dyn_serialize_tuple_variant")?
+            .dyn_serialize_tuple_variant(name, variant_index, variant, len)?;
+        self.state = State::SerializeTupleVariant(serializer, Count::new(Some(len)));
+        Ok(self)
+    }
+
+    fn dyn_serialize_map(&mut self, len: Option<usize>) -> SerializerResult<&mut dyn SerializeMap> {
+        let serializer = self
+            .serializer("dyn_serialize_map")?
+            .dyn_serialize_map(len)?;
+        self.state = State::SerializeMap(serializer, Count::new(len), false);
+        Ok(self)
+    }
+
+    fn dyn_serialize_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeStruct> {
+        let serializer = self
+            .serializer("dyn_serialize_struct")?
+            .dyn_serialize_struct(name, len)?;
+        self.state = State::SerializeStruct(serializer, Count::new(Some(len)), Vec::new());
+        Ok(self)
+    }
+
+    fn dyn_serialize_struct_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> SerializerResult<&mut dyn SerializeStructVariant> {
+        let serializer = self
+            .serializer("dyn_serialize_struct_variant")?
+            .dyn_serialize_struct_variant(name, variant_index, variant, len)?;
+        self.state = State::SerializeStructVariant(serializer, Count::new(Some(len)), Vec::new());
+        Ok(self)
+    }
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.is_human_readable
+    }
+}
+
+impl SerializeSeq for Validating<'_> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        let State::SerializeSeq(serializer, count) = &mut self.state else {
+            return Err(self.unexpected("dyn_serialize_element", ValidationState::Seq));
+        };
+        if let Err(error) = count.next(CompoundKind::Seq) {
+            return Err(self.violate(error, "dyn_serialize_element"));
+        }
+        let result = serializer.dyn_serialize_element(&Validate(value, &self.nested));
+        self.check(result)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        let State::SerializeSeq(serializer, _) = self.end(ValidationState::Seq)? else {
+            unreachable!("the state is checked by `Validating::end`");
+        };
+        let result = serializer.dyn_end();
+        self.complete(result)
+    }
+}
+
+impl SerializeTuple for Validating<'_> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        let State::SerializeTuple(serializer, count) = &mut self.state else {
+            return Err(self.unexpected("dyn_serialize_element", ValidationState::Tuple));
+        };
+        if let Err(error) = count.next(CompoundKind::Tuple) {
+            return Err(self.violate(error, "dyn_serialize_element"));
+        }
+        let result = serializer.dyn_serialize_element(&Validate(value, &self.nested));
+        self.check(result)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        let State::SerializeTuple(serializer, _) = self.end(ValidationState::Tuple)? else {
+            unreachable!("the state is checked by `Validating::end`");
+        };
+        let result = serializer.dyn_end();
+        self.complete(result)
+    }
+}
+
+impl SerializeTupleStruct for Validating<'_> {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        let State::SerializeTupleStruct(serializer, count) = &mut self.state else {
+            return Err(self.unexpected("dyn_serialize_field", ValidationState::TupleStruct));
+        };
+        if let Err(error) = count.next(CompoundKind::TupleStruct) {
+            return Err(self.violate(error, "dyn_serialize_field"));
+        }
+        let result = serializer.dyn_serialize_field(&Validate(value, &self.nested));
+        self.check(result)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        let State::SerializeTupleStruct(serializer, _) = self.end(ValidationState::TupleStruct)?
+        else {
+            unreachable!("the state is checked by `Validating::end`");
+        };
+        let result = serializer.dyn_end();
+        self.complete(result)
+    }
+}
+
+impl SerializeTupleVariant for Validating<'_> {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        let State::SerializeTupleVariant(serializer, count) = &mut self.state else {
+            return Err(self.unexpected("dyn_serialize_field", ValidationState::TupleVariant));
+        };
+        if let Err(error) = count.next(CompoundKind::TupleVariant) {
+            return Err(self.violate(error, "dyn_serialize_field"));
+        }
+        let result = serializer.dyn_serialize_field(&Validate(value, &self.nested));
+        self.check(result)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        let State::SerializeTupleVariant(serializer, _) =
+            self.end(ValidationState::TupleVariant)?
+        else {
+            unreachable!("the state is checked by `Validating::end`");
+        };
+        let result = serializer.dyn_end();
+        self.complete(result)
+    }
+}
+
+impl SerializeMap for Validating<'_> {
+    fn dyn_serialize_key(&mut self, key: &dyn Serialize) -> SerializerResult<()> {
+        let State::SerializeMap(serializer, count, pending @ false) = &mut self.state else {
+            return Err(self.unexpected("dyn_serialize_key", ValidationState::MapKey));
+        };
+        if let Err(error) = count.next(CompoundKind::Map) {
+            return Err(self.violate(error, "dyn_serialize_key"));
+        }
+        *pending = true;
+        let result = serializer.dyn_serialize_key(&Validate(key, &self.nested));
+        self.check(result)
+    }
+
+    fn dyn_serialize_value(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+        let State::SerializeMap(serializer, _, pending @ true) = &mut self.state else {
+            return Err(self.unexpected("dyn_serialize_value", ValidationState::MapValue));
+        };
+        *pending = false;
+        let result = serializer.dyn_serialize_value(&Validate(value, &self.nested));
+        self.check(result)
+    }
+
+    fn dyn_serialize_entry(
+        &mut self,
+        key: &dyn Serialize,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        let State::SerializeMap(serializer, count, false) = &mut self.state else {
+            return Err(self.unexpected("dyn_serialize_entry", ValidationState::MapKey));
+        };
+        if let Err(error) = count.next(CompoundKind::Map) {
+            return Err(self.violate(error, "dyn_serialize_entry"));
+        }
+        let result = serializer
+            .dyn_serialize_entry(&Validate(key, &self.nested), &Validate(value, &self.nested));
+        self.check(result)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        let State::SerializeMap(serializer, ..) = self.end(ValidationState::MapKey)? else {
+            unreachable!("the state is checked by `Validating::end`");
+        };
+        let result = serializer.dyn_end();
+        self.complete(result)
+    }
+}
+
+impl SerializeStruct for Validating<'_> {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        let State::SerializeStruct(serializer, count, fields) = &mut self.state else {
+            return Err(self.unexpected("dyn_serialize_field", ValidationState::Struct));
+        };
+        if let Err(error) = add_field(fields, key).and_then(|()| count.next(CompoundKind::Struct)) {
+            return Err(self.violate(error, "dyn_serialize_field"));
+        }
+        let result = serializer.dyn_serialize_field(key, &Validate(value, &self.nested));
+        self.check(result)
+    }
+
+    fn dyn_skip_field(&mut self, key: &'static str) -> SerializerResult<()> {
+        let State::SerializeStruct(serializer, _, fields) = &mut self.state else {
+            return Err(self.unexpected("dyn_skip_field", ValidationState::Struct));
+        };
+        if let Err(error) = add_field(fields, key) {
+            return Err(self.violate(error, "dyn_skip_field"));
+        }
+        let result = serializer.dyn_skip_field(key);
+        self.check(result)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        let State::SerializeStruct(serializer, ..) = self.end(ValidationState::Struct)? else {
+            unreachable!("the state is checked by `Validating::end`");
+        };
+        let result = serializer.dyn_end();
+        self.complete(result)
+    }
+}
+
+impl SerializeStructVariant for Validating<'_> {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> SerializerResult<()> {
+        let State::SerializeStructVariant(serializer, count, fields) = &mut self.state else {
+            return Err(self.unexpected("dyn_serialize_field", ValidationState::StructVariant));
+        };
+        if let Err(error) =
+            add_field(fields, key).and_then(|()| count.next(CompoundKind::StructVariant))
+        {
+            return Err(self.violate(error, "dyn_serialize_field"));
+        }
+        let result = serializer.dyn_serialize_field(key, &Validate(value, &self.nested));
+        self.check(result)
+    }
+
+    fn dyn_skip_field(&mut self, key: &'static str) -> SerializerResult<()> {
+        let State::SerializeStructVariant(serializer, _, fields) = &mut self.state else {
+            return Err(self.unexpected("dyn_skip_field", ValidationState::StructVariant));
+        };
+        if let Err(error) = add_field(fields, key) {
+            return Err(self.violate(error, "dyn_skip_field"));
+        }
+        let result = serializer.dyn_skip_field(key);
+        self.check(result)
+    }
+
+    fn dyn_end(&mut self) -> SerializerResult<()> {
+        let State::SerializeStructVariant(serializer, ..) =
+            self.end(ValidationState::StructVariant)?
+        else {
+            unreachable!("the state is checked by `Validating::end`");
+        };
+        let result = serializer.dyn_end();
+        self.complete(result)
+    }
+}
+
+/// Records a field of a struct, or a struct variant, and rejects the duplicates.
+fn add_field(fields: &mut Vec<&'static str>, key: &'static str) -> Result<(), ValidationError> {
+    if fields.contains(&key) {
+        return Err(ValidationError::DuplicateField { field: key });
+    }
+    fields.push(key);
+    Ok(())
+}
+
+/// The state of [`Validating`], which is named in a [`ValidationError`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ValidationState {
+    /// The serializer is ready for a value.
+    Ready,
+    /// The serializer has produced a value.
+    Done,
+    /// The serializer has failed.
+    Failed,
+    /// A sequence is being serialized.
+    Seq,
+    /// A tuple is being serialized.
+    Tuple,
+    /// A tuple struct is being serialized.
+    TupleStruct,
+    /// A tuple variant is being serialized.
+    TupleVariant,
+    /// A map is being serialized, and expects a key or `dyn_end`.
+    MapKey,
+    /// A map is being serialized, and expects the value of a key.
+    MapValue,
+    /// A struct is being serialized.
+    Struct,
+    /// A struct variant is being serialized.
+    StructVariant,
+}
+
+impl ValidationState {
+    /// Returns the kind of the compound being serialized, if any.
+    fn compound(self) -> Option<CompoundKind> {
+        match self {
+            ValidationState::Ready | ValidationState::Done | ValidationState::Failed => None,
+            ValidationState::Seq => Some(CompoundKind::Seq),
+            ValidationState::Tuple => Some(CompoundKind::Tuple),
+            ValidationState::TupleStruct => Some(CompoundKind::TupleStruct),
+            ValidationState::TupleVariant => Some(CompoundKind::TupleVariant),
+            ValidationState::MapKey | ValidationState::MapValue => Some(CompoundKind::Map),
+            ValidationState::Struct => Some(CompoundKind::Struct),
+            ValidationState::StructVariant => Some(CompoundKind::StructVariant),
+        }
+    }
+
+    /// Returns the state reported by [`SerializerError::WrongState`].
+    fn serializer_state(self) -> SerializerState {
+        match self {
+            ValidationState::Ready => SerializerState::Serializer,
+            ValidationState::Done => SerializerState::Ok,
+            ValidationState::Failed => SerializerState::Error,
+            ValidationState::Seq => SerializerState::SerializeSeq,
+            ValidationState::Tuple => SerializerState::SerializeTuple,
+            ValidationState::TupleStruct => SerializerState::SerializeTupleStruct,
+            ValidationState::TupleVariant => SerializerState::SerializeTupleVariant,
+            ValidationState::MapKey => SerializerState::SerializeMap,
+            ValidationState::MapValue => SerializerState::SerializeMapValue,
+            ValidationState::Struct => SerializerState::SerializeStruct,
+            ValidationState::StructVariant => SerializerState::SerializeStructVariant,
+        }
+    }
+}
+
+impl fmt::Display for ValidationState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ValidationState::Ready => "a serializer ready for a value",
+            ValidationState::Done => "a serializer that has produced a value",
+            ValidationState::Failed => "a serializer that has failed",
+            ValidationState::Seq => "a sequence",
+            ValidationState::Tuple => "a tuple",
+            ValidationState::TupleStruct => "a tuple struct",
+            ValidationState::TupleVariant => "a tuple variant",
+            ValidationState::MapKey => "a map expecting a key",
+            ValidationState::MapValue => "a map expecting a value",
+            ValidationState::Struct => "a struct",
+            ValidationState::StructVariant => "a struct variant",
+        })
+    }
+}
+
+/// The kind of a compound value, which is named in [`ValidationError::LengthMismatch`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CompoundKind {
+    /// A sequence.
+    Seq,
+    /// A tuple.
+    Tuple,
+    /// A tuple struct.
+    TupleStruct,
+    /// A tuple variant.
+    TupleVariant,
+    /// A map.
+    Map,
+    /// A struct.
+    Struct,
+    /// A struct variant.
+    StructVariant,
+}
+
+impl fmt::Display for CompoundKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CompoundKind::Seq => "a sequence",
+            CompoundKind::Tuple => "a tuple",
+            CompoundKind::TupleStruct => "a tuple struct",
+            CompoundKind::TupleVariant => "a tuple variant",
+            CompoundKind::Map => "a map",
+            CompoundKind::Struct => "a struct",
+            CompoundKind::StructVariant => "a struct variant",
+        })
+    }
+}
+
+/// A violation of the serialization protocol, caught by [`Validating`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationError {
+    /// A method is called in a state that doesn't accept it.
+    UnexpectedCall {
+        /// The name of the method, such as `dyn_serialize_value`.
+        call: &'static str,
+        /// The state accepting the method.
+        expected: ValidationState,
+        /// The actual state.
+        found: ValidationState,
+    },
+    /// The number of items in a compound differs from the announced length.
+    LengthMismatch {
+        /// The kind of the compound.
+        kind: CompoundKind,
+        /// The announced length.
+        expected: usize,
+        /// The actual number of items.
+        found: usize,
+    },
+    /// A field of a struct, or a struct variant, is serialized or skipped twice.
+    DuplicateField {
+        /// The name of the field.
+        field: &'static str,
+    },
+    /// The value is neither produced nor failed.
+    Unfinished {
+        /// The actual state.
+        found: ValidationState,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::UnexpectedCall {
+                call,
+                expected,
+                found,
+            } => write!(f, "`{call}` is called on {found}, but requires {expected}"),
+            ValidationError::LengthMismatch {
+                kind,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected {kind} of length {expected}, found length {found}"
+            ),
+            ValidationError::DuplicateField { field } => write!(f, "duplicate field `{field}`"),
+            ValidationError::Unfinished { found } => {
+                write!(f, "the value is unfinished, found {found}")
+            }
+        }
+    }
+}
+
+impl error::Error for ValidationError {}
@@ -0,0 +1,428 @@
+//! # Conformance Tests.
+//!
+//! The dynamic traits are legal to implement by hand, and such implementations are expected to
+//! follow the same state machine as [`InplaceSerializer`](crate::ser::InplaceSerializer) and
+//! [`InplaceDeserializer`](crate::de::InplaceDeserializer): a value is produced only once, a
+//! compound is closed by `dyn_end`, a map key comes before its value, and so on.
+//!
+//! This module drives an implementation through scripted call sequences, both valid and invalid,
+//! and reports the contract points, see [`Check`], that it violates.
+//!
+//! - [`check_serializer`]: checks an implementation of [`Serializer`].
+//! - [`check_deserializer`]: checks an implementation of [`Deserializer`].
+//!
+//! Every script runs on a fresh instance, which is created by a harness closure. The harness
+//! receives the script, and must call it exactly once with the instance.
+//!
+//! # Examples
+//!
+//! ```
+//! # use serde_dyn::Serializer;
+//! # use serde_dyn::testing::{self, Check};
+//! let report = testing::check_serializer(|script| {
+//!     let mut json = serde_json::Serializer::new(Vec::new());
+//!     script(&mut <dyn Serializer>::new(&mut json));
+//! });
+//!
+//! // The reference implementation satisfies every contract point.
+//! report.assert_ok();
+//! ```
+#![cfg_attr(
+    any(feature = "std", feature = "alloc"),
+    doc = "",
+    doc = "Wrapping a hand-written serializer in [`Validating`](crate::ser::Validating) satisfies \
+           every contract point."
+)]
+
+use core::fmt;
+
+use serde::de::IgnoredAny;
+
+use crate::de::{Deserializer, InplaceVisitor};
+use crate::ser::Serializer;
+
+/// A contract point of the dynamic traits, checked by [`check_serializer`] or
+/// [`check_deserializer`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Check {
+    /// The serializer accepts valid call sequences of every kind of value.
+    ValidCalls,
+    /// The serializer fails if a nested value fails.
+    NestedErrors,
+    /// The serializer, or the deserializer, rejects a second value.
+    ReuseAfterValue,
+    /// The serializer rejects the calls on a compound after `dyn_end`.
+    CallAfterEnd,
+    /// The serializer rejects the calls on itself while a compound is not ended.
+    CallDuringCompound,
+    /// The serializer rejects `dyn_serialize_value` without a preceding key.
+    ValueBeforeKey,
+    /// The serializer rejects two keys in a row.
+    KeyTwice,
+    /// The serializer rejects `dyn_end` between a key and its value.
+    EndAfterKey,
+    /// The deserializer succeeds only if the visitor produces a value.
+    VisitValue,
+    /// The deserializer fails if the visitor fails.
+    VisitorErrors,
+}
+
+impl Check {
+    /// The contract points checked by [`check_serializer`].
+    pub const SERIALIZER: &'static [Check] = &[
+        Check::ValidCalls,
+        Check::NestedErrors,
+        Check::ReuseAfterValue,
+        Check::CallAfterEnd,
+        Check::CallDuringCompound,
+        Check::ValueBeforeKey,
+        Check::KeyTwice,
+        Check::EndAfterKey,
+    ];
+
+    /// The contract points checked by [`check_deserializer`].
+    pub const DESERIALIZER: &'static [Check] = &[
+        Check::VisitValue,
+        Check::VisitorErrors,
+        Check::ReuseAfterValue,
+    ];
+
+    /// Returns a short description of the contract point.
+    #[must_use]
+    pub fn description(self) -> &'static str {
+        match self {
+            Check::ValidCalls => "accepts valid call sequences",
+            Check::NestedErrors => "fails if a nested value fails",
+            Check::ReuseAfterValue => "rejects a second value",
+            Check::CallAfterEnd => "rejects calls on a compound after `dyn_end`",
+            Check::CallDuringCompound => "rejects calls on the serializer inside a compound",
+            Check::ValueBeforeKey => "rejects a map value without a preceding key",
+            Check::KeyTwice => "rejects two map keys in a row",
+            Check::EndAfterKey => "rejects `dyn_end` between a map key and its value",
+            Check::VisitValue => "succeeds only if the visitor produces a value",
+            Check::VisitorErrors => "fails if the visitor fails",
+        }
+    }
+
+    fn bit(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+/// The result of [`check_serializer`] or [`check_deserializer`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Report {
+    checks: &'static [Check],
+    violated: u16,
+}
+
+impl Report {
+    fn new(checks: &'static [Check]) -> Self {
+        Report {
+            checks,
+            violated: 0,
+        }
+    }
+
+    /// Returns `true` if no contract point is violated.
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.violated == 0
+    }
+
+    /// Returns `true` if `check` is checked and violated.
+    #[must_use]
+    pub fn is_violated(&self, check: Check) -> bool {
+        self.violated & check.bit() != 0
+    }
+
+    /// Returns the checked contract points.
+    #[must_use]
+    pub fn checks(&self) -> &'static [Check] {
+        self.checks
+    }
+
+    /// Returns the violated contract points.
+    pub fn violations(&self) -> impl Iterator<Item = Check> + '_ {
+        self.checks
+            .iter()
+            .copied()
+            .filter(|&check| self.is_violated(check))
+    }
+
+    /// Asserts that no contract point is violated.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the report if any contract point is violated.
+    #[track_caller]
+    pub fn assert_ok(&self) {
+        assert!(self.is_ok(), "some contract points are violated:\n{self}");
+    }
+}
+
+impl fmt::Debug for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.violations()).finish()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in self.checks {
+            let status = if self.is_violated(*check) {
+                "violated"
+            } else {
+                "ok"
+            };
+            writeln!(f, "{status:>8}: {check}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A script returning `true` if the implementation behaves as expected.
+type SerializerScript = fn(&mut dyn Serializer) -> bool;
+
+/// The scripts of [`check_serializer`].
+const SERIALIZER_SCRIPTS: &[(Check, SerializerScript)] = &[
+    (Check::ValidCalls, |ser| ser.dyn_serialize_unit().is_ok()),
+    (Check::ValidCalls, |ser| ser.dyn_serialize_some(&1).is_ok()),
+    (Check::ValidCalls, |ser| {
+        ser.dyn_serialize_newtype_variant("E", 0, "A", &1).is_ok()
+    }),
+    (Check::ValidCalls, |ser| {
+        ser.dyn_serialize_seq(Some(2)).is_ok_and(|seq| {
+            seq.dyn_serialize_element(&1).is_ok()
+                && seq.dyn_serialize_element(&2).is_ok()
+                && seq.dyn_end().is_ok()
+        })
+    }),
+    (Check::ValidCalls, |ser| {
+        ser.dyn_serialize_tuple(1)
+            .is_ok_and(|tuple| tuple.dyn_serialize_element(&1).is_ok() && tuple.dyn_end().is_ok())
+    }),
+    (Check::ValidCalls, |ser| {
+        ser.dyn_serialize_tuple_struct("T", 1)
+            .is_ok_and(|tuple| tuple.dyn_serialize_field(&1).is_ok() && tuple.dyn_end().is_ok())
+    }),
+    (Check::ValidCalls, |ser| {
+        ser.dyn_serialize_tuple_variant("E", 0, "A", 1)
+            .is_ok_and(|tuple| tuple.dyn_serialize_field(&1).is_ok() && tuple.dyn_end().is_ok())
+    }),
+    (Check::ValidCalls, |ser| {
+        ser.dyn_serialize_map(Some(2)).is_ok_and(|map| {
+            map.dyn_serialize_key(&"a").is_ok()
+                && map.dyn_serialize_value(&1).is_ok()
+                && map.dyn_serialize_entry(&"b", &2).is_ok()
+                && map.dyn_end().is_ok()
+        })
+    }),
+    (Check::ValidCalls, |ser| {
+        ser.dyn_serialize_struct("S", 1).is_ok_and(|s| {
+            s.dyn_serialize_field("a", &1).is_ok()
+                && s.dyn_skip_field("b").is_ok()
+                && s.dyn_end().is_ok()
+        })
+    }),
+    (Check::ValidCalls, |ser| {
+        ser.dyn_serialize_struct_variant("E", 0, "A", 1)
+            .is_ok_and(|s| s.dyn_serialize_field("a", &1).is_ok() && s.dyn_end().is_ok())
+    }),
+    (Check::NestedErrors, |ser| {
+        ser.dyn_serialize_some(&Fail).is_err()
+    }),
+    (Check::NestedErrors, |ser| {
+        ser.dyn_serialize_seq(Some(1))
+            .is_ok_and(|seq| seq.dyn_serialize_element(&Fail).is_err())
+    }),
+    (Check::ReuseAfterValue, |ser| {
+        ser.dyn_serialize_unit().is_ok() && ser.dyn_serialize_unit().is_err()
+    }),
+    (Check::ReuseAfterValue, |ser| {
+        ser.dyn_serialize_seq(Some(0))
+            .is_ok_and(|seq| seq.dyn_end().is_ok())
+            && ser.dyn_serialize_unit().is_err()
+    }),
+    (Check::CallAfterEnd, |ser| {
+        ser.dyn_serialize_seq(Some(0)).is_ok_and(|seq| {
+            seq.dyn_end().is_ok()
+                && seq.dyn_serialize_element(&1).is_err()
+                && seq.dyn_end().is_err()
+        })
+    }),
+    (Check::CallAfterEnd, |ser| {
+        ser.dyn_serialize_tuple(0).is_ok_and(|tuple| {
+            tuple.dyn_end().is_ok()
+                && tuple.dyn_serialize_element(&1).is_err()
+                && tuple.dyn_end().is_err()
+        })
+    }),
+    (Check::CallAfterEnd, |ser| {
+        ser.dyn_serialize_tuple_struct("T", 0).is_ok_and(|tuple| {
+            tuple.dyn_end().is_ok()
+                && tuple.dyn_serialize_field(&1).is_err()
+                && tuple.dyn_end().is_err()
+        })
+    }),
+    (Check::CallAfterEnd, |ser| {
+        ser.dyn_serialize_tuple_variant("E", 0, "A", 0)
+            .is_ok_and(|tuple| {
+                tuple.dyn_end().is_ok()
+                    && tuple.dyn_serialize_field(&1).is_err()
+                    && tuple.dyn_end().is_err()
+            })
+    }),
+    (Check::CallAfterEnd, |ser| {
+        ser.dyn_serialize_map(Some(0)).is_ok_and(|map| {
+            map.dyn_end().is_ok()
+                && map.dyn_serialize_entry(&"a", &1).is_err()
+                && map.dyn_end().is_err()
+        })
+    }),
+    (Check::CallAfterEnd, |ser| {
+        ser.dyn_serialize_struct("S", 0).is_ok_and(|s| {
+            s.dyn_end().is_ok() && s.dyn_serialize_field("a", &1).is_err() && s.dyn_end().is_err()
+        })
+    }),
+    (Check::CallAfterEnd, |ser| {
+        ser.dyn_serialize_struct_variant("E", 0, "A", 0)
+            .is_ok_and(|s| {
+                s.dyn_end().is_ok()
+                    && s.dyn_serialize_field("a", &1).is_err()
+                    && s.dyn_end().is_err()
+            })
+    }),
+    (Check::CallDuringCompound, |ser| {
+        ser.dyn_serialize_seq(Some(1)).is_ok() && ser.dyn_serialize_unit().is_err()
+    }),
+    (Check::CallDuringCompound, |ser| {
+        ser.dyn_serialize_map(Some(1)).is_ok() && ser.dyn_serialize_seq(Some(1)).is_err()
+    }),
+    (Check::ValueBeforeKey, |ser| {
+        ser.dyn_serialize_map(Some(1))
+            .is_ok_and(|map| map.dyn_serialize_value(&1).is_err())
+    }),
+    (Check::KeyTwice, |ser| {
+        ser.dyn_serialize_map(Some(2)).is_ok_and(|map| {
+            map.dyn_serialize_key(&"a").is_ok() && map.dyn_serialize_key(&"b").is_err()
+        })
+    }),
+    (Check::EndAfterKey, |ser| {
+        ser.dyn_serialize_map(Some(1))
+            .is_ok_and(|map| map.dyn_serialize_key(&"a").is_ok() && map.dyn_end().is_err())
+    }),
+];
+
+/// Checks an implementation of [`Serializer`] against the contract points in
+/// [`Check::SERIALIZER`].
+///
+/// The harness `with_serializer` is called once for every script. It must create a fresh
+/// serializer, and pass it to the script. The serializer must accept integers, strings, and map
+/// keys that are strings.
+///
+/// # Panics
+///
+/// Panics if the harness doesn't call the script, or if the serializer panics.
+pub fn check_serializer<F>(mut with_serializer: F) -> Report
+where
+    F: FnMut(&mut dyn FnMut(&mut dyn Serializer)),
+{
+    let mut report = Report::new(Check::SERIALIZER);
+    for (check, script) in SERIALIZER_SCRIPTS {
+        let mut passed = None;
+        with_serializer(&mut |ser| passed = Some(script(ser)));
+        if !passed.expect("the harness should call the script") {
+            report.violated |= check.bit();
+        }
+    }
+    report
+}
+
+/// A script returning `true` if the implementation behaves as expected.
+type DeserializerScript = fn(&mut dyn Deserializer<'_>) -> bool;
+
+/// The scripts of [`check_deserializer`].
+const DESERIALIZER_SCRIPTS: &[(Check, DeserializerScript)] = &[
+    (Check::VisitValue, |de| {
+        let mut visitor = InplaceVisitor::Visitor(IgnoredAny);
+        de.dyn_deserialize_any(&mut visitor).is_ok() && matches!(visitor, InplaceVisitor::Value(_))
+    }),
+    (Check::VisitorErrors, |de| {
+        let mut visitor = InplaceVisitor::Visitor(Reject);
+        de.dyn_deserialize_any(&mut visitor).is_err()
+    }),
+    (Check::ReuseAfterValue, |de| {
+        de.dyn_deserialize_any(&mut InplaceVisitor::Visitor(IgnoredAny))
+            .is_ok()
+            && de
+                .dyn_deserialize_any(&mut InplaceVisitor::Visitor(IgnoredAny))
+                .is_err()
+    }),
+];
+
+/// Checks an implementation of [`Deserializer`] against the contract points in
+/// [`Check::DESERIALIZER`].
+///
+/// The harness `with_deserializer` is called once for every script. It must create a fresh
+/// deserializer holding a single value, and pass it to the script. The value is read like
+/// [`IgnoredAny`] does, so an enum must be a newtype variant.
+///
+/// # Panics
+///
+/// Panics if the harness doesn't call the script, or if the deserializer panics.
+///
+/// # Examples
+///
+/// ```
+/// # use serde_dyn::Deserializer;
+/// # use serde_dyn::testing;
+/// let report = testing::check_deserializer(|script| {
+///     let mut json = serde_json::Deserializer::from_str(r#"{"a":[1,2]}"#);
+///     script(&mut <dyn Deserializer>::new(&mut json));
+/// });
+/// report.assert_ok();
+/// ```
+pub fn check_deserializer<F>(mut with_deserializer: F) -> Report
+where
+    F: FnMut(&mut dyn for<'de> FnMut(&mut dyn Deserializer<'de>)),
+{
+    let mut report = Report::new(Check::DESERIALIZER);
+    for (check, script) in DESERIALIZER_SCRIPTS {
+        let mut passed = None;
+        with_deserializer(&mut |de| passed = Some(script(de)));
+        if !passed.expect("the harness should call the script") {
+            report.violated |= check.bit();
+        }
+    }
+    report
+}
+
+/// A value that always fails to serialize.
+struct Fail;
+
+impl serde::Serialize for Fail {
+    fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom(
+            "the value is rejected by the script",
+        ))
+    }
+}
+
+/// A visitor that rejects every value.
+struct Reject;
+
+impl serde::de::Visitor<'_> for Reject {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("nothing (the value is rejected by the script)")
+    }
+}
@@ -0,0 +1,275 @@
+//! # Token Streams.
+//!
+//! This module records and replays the calls of the dynamic traits as a flat list of [`Token`]s,
+//! in the way [`serde_test`] does for static types, so that custom implementations of
+//! [`Serialize`], [`serde::Deserialize`] and [`Serializer`](crate::ser::Serializer) can be tested
+//! without a real data format.
+//!
+//! - [`Recorder`]: a [`Serializer`](crate::ser::Serializer) that records the tokens.
+//! - [`Tokens`]: a [`Deserializer`] that replays the tokens.
+//! - [`assert_tokens`], [`assert_ser_tokens`] and [`assert_de_tokens`] compare a value with the
+//!   tokens, while [`assert_ser_tokens_error`] and [`assert_de_tokens_error`] check the failures.
+//!
+//! [`serde_test`]: https://docs.rs/serde_test
+//!
+//! # Examples
+//!
+//! ```
+//! # use serde_dyn::token::{self, Token};
+//! #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+//! struct Point {
+//!     x: i32,
+//!     y: i32,
+//! }
+//!
+//! token::assert_tokens(
+//!     &Point { x: 1, y: 2 },
+//!     &[
+//!         Token::Struct { name: "Point", len: 2 },
+//!         Token::Field("x"),
+//!         Token::I32(1),
+//!         Token::Field("y"),
+//!         Token::I32(2),
+//!         Token::StructEnd,
+//!     ],
+//! );
+//! ```
+
+use core::fmt;
+
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::string::{String, ToString};
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::vec::Vec;
+
+use crate::de::{Deserializer, Tokens};
+use crate::ser::{Recorder, Serialize};
+
+/// A single call of the dynamic traits, recorded by [`Recorder`] and replayed by [`Tokens`].
+///
+/// Compound values are flattened: the token that begins a sequence, a map, a struct, etc. is
+/// followed by the tokens of the nested values, then the matching end token.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+    /// Serialized by `dyn_serialize_bool`.
+    Bool(bool),
+    /// Serialized by `dyn_serialize_i8`.
+    I8(i8),
+    /// Serialized by `dyn_serialize_i16`.
+    I16(i16),
+    /// Serialized by `dyn_serialize_i32`.
+    I32(i32),
+    /// Serialized by `dyn_serialize_i64`.
+    I64(i64),
+    /// Serialized by `dyn_serialize_i128`.
+    I128(i128),
+    /// Serialized by `dyn_serialize_u8`.
+    U8(u8),
+    /// Serialized by `dyn_serialize_u16`.
+    U16(u16),
+    /// Serialized by `dyn_serialize_u32`.
+    U32(u32),
+    /// Serialized by `dyn_serialize_u64`.
+    U64(u64),
+    /// Serialized by `dyn_serialize_u128`.
+    U128(u128),
+    /// Serialized by `dyn_serialize_f32`.
+    F32(f32),
+    /// Serialized by `dyn_serialize_f64`.
+    F64(f64),
+    /// Serialized by `dyn_serialize_char`.
+    Char(char),
+    /// Serialized by `dyn_serialize_str` or `dyn_collect_str`.
+    Str(String),
+    /// Serialized by `dyn_serialize_bytes`.
+    Bytes(Vec<u8>),
+    /// Serialized by `dyn_serialize_none`.
+    None,
+    /// Serialized by `dyn_serialize_some`, followed by the wrapped value.
+    Some,
+    /// Serialized by `dyn_serialize_unit`.
+    Unit,
+    /// Serialized by `dyn_serialize_unit_struct`.
+    UnitStruct(&'static str),
+    /// Serialized by `dyn_serialize_unit_variant`.
+    UnitVariant {
+        /// The name of the enum.
+        name: &'static str,
+        /// The index of the variant within the enum.
+        variant_index: u32,
+        /// The name of the variant.
+        variant: &'static str,
+    },
+    /// Serialized by `dyn_serialize_newtype_struct`, followed by the wrapped value.
+    NewtypeStruct {
+        /// The name of the struct.
+        name: &'static str,
+    },
+    /// Serialized by `dyn_serialize_newtype_variant`, followed by the wrapped value.
+    NewtypeVariant {
+        /// The name of the enum.
+        name: &'static str,
+        /// The index of the variant within the enum.
+        variant_index: u32,
+        /// The name of the variant.
+        variant: &'static str,
+    },
+    /// Serialized by `dyn_serialize_seq` or `dyn_collect_seq`, followed by the elements and
+    /// [`Token::SeqEnd`].
+    Seq {
+        /// The length of the sequence, if known.
+        len: Option<usize>,
+    },
+    /// Ends a [`Token::Seq`].
+    SeqEnd,
+    /// Serialized by `dyn_serialize_tuple`, followed by the elements and [`Token::TupleEnd`].
+    Tuple {
+        /// The length of the tuple.
+        len: usize,
+    },
+    /// Ends a [`Token::Tuple`].
+    TupleEnd,
+    /// Serialized by `dyn_serialize_tuple_struct`, followed by the fields and
+    /// [`Token::TupleStructEnd`].
+    TupleStruct {
+        /// The name of the struct.
+        name: &'static str,
+        /// The number of the fields.
+        len: usize,
+    },
+    /// Ends a [`Token::TupleStruct`].
+    TupleStructEnd,
+    /// Serialized by `dyn_serialize_tuple_variant`, followed by the fields and
+    /// [`Token::TupleVariantEnd`].
+    TupleVariant {
+        /// The name of the enum.
+        name: &'static str,
+        /// The index of the variant within the enum.
+        variant_index: u32,
+        /// The name of the variant.
+        variant: &'static str,
+        /// The number of the fields.
+        len: usize,
+    },
+    /// Ends a [`Token::TupleVariant`].
+    TupleVariantEnd,
+    /// Serialized by `dyn_serialize_map` or `dyn_collect_map`, followed by the keys and values
+    /// and [`Token::MapEnd`].
+    Map {
+        /// The number of the entries, if known.
+        len: Option<usize>,
+    },
+    /// Ends a [`Token::Map`].
+    MapEnd,
+    /// Serialized by `dyn_serialize_struct`, followed by the fields and [`Token::StructEnd`].
+    Struct {
+        /// The name of the struct.
+        name: &'static str,
+        /// The number of the fields.
+        len: usize,
+    },
+    /// Ends a [`Token::Struct`].
+    StructEnd,
+    /// Serialized by `dyn_serialize_struct_variant`, followed by the fields and
+    /// [`Token::StructVariantEnd`].
+    StructVariant {
+        /// The name of the enum.
+        name: &'static str,
+        /// The index of the variant within the enum.
+        variant_index: u32,
+        /// The name of the variant.
+        variant: &'static str,
+        /// The number of the fields.
+        len: usize,
+    },
+    /// Ends a [`Token::StructVariant`].
+    StructVariantEnd,
+    /// The key of a field of a struct or a struct variant, followed by the value.
+    Field(&'static str),
+    /// A field of a struct or a struct variant that is skipped.
+    SkippedField(&'static str),
+}
+
+/// Asserts that `value` serializes into `tokens`, and that `tokens` deserializes into `value`.
+///
+/// # Panics
+///
+/// Panics if either [`assert_ser_tokens`] or [`assert_de_tokens`] panics.
+#[track_caller]
+pub fn assert_tokens<'de, T>(value: &T, tokens: &'de [Token])
+where
+    T: Serialize + serde::Deserialize<'de> + PartialEq + fmt::Debug,
+{
+    assert_ser_tokens(value, tokens);
+    assert_de_tokens(value, tokens);
+}
+
+/// Asserts that `value` serializes into `tokens`.
+///
+/// # Panics
+///
+/// Panics if the serialization fails, or the recorded tokens differ from `tokens`.
+#[track_caller]
+pub fn assert_ser_tokens<T: ?Sized + Serialize>(value: &T, tokens: &[Token]) {
+    let mut recorder = Recorder::new();
+    if let Err(error) = value.dyn_serialize(&mut recorder) {
+        let error = recorder.error().unwrap_or(&error);
+        panic!("value failed to serialize: {error}");
+    }
+    assert_eq!(recorder.tokens(), tokens);
+}
+
+/// Asserts that `value` fails to serialize with the message `error`.
+///
+/// # Panics
+///
+/// Panics if the serialization succeeds, or fails with another message.
+#[track_caller]
+pub fn assert_ser_tokens_error<T: ?Sized + Serialize>(value: &T, error: &str) {
+    let mut recorder = Recorder::new();
+    match value.dyn_serialize(&mut recorder) {
+        Ok(()) => panic!("value serialized successfully into {:?}", recorder.tokens()),
+        Err(e) => assert_eq!(recorder.error().unwrap_or(&e).to_string(), error),
+    }
+}
+
+/// Asserts that `tokens` deserializes into `value`, and that every token is consumed.
+///
+/// # Panics
+///
+/// Panics if the deserialization fails, the deserialized value differs from `value`, or some
+/// tokens remain.
+#[track_caller]
+pub fn assert_de_tokens<'de, T>(value: &T, tokens: &'de [Token])
+where
+    T: serde::Deserialize<'de> + PartialEq + fmt::Debug,
+{
+    let mut deserializer = Tokens::new(tokens);
+    let result = T::deserialize(&mut deserializer as &mut dyn Deserializer<'de>);
+    match (result.as_ref(), deserializer.error()) {
+        (Ok(v), None) => assert_eq!(v, value),
+        (_, Some(error)) | (Err(error), None) => {
+            panic!("tokens failed to deserialize: {error}")
+        }
+    }
+    let remaining = deserializer.remaining();
+    assert!(remaining.is_empty(), "remaining tokens: {remaining:?}");
+}
+
+/// Asserts that `tokens` fails to deserialize into `T` with the message `error`.
+///
+/// # Panics
+///
+/// Panics if the deserialization succeeds, or fails with another message.
+#[track_caller]
+pub fn assert_de_tokens_error<'de, T>(tokens: &'de [Token], error: &str)
+where
+    T: serde::Deserialize<'de> + fmt::Debug,
+{
+    let mut deserializer = Tokens::new(tokens);
+    let result = T::deserialize(&mut deserializer as &mut dyn Deserializer<'de>);
+    match (result.as_ref(), deserializer.error()) {
+        (Ok(value), None) => panic!("tokens deserialized successfully into {value:?}"),
+        (_, Some(e)) | (Err(e), None) => assert_eq!(e.to_string(), error),
+    }
+}
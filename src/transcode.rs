@@ -0,0 +1,335 @@
+//! # Dynamic Transcoding.
+//!
+//! This module streams any self-describing input from a dynamic [`Deserializer`] into a dynamic
+//! [`Serializer`] without building an intermediate tree.
+
+use core::cell::Cell;
+use core::fmt;
+
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::string::String;
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::vec::Vec;
+
+use serde::de::Error as _;
+
+use crate::de::{
+    DeserializeError, DeserializeResult, DeserializeSeed, Deserializer, DeserializerError,
+    EnumAccess, MapAccess, SeqAccess, Visitor,
+};
+use crate::ser::{
+    SerializeError, SerializeMap, SerializeResult, SerializeSeq, Serializer, SerializerError,
+    SerializerResult, SerializerState,
+};
+
+/// Transcode the value from the dynamic [`Deserializer`] into the dynamic [`Serializer`].
+///
+/// The input is driven by `dyn_deserialize_any`, so the deserializer must be self-describing.
+/// Every value visited is forwarded to the serializer immediately, and sequences and maps are
+/// streamed element by element.
+///
+/// Enums are not supported, since the kind of a variant is only known to the type that visits it.
+/// Deserializers that call `visit_enum` from `dyn_deserialize_any`, such as `value::Value`, fail
+/// with an invalid type error.
+///
+/// When the serializer fails, the returned error keeps its [`SerializerError`] as the source with
+/// `std` or `alloc`, so that it can be told apart from a failure of the deserializer. The concrete
+/// errors stay in the [`InplaceDeserializer`] and the [`InplaceSerializer`].
+///
+/// [`InplaceDeserializer`]: crate::de::InplaceDeserializer
+/// [`InplaceSerializer`]: crate::ser::InplaceSerializer
+///
+/// # Examples
+///
+/// ```
+/// # use serde_dyn::{Deserializer, Serializer};
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{"a":[1,2.5,null]}"#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+///
+/// let mut cbor = Vec::new();
+/// let writer = serde_cbor::ser::IoWrite::new(std::io::Cursor::new(&mut cbor));
+/// let mut serializer = serde_cbor::Serializer::new(writer);
+/// let mut serializer = <dyn Serializer>::new(&mut serializer);
+///
+/// serde_dyn::transcode(&mut deserializer, &mut serializer).unwrap();
+/// assert_eq!(cbor, b"\xbf\x61a\x9f\x01\xf9\x41\x00\xf6\xff\xff");
+/// ```
+pub fn transcode<'de>(
+    deserializer: &mut dyn Deserializer<'de>,
+    serializer: &mut dyn Serializer,
+) -> DeserializeResult<()> {
+    let failed = Failed(Cell::new(None));
+    let mut transcoder = Transcoder {
+        serializer,
+        failed: &failed,
+    };
+    deserializer
+        .dyn_deserialize_any(&mut transcoder)
+        .map_err(|error| failed.into_error(error))
+}
+
+/// The side that failed first, which is the innermost failure.
+enum Failure {
+    Input(DeserializerError),
+    Output(SerializerError),
+}
+
+/// Remembers the first failure across the nested transcoders.
+///
+/// The errors cross the concrete formats on their way out, which only keep the message.
+struct Failed(Cell<Option<Failure>>);
+
+impl Failed {
+    fn fail(&self, failure: Failure) {
+        let first = self.0.take().unwrap_or(failure);
+        self.0.set(Some(first));
+    }
+
+    /// Remembers the failure of the serializer.
+    fn output<T>(&self, result: SerializerResult<T>) -> DeserializeResult<T> {
+        result.map_err(|error| {
+            self.fail(Failure::Output(error));
+            DeserializeError::custom(error)
+        })
+    }
+
+    #[cold]
+    fn into_error(self, error: DeserializerError) -> DeserializeError {
+        match self.0.into_inner() {
+            None => DeserializeError::from(error),
+            Some(Failure::Input(error)) => DeserializeError::from(error),
+            #[cfg(any(feature = "std", feature = "alloc"))]
+            Some(Failure::Output(error)) => DeserializeError::from_source(error),
+            #[cfg(not(any(feature = "std", feature = "alloc")))]
+            Some(Failure::Output(error)) => DeserializeError::custom(error),
+        }
+    }
+}
+
+/// Implements [`Visitor`] by forwarding every visited value to the serializer.
+struct Transcoder<'a> {
+    serializer: &'a mut dyn Serializer,
+    failed: &'a Failed,
+}
+
+/// Implements [`Serialize`](crate::ser::Serialize) by transcoding the one-shot deserializer.
+struct Transcode<'a, 'de>(Cell<Option<&'a mut dyn Deserializer<'de>>>, &'a Failed);
+
+impl<'a, 'de> Transcode<'a, 'de> {
+    fn new(deserializer: &'a mut dyn Deserializer<'de>, failed: &'a Failed) -> Self {
+        Transcode(Cell::new(Some(deserializer)), failed)
+    }
+}
+
+impl crate::ser::Serialize for Transcode<'_, '_> {
+    fn dyn_serialize(&self, serializer: &mut dyn Serializer) -> SerializeResult<()> {
+        match self.0.take() {
+            Some(deserializer) => deserializer
+                .dyn_deserialize_any(&mut Transcoder {
+                    serializer,
+                    failed: self.1,
+                })
+                .map_err(|error| {
+                    self.1.fail(Failure::Input(error));
+                    serde::ser::Error::custom(error)
+                }),
+            // The deserializer has been transcoded already.
+            None => Err(SerializeError::from(SerializerError::WrongState {
+                operation: "Serialize::dyn_serialize",
+                found: SerializerState::Ok,
+            })),
+        }
+    }
+}
+
+/// Serializes each element of the sequence.
+struct SeqSeed<'a>(&'a mut dyn SerializeSeq, &'a Failed);
+
+impl<'de> DeserializeSeed<'de> for SeqSeed<'_> {
+    fn dyn_deserialize(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        self.1.output(
+            self.0
+                .dyn_serialize_element(&Transcode::new(deserializer, self.1)),
+        )
+    }
+}
+
+/// Serializes each key of the map.
+struct KeySeed<'a>(&'a mut dyn SerializeMap, &'a Failed);
+
+impl<'de> DeserializeSeed<'de> for KeySeed<'_> {
+    fn dyn_deserialize(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        self.1.output(
+            self.0
+                .dyn_serialize_key(&Transcode::new(deserializer, self.1)),
+        )
+    }
+}
+
+/// Serializes each value of the map.
+struct ValueSeed<'a>(&'a mut dyn SerializeMap, &'a Failed);
+
+impl<'de> DeserializeSeed<'de> for ValueSeed<'_> {
+    fn dyn_deserialize(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        self.1.output(
+            self.0
+                .dyn_serialize_value(&Transcode::new(deserializer, self.1)),
+        )
+    }
+}
+
+impl<'de> Visitor<'de> for Transcoder<'_> {
+    fn dyn_expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    fn dyn_visit_bool(&mut self, v: bool) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_bool(v))
+    }
+
+    fn dyn_visit_i8(&mut self, v: i8) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_i8(v))
+    }
+
+    fn dyn_visit_i16(&mut self, v: i16) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_i16(v))
+    }
+
+    fn dyn_visit_i32(&mut self, v: i32) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_i32(v))
+    }
+
+    fn dyn_visit_i64(&mut self, v: i64) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_i64(v))
+    }
+
+    fn dyn_visit_i128(&mut self, v: i128) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_i128(v))
+    }
+
+    fn dyn_visit_u8(&mut self, v: u8) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_u8(v))
+    }
+
+    fn dyn_visit_u16(&mut self, v: u16) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_u16(v))
+    }
+
+    fn dyn_visit_u32(&mut self, v: u32) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_u32(v))
+    }
+
+    fn dyn_visit_u64(&mut self, v: u64) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_u64(v))
+    }
+
+    fn dyn_visit_u128(&mut self, v: u128) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_u128(v))
+    }
+
+    fn dyn_visit_f32(&mut self, v: f32) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_f32(v))
+    }
+
+    fn dyn_visit_f64(&mut self, v: f64) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_f64(v))
+    }
+
+    fn dyn_visit_char(&mut self, v: char) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_char(v))
+    }
+
+    fn dyn_visit_str(&mut self, v: &str) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_str(v))
+    }
+
+    fn dyn_visit_borrowed_str(&mut self, v: &'de str) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_str(v))
+    }
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn dyn_visit_string(&mut self, v: String) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_str(&v))
+    }
+
+    fn dyn_visit_bytes(&mut self, v: &[u8]) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_bytes(v))
+    }
+
+    fn dyn_visit_borrowed_bytes(&mut self, v: &'de [u8]) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_bytes(v))
+    }
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn dyn_visit_byte_buf(&mut self, v: Vec<u8>) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_bytes(&v))
+    }
+
+    fn dyn_visit_none(&mut self) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_none())
+    }
+
+    fn dyn_visit_some(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        let value = Transcode::new(deserializer, self.failed);
+        self.failed
+            .output(self.serializer.dyn_serialize_some(&value))
+    }
+
+    fn dyn_visit_unit(&mut self) -> DeserializeResult<()> {
+        self.failed.output(self.serializer.dyn_serialize_unit())
+    }
+
+    fn dyn_visit_newtype_struct(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        // The name of the newtype struct is unknown, so it's transcoded transparently.
+        deserializer
+            .dyn_deserialize_any(self)
+            .map_err(DeserializeError::from)
+    }
+
+    fn dyn_visit_seq(&mut self, seq: &mut dyn SeqAccess<'de>) -> DeserializeResult<()> {
+        let serializer = self
+            .failed
+            .output(self.serializer.dyn_serialize_seq(seq.dyn_size_hint()))?;
+        while seq
+            .dyn_next_element(&mut SeqSeed(serializer, self.failed))?
+            .is_some()
+        {}
+        self.failed.output(serializer.dyn_end())
+    }
+
+    fn dyn_visit_map(&mut self, map: &mut dyn MapAccess<'de>) -> DeserializeResult<()> {
+        let serializer = self
+            .failed
+            .output(self.serializer.dyn_serialize_map(map.dyn_size_hint()))?;
+        while map
+            .dyn_next_key(&mut KeySeed(serializer, self.failed))?
+            .is_some()
+        {
+            map.dyn_next_value(&mut ValueSeed(serializer, self.failed))?;
+        }
+        self.failed.output(serializer.dyn_end())
+    }
+
+    fn dyn_visit_enum(&mut self, data: &mut dyn EnumAccess<'de>) -> DeserializeResult<()> {
+        let _ = data;
+        Err(DeserializeError::invalid_type(
+            serde::de::Unexpected::Enum,
+            &"any value except enum",
+        ))
+    }
+}
@@ -0,0 +1,1366 @@
+//! # In-memory Value Tree.
+//!
+//! This module provides [`Value`], a lossless in-memory representation of everything that the
+//! [`Serializer`](crate::ser::Serializer) trait can emit, including 128-bit integers, raw bytes and
+//! the names and indices of structs and enum variants.
+//!
+//! - [`ValueSerializer`]: a [`serde::Serializer`] that builds a [`Value`].
+//! - [`Value`] itself implements [`serde::Serialize`], [`serde::Deserialize`] and
+//!   [`serde::Deserializer`].
+//!
+//! Both of them can be converted into their dynamic counterparts with `<dyn Serializer>::new` and
+//! `<dyn Deserializer>::new`, which allows to buffer, inspect and replay data no matter which
+//! format it came from.
+//!
+//! # Examples
+//!
+//! ```
+//! # use serde_dyn::{Serialize, Serializer, Deserializer};
+//! # use serde_dyn::ser::InplaceSerializer;
+//! # use serde_dyn::value::{Value, ValueSerializer};
+//! let mut serializer = <dyn Serializer>::new(ValueSerializer);
+//! (&(1_u8, "two") as &dyn Serialize).dyn_serialize(&mut serializer).unwrap();
+//! let InplaceSerializer::Ok(value) = serializer else { unreachable!() };
+//! assert_eq!(value, Value::Tuple(vec![Value::U8(1), Value::String("two".into())]));
+//!
+//! let mut deserializer = <dyn Deserializer>::new(value);
+//! let deserializer: &mut dyn Deserializer = &mut deserializer;
+//! let value: (u8, String) = serde::Deserialize::deserialize(deserializer).unwrap();
+//! assert_eq!(value, (1, "two".to_string()));
+//! ```
+
+use core::fmt;
+
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::boxed::Box;
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::string::String;
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::vec::Vec;
+
+use serde::de::value::{
+    BorrowedStrDeserializer, MapAccessDeserializer, MapDeserializer, SeqDeserializer,
+    StringDeserializer,
+};
+use serde::de::{IgnoredAny, IntoDeserializer, Unexpected};
+
+use crate::de::{DeserializeError, DeserializeResult};
+use crate::ser::{SerializeError, SerializeResult, SerializerError, SerializerState};
+
+/// Serialize the given data structure into a [`Value`].
+///
+/// # Examples
+///
+/// ```
+/// # use serde_dyn::value::{self, Value};
+/// let value = value::to_value(&Some(42_i128)).unwrap();
+/// assert_eq!(value, Value::Some(Box::new(Value::I128(42))));
+/// ```
+pub fn to_value<T: ?Sized + serde::Serialize>(value: &T) -> SerializeResult<Value> {
+    value.serialize(ValueSerializer)
+}
+
+/// Deserialize an instance of type `T` from a [`Value`].
+///
+/// # Examples
+///
+/// ```
+/// # use serde_dyn::value::{self, Value};
+/// let value = Value::Seq(vec![Value::U8(1), Value::U8(2)]);
+/// let value: Vec<u32> = value::from_value(value).unwrap();
+/// assert_eq!(value, [1, 2]);
+/// ```
+pub fn from_value<T: serde::de::DeserializeOwned>(value: Value) -> DeserializeResult<T> {
+    T::deserialize(value)
+}
+
+/// A lossless representation of any data structure supported by [`serde`].
+///
+/// Each variant corresponds to one method of [`serde::Serializer`], so that serializing a `Value`
+/// replays exactly the same calls that produced it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// Serialized by [`serde::Serializer::serialize_bool`].
+    Bool(bool),
+    /// Serialized by [`serde::Serializer::serialize_i8`].
+    I8(i8),
+    /// Serialized by [`serde::Serializer::serialize_i16`].
+    I16(i16),
+    /// Serialized by [`serde::Serializer::serialize_i32`].
+    I32(i32),
+    /// Serialized by [`serde::Serializer::serialize_i64`].
+    I64(i64),
+    /// Serialized by [`serde::Serializer::serialize_i128`].
+    I128(i128),
+    /// Serialized by [`serde::Serializer::serialize_u8`].
+    U8(u8),
+    /// Serialized by [`serde::Serializer::serialize_u16`].
+    U16(u16),
+    /// Serialized by [`serde::Serializer::serialize_u32`].
+    U32(u32),
+    /// Serialized by [`serde::Serializer::serialize_u64`].
+    U64(u64),
+    /// Serialized by [`serde::Serializer::serialize_u128`].
+    U128(u128),
+    /// Serialized by [`serde::Serializer::serialize_f32`].
+    F32(f32),
+    /// Serialized by [`serde::Serializer::serialize_f64`].
+    F64(f64),
+    /// Serialized by [`serde::Serializer::serialize_char`].
+    Char(char),
+    /// Serialized by [`serde::Serializer::serialize_str`].
+    String(String),
+    /// Serialized by [`serde::Serializer::serialize_bytes`].
+    Bytes(Vec<u8>),
+    /// Serialized by [`serde::Serializer::serialize_none`].
+    None,
+    /// Serialized by [`serde::Serializer::serialize_some`].
+    Some(Box<Value>),
+    /// Serialized by [`serde::Serializer::serialize_unit`].
+    Unit,
+    /// Serialized by [`serde::Serializer::serialize_unit_struct`].
+    UnitStruct(&'static str),
+    /// Serialized by [`serde::Serializer::serialize_unit_variant`].
+    UnitVariant {
+        /// The name of the enum.
+        name: &'static str,
+        /// The index of the variant within the enum.
+        variant_index: u32,
+        /// The name of the variant.
+        variant: &'static str,
+    },
+    /// Serialized by [`serde::Serializer::serialize_newtype_struct`].
+    NewtypeStruct {
+        /// The name of the struct.
+        name: &'static str,
+        /// The wrapped value.
+        value: Box<Value>,
+    },
+    /// Serialized by [`serde::Serializer::serialize_newtype_variant`].
+    NewtypeVariant {
+        /// The name of the enum.
+        name: &'static str,
+        /// The index of the variant within the enum.
+        variant_index: u32,
+        /// The name of the variant.
+        variant: &'static str,
+        /// The wrapped value.
+        value: Box<Value>,
+    },
+    /// Serialized by [`serde::Serializer::serialize_seq`].
+    Seq(Vec<Value>),
+    /// Serialized by [`serde::Serializer::serialize_tuple`].
+    Tuple(Vec<Value>),
+    /// Serialized by [`serde::Serializer::serialize_tuple_struct`].
+    TupleStruct {
+        /// The name of the struct.
+        name: &'static str,
+        /// The fields of the struct.
+        fields: Vec<Value>,
+    },
+    /// Serialized by [`serde::Serializer::serialize_tuple_variant`].
+    TupleVariant {
+        /// The name of the enum.
+        name: &'static str,
+        /// The index of the variant within the enum.
+        variant_index: u32,
+        /// The name of the variant.
+        variant: &'static str,
+        /// The fields of the variant.
+        fields: Vec<Value>,
+    },
+    /// Serialized by [`serde::Serializer::serialize_map`].
+    Map(Vec<(Value, Value)>),
+    /// Serialized by [`serde::Serializer::serialize_struct`].
+    Struct {
+        /// The name of the struct.
+        name: &'static str,
+        /// The named fields of the struct, skipped fields excluded.
+        fields: Vec<(&'static str, Value)>,
+    },
+    /// Serialized by [`serde::Serializer::serialize_struct_variant`].
+    StructVariant {
+        /// The name of the enum.
+        name: &'static str,
+        /// The index of the variant within the enum.
+        variant_index: u32,
+        /// The name of the variant.
+        variant: &'static str,
+        /// The named fields of the variant, skipped fields excluded.
+        fields: Vec<(&'static str, Value)>,
+    },
+}
+
+impl Value {
+    /// Returns what a visitor cannot tell about the value, see [`Shape`].
+    #[cfg(feature = "std")]
+    fn shape(&self) -> Option<Shape> {
+        let field_names =
+            |fields: &[(&'static str, Value)]| fields.iter().map(|&(key, _)| key).collect();
+        let variant = |name, variant_index, variant, kind| Shape::Variant {
+            name,
+            variant_index,
+            variant,
+            kind,
+        };
+        match *self {
+            Value::Tuple(_) => Some(Shape::Tuple),
+            Value::UnitStruct(name) => Some(Shape::UnitStruct(name)),
+            Value::NewtypeStruct { name, .. } => Some(Shape::NewtypeStruct(name)),
+            Value::TupleStruct { name, .. } => Some(Shape::TupleStruct(name)),
+            Value::Struct { name, ref fields } => Some(Shape::Struct(name, field_names(fields))),
+            Value::UnitVariant {
+                name,
+                variant_index,
+                variant: v,
+            } => Some(variant(name, variant_index, v, VariantKind::Unit)),
+            Value::NewtypeVariant {
+                name,
+                variant_index,
+                variant: v,
+                ..
+            } => Some(variant(name, variant_index, v, VariantKind::Newtype)),
+            Value::TupleVariant {
+                name,
+                variant_index,
+                variant: v,
+                ref fields,
+            } => Some(variant(
+                name,
+                variant_index,
+                v,
+                VariantKind::Tuple(fields.len()),
+            )),
+            Value::StructVariant {
+                name,
+                variant_index,
+                variant: v,
+                ref fields,
+            } => Some(variant(
+                name,
+                variant_index,
+                v,
+                VariantKind::Struct(field_names(fields)),
+            )),
+            _ => None,
+        }
+    }
+
+    fn unexpected(&self) -> Unexpected<'_> {
+        match self {
+            Value::Bool(v) => Unexpected::Bool(*v),
+            Value::I8(v) => Unexpected::Signed(i64::from(*v)),
+            Value::I16(v) => Unexpected::Signed(i64::from(*v)),
+            Value::I32(v) => Unexpected::Signed(i64::from(*v)),
+            Value::I64(v) => Unexpected::Signed(*v),
+            Value::I128(_) => Unexpected::Other("i128"),
+            Value::U8(v) => Unexpected::Unsigned(u64::from(*v)),
+            Value::U16(v) => Unexpected::Unsigned(u64::from(*v)),
+            Value::U32(v) => Unexpected::Unsigned(u64::from(*v)),
+            Value::U64(v) => Unexpected::Unsigned(*v),
+            Value::U128(_) => Unexpected::Other("u128"),
+            Value::F32(v) => Unexpected::Float(f64::from(*v)),
+            Value::F64(v) => Unexpected::Float(*v),
+            Value::Char(v) => Unexpected::Char(*v),
+            Value::String(v) => Unexpected::Str(v),
+            Value::Bytes(v) => Unexpected::Bytes(v),
+            Value::None | Value::Some(_) => Unexpected::Option,
+            Value::Unit | Value::UnitStruct(_) => Unexpected::Unit,
+            Value::UnitVariant { .. } => Unexpected::UnitVariant,
+            Value::NewtypeStruct { .. } => Unexpected::NewtypeStruct,
+            Value::NewtypeVariant { .. } => Unexpected::NewtypeVariant,
+            Value::Seq(_) | Value::Tuple(_) | Value::TupleStruct { .. } => Unexpected::Seq,
+            Value::TupleVariant { .. } => Unexpected::TupleVariant,
+            Value::Map(_) | Value::Struct { .. } => Unexpected::Map,
+            Value::StructVariant { .. } => Unexpected::StructVariant,
+        }
+    }
+}
+
+impl serde::Serialize for Value {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{
+            SerializeMap, SerializeStruct, SerializeStructVariant, SerializeTuple,
+            SerializeTupleStruct, SerializeTupleVariant,
+        };
+
+        match self {
+            Value::Bool(v) => serializer.serialize_bool(*v),
+            Value::I8(v) => serializer.serialize_i8(*v),
+            Value::I16(v) => serializer.serialize_i16(*v),
+            Value::I32(v) => serializer.serialize_i32(*v),
+            Value::I64(v) => serializer.serialize_i64(*v),
+            Value::I128(v) => serializer.serialize_i128(*v),
+            Value::U8(v) => serializer.serialize_u8(*v),
+            Value::U16(v) => serializer.serialize_u16(*v),
+            Value::U32(v) => serializer.serialize_u32(*v),
+            Value::U64(v) => serializer.serialize_u64(*v),
+            Value::U128(v) => serializer.serialize_u128(*v),
+            Value::F32(v) => serializer.serialize_f32(*v),
+            Value::F64(v) => serializer.serialize_f64(*v),
+            Value::Char(v) => serializer.serialize_char(*v),
+            Value::String(v) => serializer.serialize_str(v),
+            Value::Bytes(v) => serializer.serialize_bytes(v),
+            Value::None => serializer.serialize_none(),
+            Value::Some(value) => serializer.serialize_some(value),
+            Value::Unit => serializer.serialize_unit(),
+            Value::UnitStruct(name) => serializer.serialize_unit_struct(name),
+            Value::UnitVariant {
+                name,
+                variant_index,
+                variant,
+            } => serializer.serialize_unit_variant(name, *variant_index, variant),
+            Value::NewtypeStruct { name, value } => {
+                serializer.serialize_newtype_struct(name, value)
+            }
+            Value::NewtypeVariant {
+                name,
+                variant_index,
+                variant,
+                value,
+            } => serializer.serialize_newtype_variant(name, *variant_index, variant, value),
+            Value::Seq(elements) => serializer.collect_seq(elements),
+            Value::Tuple(elements) => {
+                let mut serializer = serializer.serialize_tuple(elements.len())?;
+                for element in elements {
+                    serializer.serialize_element(element)?;
+                }
+                serializer.end()
+            }
+            Value::TupleStruct { name, fields } => {
+                let mut serializer = serializer.serialize_tuple_struct(name, fields.len())?;
+                for field in fields {
+                    serializer.serialize_field(field)?;
+                }
+                serializer.end()
+            }
+            Value::TupleVariant {
+                name,
+                variant_index,
+                variant,
+                fields,
+            } => {
+                let mut serializer = serializer.serialize_tuple_variant(
+                    name,
+                    *variant_index,
+                    variant,
+                    fields.len(),
+                )?;
+                for field in fields {
+                    serializer.serialize_field(field)?;
+                }
+                serializer.end()
+            }
+            Value::Map(entries) => {
+                let mut serializer = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    serializer.serialize_entry(key, value)?;
+                }
+                serializer.end()
+            }
+            Value::Struct { name, fields } => {
+                let mut serializer = serializer.serialize_struct(name, fields.len())?;
+                for (key, value) in fields {
+                    serializer.serialize_field(key, value)?;
+                }
+                serializer.end()
+            }
+            Value::StructVariant {
+                name,
+                variant_index,
+                variant,
+                fields,
+            } => {
+                let mut serializer = serializer.serialize_struct_variant(
+                    name,
+                    *variant_index,
+                    variant,
+                    fields.len(),
+                )?;
+                for (key, value) in fields {
+                    serializer.serialize_field(key, value)?;
+                }
+                serializer.end()
+            }
+        }
+    }
+}
+
+/// Deserializes a [`Value`] from any self-describing format with `deserialize_any`.
+///
+/// The input is kept as the format reports it, e.g. 128-bit integers and raw bytes survive. With
+/// `std`, deserializing a `Value` from a `Value` rebuilds it exactly, including the names of
+/// structs and enum variants. Other formats don't report the names, so newtype structs are
+/// transparent, and enum variants become maps with the variant as the only key.
+impl<'de> serde::Deserialize<'de> for Value {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+/// The [`serde::de::Visitor`] building a [`Value`].
+struct ValueVisitor;
+
+impl<'de> serde::de::Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i8<E>(self, v: i8) -> Result<Value, E> {
+        Ok(Value::I8(v))
+    }
+
+    fn visit_i16<E>(self, v: i16) -> Result<Value, E> {
+        Ok(Value::I16(v))
+    }
+
+    fn visit_i32<E>(self, v: i32) -> Result<Value, E> {
+        Ok(Value::I32(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::I64(v))
+    }
+
+    fn visit_i128<E>(self, v: i128) -> Result<Value, E> {
+        Ok(Value::I128(v))
+    }
+
+    fn visit_u8<E>(self, v: u8) -> Result<Value, E> {
+        Ok(Value::U8(v))
+    }
+
+    fn visit_u16<E>(self, v: u16) -> Result<Value, E> {
+        Ok(Value::U16(v))
+    }
+
+    fn visit_u32<E>(self, v: u32) -> Result<Value, E> {
+        Ok(Value::U32(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::U64(v))
+    }
+
+    fn visit_u128<E>(self, v: u128) -> Result<Value, E> {
+        Ok(Value::U128(v))
+    }
+
+    fn visit_f32<E>(self, v: f32) -> Result<Value, E> {
+        Ok(Value::F32(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::F64(v))
+    }
+
+    fn visit_char<E>(self, v: char) -> Result<Value, E> {
+        Ok(Value::Char(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(v.into()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Value, E> {
+        Ok(Value::Bytes(v.into()))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Value, E> {
+        Ok(Value::Bytes(v))
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::None)
+    }
+
+    fn visit_some<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        let value = deserializer.deserialize_any(self)?;
+        Ok(Value::Some(Box::new(value)))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        match take_shape() {
+            Some(Shape::UnitStruct(name)) => Ok(Value::UnitStruct(name)),
+            _ => Ok(Value::Unit),
+        }
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match take_shape() {
+            Some(Shape::NewtypeStruct(name)) => Ok(Value::NewtypeStruct {
+                name,
+                value: Box::new(deserializer.deserialize_any(self)?),
+            }),
+            _ => deserializer.deserialize_any(self),
+        }
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, seq: A) -> Result<Value, A::Error> {
+        let shape = take_shape();
+        let elements = Elements.visit_seq(seq)?;
+        match shape {
+            Some(Shape::Tuple) => Ok(Value::Tuple(elements)),
+            Some(Shape::TupleStruct(name)) => Ok(Value::TupleStruct {
+                name,
+                fields: elements,
+            }),
+            _ => Ok(Value::Seq(elements)),
+        }
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, map: A) -> Result<Value, A::Error> {
+        let shape = take_shape();
+        let entries = Entries.visit_map(map)?;
+        match shape {
+            Some(Shape::Struct(name, keys)) => match into_fields(keys, entries) {
+                Ok(fields) => Ok(Value::Struct { name, fields }),
+                Err(map) => Ok(map),
+            },
+            _ => Ok(Value::Map(entries)),
+        }
+    }
+
+    fn visit_enum<A: serde::de::EnumAccess<'de>>(self, data: A) -> Result<Value, A::Error> {
+        use serde::de::VariantAccess as _;
+
+        let Some(Shape::Variant {
+            name,
+            variant_index,
+            variant,
+            kind,
+        }) = take_shape()
+        else {
+            let (variant, data) = data.variant::<Value>()?;
+            let value = data.newtype_variant()?;
+            return Ok(Value::Map(Vec::from([(variant, value)])));
+        };
+
+        let (IgnoredAny, data) = data.variant()?;
+        match kind {
+            VariantKind::Unit => {
+                data.unit_variant()?;
+                Ok(Value::UnitVariant {
+                    name,
+                    variant_index,
+                    variant,
+                })
+            }
+            VariantKind::Newtype => Ok(Value::NewtypeVariant {
+                name,
+                variant_index,
+                variant,
+                value: Box::new(data.newtype_variant()?),
+            }),
+            VariantKind::Tuple(len) => Ok(Value::TupleVariant {
+                name,
+                variant_index,
+                variant,
+                fields: data.tuple_variant(len, Elements)?,
+            }),
+            VariantKind::Struct(keys) => {
+                match into_fields(keys, data.struct_variant(&[], Entries)?) {
+                    Ok(fields) => Ok(Value::StructVariant {
+                        name,
+                        variant_index,
+                        variant,
+                        fields,
+                    }),
+                    Err(map) => Ok(Value::Map(Vec::from([(
+                        Value::String(variant.into()),
+                        map,
+                    )]))),
+                }
+            }
+        }
+    }
+}
+
+/// The [`serde::de::Visitor`] collecting the elements of a sequence.
+struct Elements;
+
+impl<'de> serde::de::Visitor<'de> for Elements {
+    type Value = Vec<Value>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a sequence")
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<Value>, A::Error> {
+        let mut elements = Vec::new();
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+        Ok(elements)
+    }
+}
+
+/// The [`serde::de::Visitor`] collecting the entries of a map.
+struct Entries;
+
+impl<'de> serde::de::Visitor<'de> for Entries {
+    type Value = Vec<(Value, Value)>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Vec<(Value, Value)>, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+}
+
+/// Pairs the values of `entries` with `keys`, if they are the keys of `entries` in order.
+/// Otherwise returns the entries as a [`Value::Map`].
+fn into_fields(
+    keys: Vec<&'static str>,
+    entries: Vec<(Value, Value)>,
+) -> Result<Vec<(&'static str, Value)>, Value> {
+    let matches = keys.len() == entries.len()
+        && keys
+            .iter()
+            .zip(&entries)
+            .all(|(key, (entry, _))| matches!(entry, Value::String(entry) if entry == key));
+    if !matches {
+        return Err(Value::Map(entries));
+    }
+    Ok(keys
+        .into_iter()
+        .zip(entries)
+        .map(|(key, (_, value))| (key, value))
+        .collect())
+}
+
+/// What a [`Value`] holds beyond the [`serde::de::Visitor`] calls it is deserialized into.
+///
+/// With `std`, the deserializer of a `Value` lends it to the [`ValueVisitor`] through a thread
+/// local, since a visitor has no way to receive `&'static` names.
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+enum Shape {
+    Tuple,
+    UnitStruct(&'static str),
+    NewtypeStruct(&'static str),
+    TupleStruct(&'static str),
+    Struct(&'static str, Vec<&'static str>),
+    Variant {
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        kind: VariantKind,
+    },
+}
+
+/// The kind of an enum variant in [`Shape::Variant`].
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+enum VariantKind {
+    Unit,
+    Newtype,
+    Tuple(usize),
+    Struct(Vec<&'static str>),
+}
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    /// The shape of the [`Value`] being deserialized, see [`LendShape`].
+    static SHAPE: core::cell::Cell<Option<Shape>> = const { core::cell::Cell::new(None) };
+}
+
+/// Lends the shape of a [`Value`] to the [`ValueVisitor`] it is deserialized with, until dropped.
+#[cfg(feature = "std")]
+struct LendShape;
+
+#[cfg(feature = "std")]
+impl LendShape {
+    fn new(value: &Value) -> Self {
+        SHAPE.set(value.shape());
+        LendShape
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for LendShape {
+    fn drop(&mut self) {
+        SHAPE.set(None);
+    }
+}
+
+/// Takes the shape lent by [`LendShape`], if any.
+fn take_shape() -> Option<Shape> {
+    #[cfg(feature = "std")]
+    {
+        SHAPE.take()
+    }
+
+    #[cfg(not(feature = "std"))]
+    {
+        None
+    }
+}
+
+/// An implementation of the [`serde::Serializer`] trait that builds a [`Value`].
+///
+/// Use `<dyn Serializer>::new(ValueSerializer)` to get a dynamic
+/// [`Serializer`](crate::ser::Serializer), which leaves the [`Value`] in
+/// [`InplaceSerializer::Ok`](crate::ser::InplaceSerializer::Ok).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ValueSerializer;
+
+impl serde::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = SerializeError;
+    type SerializeSeq = SerializeValue;
+    type SerializeTuple = SerializeValue;
+    type SerializeTupleStruct = SerializeValue;
+    type SerializeTupleVariant = SerializeValue;
+    type SerializeMap = SerializeValue;
+    type SerializeStruct = SerializeValue;
+    type SerializeStructVariant = SerializeValue;
+
+    fn serialize_bool(self, v: bool) -> SerializeResult<Value> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> SerializeResult<Value> {
+        Ok(Value::I8(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> SerializeResult<Value> {
+        Ok(Value::I16(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> SerializeResult<Value> {
+        Ok(Value::I32(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> SerializeResult<Value> {
+        Ok(Value::I64(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> SerializeResult<Value> {
+        Ok(Value::I128(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> SerializeResult<Value> {
+        Ok(Value::U8(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> SerializeResult<Value> {
+        Ok(Value::U16(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> SerializeResult<Value> {
+        Ok(Value::U32(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> SerializeResult<Value> {
+        Ok(Value::U64(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> SerializeResult<Value> {
+        Ok(Value::U128(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> SerializeResult<Value> {
+        Ok(Value::F32(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> SerializeResult<Value> {
+        Ok(Value::F64(v))
+    }
+
+    fn serialize_char(self, v: char) -> SerializeResult<Value> {
+        Ok(Value::Char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> SerializeResult<Value> {
+        Ok(Value::String(v.into()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> SerializeResult<Value> {
+        Ok(Value::Bytes(v.into()))
+    }
+
+    fn serialize_none(self) -> SerializeResult<Value> {
+        Ok(Value::None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> SerializeResult<Value>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        Ok(Value::Some(Box::new(to_value(value)?)))
+    }
+
+    fn serialize_unit(self) -> SerializeResult<Value> {
+        Ok(Value::Unit)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> SerializeResult<Value> {
+        Ok(Value::UnitStruct(name))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> SerializeResult<Value> {
+        Ok(Value::UnitVariant {
+            name,
+            variant_index,
+            variant,
+        })
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> SerializeResult<Value>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        Ok(Value::NewtypeStruct {
+            name,
+            value: Box::new(to_value(value)?),
+        })
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> SerializeResult<Value>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        Ok(Value::NewtypeVariant {
+            name,
+            variant_index,
+            variant,
+            value: Box::new(to_value(value)?),
+        })
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> SerializeResult<SerializeValue> {
+        Ok(SerializeValue::new(Value::Seq(Vec::with_capacity(
+            len.unwrap_or_default(),
+        ))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> SerializeResult<SerializeValue> {
+        Ok(SerializeValue::new(Value::Tuple(Vec::with_capacity(len))))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> SerializeResult<SerializeValue> {
+        Ok(SerializeValue::new(Value::TupleStruct {
+            name,
+            fields: Vec::with_capacity(len),
+        }))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> SerializeResult<SerializeValue> {
+        Ok(SerializeValue::new(Value::TupleVariant {
+            name,
+            variant_index,
+            variant,
+            fields: Vec::with_capacity(len),
+        }))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> SerializeResult<SerializeValue> {
+        Ok(SerializeValue::new(Value::Map(Vec::with_capacity(
+            len.unwrap_or_default(),
+        ))))
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> SerializeResult<SerializeValue> {
+        Ok(SerializeValue::new(Value::Struct {
+            name,
+            fields: Vec::with_capacity(len),
+        }))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> SerializeResult<SerializeValue> {
+        Ok(SerializeValue::new(Value::StructVariant {
+            name,
+            variant_index,
+            variant,
+            fields: Vec::with_capacity(len),
+        }))
+    }
+}
+
+/// Returned from [`ValueSerializer`] to build compound values, such as sequences and structs.
+#[derive(Debug)]
+pub struct SerializeValue {
+    value: Value,
+    key: Option<Value>,
+}
+
+impl SerializeValue {
+    const fn new(value: Value) -> Self {
+        SerializeValue { value, key: None }
+    }
+
+    /// Returns the state that the compound being built corresponds to.
+    fn state(&self) -> SerializerState {
+        match self.value {
+            Value::Seq(_) => SerializerState::SerializeSeq,
+            Value::Tuple(_) => SerializerState::SerializeTuple,
+            Value::TupleStruct { .. } => SerializerState::SerializeTupleStruct,
+            Value::TupleVariant { .. } => SerializerState::SerializeTupleVariant,
+            Value::Map(_) => SerializerState::SerializeMap,
+            Value::Struct { .. } => SerializerState::SerializeStruct,
+            Value::StructVariant { .. } => SerializerState::SerializeStructVariant,
+            _ => SerializerState::Ok,
+        }
+    }
+
+    #[cold]
+    fn wrong_state(&self, operation: &'static str) -> SerializeError {
+        SerializeError::from(SerializerError::WrongState {
+            operation,
+            found: self.state(),
+        })
+    }
+
+    fn push_element<T>(&mut self, value: &T, operation: &'static str) -> SerializeResult<()>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        match &mut self.value {
+            Value::Seq(elements)
+            | Value::Tuple(elements)
+            | Value::TupleStruct {
+                fields: elements, ..
+            }
+            | Value::TupleVariant {
+                fields: elements, ..
+            } => {
+                elements.push(to_value(value)?);
+                Ok(())
+            }
+            _ => Err(self.wrong_state(operation)),
+        }
+    }
+
+    fn push_field<T>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+        operation: &'static str,
+    ) -> SerializeResult<()>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        match &mut self.value {
+            Value::Struct { fields, .. } | Value::StructVariant { fields, .. } => {
+                fields.push((key, to_value(value)?));
+                Ok(())
+            }
+            _ => Err(self.wrong_state(operation)),
+        }
+    }
+}
+
+impl serde::ser::SerializeSeq for SerializeValue {
+    type Ok = Value;
+    type Error = SerializeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> SerializeResult<()>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.push_element(value, "SerializeSeq::serialize_element")
+    }
+
+    fn end(self) -> SerializeResult<Value> {
+        Ok(self.value)
+    }
+}
+
+impl serde::ser::SerializeTuple for SerializeValue {
+    type Ok = Value;
+    type Error = SerializeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> SerializeResult<()>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.push_element(value, "SerializeTuple::serialize_element")
+    }
+
+    fn end(self) -> SerializeResult<Value> {
+        Ok(self.value)
+    }
+}
+
+impl serde::ser::SerializeTupleStruct for SerializeValue {
+    type Ok = Value;
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> SerializeResult<()>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.push_element(value, "SerializeTupleStruct::serialize_field")
+    }
+
+    fn end(self) -> SerializeResult<Value> {
+        Ok(self.value)
+    }
+}
+
+impl serde::ser::SerializeTupleVariant for SerializeValue {
+    type Ok = Value;
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> SerializeResult<()>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.push_element(value, "SerializeTupleVariant::serialize_field")
+    }
+
+    fn end(self) -> SerializeResult<Value> {
+        Ok(self.value)
+    }
+}
+
+impl serde::ser::SerializeMap for SerializeValue {
+    type Ok = Value;
+    type Error = SerializeError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> SerializeResult<()>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        if let (Value::Map(_), None) = (&self.value, &self.key) {
+            self.key = Some(to_value(key)?);
+            Ok(())
+        } else {
+            Err(self.wrong_state("SerializeMap::serialize_key"))
+        }
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> SerializeResult<()>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        if let (Value::Map(entries), Some(key)) = (&mut self.value, self.key.take()) {
+            entries.push((key, to_value(value)?));
+            Ok(())
+        } else {
+            Err(self.wrong_state("SerializeMap::serialize_value"))
+        }
+    }
+
+    fn end(self) -> SerializeResult<Value> {
+        if self.key.is_none() {
+            Ok(self.value)
+        } else {
+            Err(self.wrong_state("SerializeMap::end"))
+        }
+    }
+}
+
+impl serde::ser::SerializeStruct for SerializeValue {
+    type Ok = Value;
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> SerializeResult<()>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.push_field(key, value, "SerializeStruct::serialize_field")
+    }
+
+    fn end(self) -> SerializeResult<Value> {
+        Ok(self.value)
+    }
+}
+
+impl serde::ser::SerializeStructVariant for SerializeValue {
+    type Ok = Value;
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> SerializeResult<()>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.push_field(key, value, "SerializeStructVariant::serialize_field")
+    }
+
+    fn end(self) -> SerializeResult<Value> {
+        Ok(self.value)
+    }
+}
+
+impl<'de> IntoDeserializer<'de, DeserializeError> for Value {
+    type Deserializer = Value;
+
+    #[inline]
+    fn into_deserializer(self) -> Value {
+        self
+    }
+}
+
+impl<'de> serde::Deserializer<'de> for Value {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> DeserializeResult<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        #[cfg(feature = "std")]
+        let _shape = LendShape::new(&self);
+        match self {
+            Value::Bool(v) => visitor.visit_bool(v),
+            Value::I8(v) => visitor.visit_i8(v),
+            Value::I16(v) => visitor.visit_i16(v),
+            Value::I32(v) => visitor.visit_i32(v),
+            Value::I64(v) => visitor.visit_i64(v),
+            Value::I128(v) => visitor.visit_i128(v),
+            Value::U8(v) => visitor.visit_u8(v),
+            Value::U16(v) => visitor.visit_u16(v),
+            Value::U32(v) => visitor.visit_u32(v),
+            Value::U64(v) => visitor.visit_u64(v),
+            Value::U128(v) => visitor.visit_u128(v),
+            Value::F32(v) => visitor.visit_f32(v),
+            Value::F64(v) => visitor.visit_f64(v),
+            Value::Char(v) => visitor.visit_char(v),
+            Value::String(v) => visitor.visit_string(v),
+            Value::Bytes(v) => visitor.visit_byte_buf(v),
+            Value::None => visitor.visit_none(),
+            Value::Some(value) => visitor.visit_some(*value),
+            Value::Unit | Value::UnitStruct(_) => visitor.visit_unit(),
+            Value::NewtypeStruct { value, .. } => visitor.visit_newtype_struct(*value),
+            Value::Seq(elements)
+            | Value::Tuple(elements)
+            | Value::TupleStruct {
+                fields: elements, ..
+            } => visit_seq(elements, visitor),
+            Value::Map(entries) => visit_map(entries, visitor),
+            Value::Struct { fields, .. } => visit_map(fields, visitor),
+            value @ (Value::UnitVariant { .. }
+            | Value::NewtypeVariant { .. }
+            | Value::TupleVariant { .. }
+            | Value::StructVariant { .. }) => visitor.visit_enum(EnumDeserializer(value)),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> DeserializeResult<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        match self {
+            Value::None => visitor.visit_none(),
+            Value::Some(value) => visitor.visit_some(*value),
+            // `Option` reads a unit as `None`, as with `serde_json`'s `null`.
+            Value::Unit => visitor.visit_unit(),
+            value => visitor.visit_some(value),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> DeserializeResult<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        match self {
+            Value::NewtypeStruct { value, .. } => visitor.visit_newtype_struct(*value),
+            value => visitor.visit_newtype_struct(value),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> DeserializeResult<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        match self {
+            value @ (Value::UnitVariant { .. }
+            | Value::NewtypeVariant { .. }
+            | Value::TupleVariant { .. }
+            | Value::StructVariant { .. }) => visitor.visit_enum(EnumDeserializer(value)),
+            // Unit variants from self-describing formats, e.g. `"Variant"`.
+            Value::String(variant) => StringDeserializer::<DeserializeError>::new(variant)
+                .deserialize_enum(name, variants, visitor),
+            // Externally tagged variants from self-describing formats, e.g. `{"Variant":...}`.
+            Value::Map(entries) if entries.len() == 1 => {
+                let mut map = MapDeserializer::new(entries.into_iter());
+                let value = visitor.visit_enum(MapAccessDeserializer::new(&mut map))?;
+                map.end()?;
+                Ok(value)
+            }
+            value => Err(serde::de::Error::invalid_type(
+                value.unexpected(),
+                &"an enum variant",
+            )),
+        }
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> DeserializeResult<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        drop(self);
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+    }
+}
+
+fn visit_seq<'de, V>(elements: Vec<Value>, visitor: V) -> DeserializeResult<V::Value>
+where
+    V: serde::de::Visitor<'de>,
+{
+    let mut seq = SeqDeserializer::new(elements.into_iter());
+    let value = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+    Ok(value)
+}
+
+fn visit_map<'de, K, V>(entries: Vec<(K, Value)>, visitor: V) -> DeserializeResult<V::Value>
+where
+    K: IntoDeserializer<'de, DeserializeError>,
+    V: serde::de::Visitor<'de>,
+{
+    let mut map = MapDeserializer::new(entries.into_iter());
+    let value = visitor.visit_map(&mut map)?;
+    map.end()?;
+    Ok(value)
+}
+
+/// An implementation of [`serde::de::EnumAccess`] and [`serde::de::VariantAccess`] for
+/// [`Value::UnitVariant`], [`Value::NewtypeVariant`], [`Value::TupleVariant`] and
+/// [`Value::StructVariant`].
+struct EnumDeserializer(Value);
+
+impl<'de> serde::de::EnumAccess<'de> for EnumDeserializer {
+    type Error = DeserializeError;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> DeserializeResult<(V::Value, Self)>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        let variant = match self.0 {
+            Value::UnitVariant { variant, .. }
+            | Value::NewtypeVariant { variant, .. }
+            | Value::TupleVariant { variant, .. }
+            | Value::StructVariant { variant, .. } => variant,
+            ref value => {
+                return Err(serde::de::Error::invalid_type(
+                    value.unexpected(),
+                    &"an enum variant",
+                ));
+            }
+        };
+        let variant =
+            seed.deserialize(BorrowedStrDeserializer::<DeserializeError>::new(variant))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> serde::de::VariantAccess<'de> for EnumDeserializer {
+    type Error = DeserializeError;
+
+    fn unit_variant(self) -> DeserializeResult<()> {
+        match self.0 {
+            Value::UnitVariant { .. } => Ok(()),
+            value => Err(serde::de::Error::invalid_type(
+                value.unexpected(),
+                &"unit variant",
+            )),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> DeserializeResult<T::Value>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        match self.0 {
+            Value::NewtypeVariant { value, .. } => seed.deserialize(*value),
+            value => Err(serde::de::Error::invalid_type(
+                value.unexpected(),
+                &"newtype variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> DeserializeResult<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        match self.0 {
+            Value::TupleVariant { fields, .. } => visit_seq(fields, visitor),
+            value => Err(serde::de::Error::invalid_type(
+                value.unexpected(),
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> DeserializeResult<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        match self.0 {
+            Value::StructVariant { fields, .. } => visit_map(fields, visitor),
+            value => Err(serde::de::Error::invalid_type(
+                value.unexpected(),
+                &"struct variant",
+            )),
+        }
+    }
+}
@@ -222,3 +222,441 @@ fn test_serialize_struct_variant() {
     let value = <Enum as serde::Deserialize<'_>>::deserialize(deserializer).unwrap();
     assert_eq!(value, Enum::StructVariant { a: true });
 }
+
+#[test]
+fn test_with_dyn() {
+    let mut deserializer = serde_json::Deserializer::from_str("[1,2]");
+    let value = serde_dyn::de::with_dyn(&mut deserializer, |deserializer| {
+        <(u8, u8) as serde::Deserialize<'_>>::deserialize(deserializer)
+    });
+    assert_eq!(value.unwrap(), (1, 2));
+
+    // The original error of `serde_json` keeps its position.
+    let mut deserializer = serde_json::Deserializer::from_str("[1,\n true]");
+    let error = serde_dyn::de::with_dyn(&mut deserializer, |deserializer| {
+        <(u8, u8) as serde::Deserialize<'_>>::deserialize(deserializer)
+    })
+    .unwrap_err();
+    assert_eq!((error.line(), error.column()), (2, 5));
+    assert!(error.is_data());
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_error_source() {
+    use std::error::Error as _;
+    use std::io::{self, Read};
+
+    struct Unplugged;
+
+    impl Read for Unplugged {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "unplugged"))
+        }
+    }
+
+    let mut json = serde_json::Deserializer::from_reader(Unplugged);
+    let error = serde_dyn::path::deserialize(&mut json, |deserializer| {
+        <Vec<u8> as serde::Deserialize>::deserialize(deserializer)
+    })
+    .unwrap_err();
+    let source = error.downcast_ref::<serde_json::Error>().unwrap();
+    assert!(source.is_io());
+    assert_eq!(source.io_error_kind(), Some(io::ErrorKind::BrokenPipe));
+    // The wrapper is transparent, and the I/O error has no source.
+    assert!(error.source().is_none());
+
+    // The same holds for a plain dynamic deserializer.
+    let mut json = serde_json::Deserializer::from_reader(Unplugged);
+    let mut deserializer = <dyn Deserializer>::new(&mut json);
+    let result =
+        <Vec<u8> as serde::Deserialize>::deserialize(&mut deserializer as &mut dyn Deserializer);
+    let error = deserializer.into_result(result).unwrap_err();
+    let source = error.downcast_ref::<serde_json::Error>().unwrap();
+    assert_eq!(source.io_error_kind(), Some(io::ErrorKind::BrokenPipe));
+
+    let error = <serde_dyn::de::DeserializeError as serde::de::Error>::custom("no source");
+    assert!(error.source().is_none());
+}
+
+#[test]
+fn test_error_source_through_visitor() {
+    use std::fmt;
+    use std::num::ParseIntError;
+
+    use serde_dyn::de::{DeserializeError, DeserializeResult, Visitor};
+    use serde_dyn::value::Value;
+
+    struct Parse;
+
+    impl<'de> Visitor<'de> for Parse {
+        fn dyn_expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("a number")
+        }
+
+        fn dyn_visit_str(&mut self, v: &str) -> DeserializeResult<()> {
+            v.parse::<u8>().map_err(DeserializeError::from_source)?;
+            Ok(())
+        }
+    }
+
+    // The visitor errors pass through the concrete `serde::de::Error`, which is
+    // `DeserializeError` here, with their source and path.
+    let error = serde::Deserializer::deserialize_any(
+        Value::String("x".to_string()),
+        &mut Parse as &mut dyn Visitor,
+    )
+    .unwrap_err();
+    assert_eq!(error.to_string(), "invalid digit found in string");
+    assert!(error.downcast_ref::<ParseIntError>().is_some());
+
+    #[derive(Debug)]
+    struct Parsed;
+
+    impl<'de> serde::Deserialize<'de> for Parsed {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_any(&mut Parse as &mut dyn Visitor)?;
+            Ok(Parsed)
+        }
+    }
+
+    let value = Value::Seq(vec![Value::String("x".to_string())]);
+    let error = serde_dyn::path::deserialize(value, |deserializer| {
+        <Vec<Parsed> as serde::Deserialize>::deserialize(deserializer)
+    })
+    .unwrap_err();
+    assert_eq!(error.to_string(), "[0]: invalid digit found in string");
+    assert!(error.downcast_ref::<ParseIntError>().is_some());
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_error_send_sync() {
+    fn assert_send_sync<T: Send + Sync + Clone + 'static>() {}
+    assert_send_sync::<serde_dyn::de::DeserializeError>();
+    assert_eq!(
+        std::mem::size_of::<serde_dyn::de::DeserializeError>(),
+        std::mem::size_of::<usize>()
+    );
+
+    let error = <serde_dyn::de::DeserializeError as serde::de::Error>::missing_field("port");
+    let cloned = error.clone();
+    let error = std::thread::spawn(move || error).join().unwrap();
+    assert_eq!(error.to_string(), cloned.to_string());
+    assert_eq!(error.kind(), cloned.kind());
+}
+
+#[cfg(all(
+    not(any(feature = "std", feature = "alloc")),
+    feature = "heapless-errors"
+))]
+#[test]
+fn test_error_heapless() {
+    use serde_dyn::de::DeserializeError;
+
+    let error = <DeserializeError as serde::de::Error>::invalid_type(
+        serde::de::Unexpected::Unit,
+        &"a boolean",
+    );
+    assert_eq!(
+        error.to_string(),
+        "invalid type: unit value, expected a boolean"
+    );
+
+    let error = <DeserializeError as serde::de::Error>::missing_field("port");
+    assert_eq!(error.to_string(), "missing field `port`");
+}
+
+#[test]
+fn test_deserialize_slot() {
+    use serde_dyn::de::Deserialize;
+
+    let mut name: Option<String> = None;
+    let mut port: Option<u16> = None;
+    {
+        let slots: [&mut dyn Deserialize<'_>; 2] = [&mut name, &mut port];
+        make_deserializer!(deserializer = "\"localhost\"");
+        slots[0].dyn_deserialize(deserializer).unwrap();
+        make_deserializer!(deserializer = "80");
+        slots[1].dyn_deserialize(deserializer).unwrap();
+
+        // The slot is untouched on failure.
+        make_deserializer!(deserializer = "true");
+        assert!(slots[0].dyn_deserialize(deserializer).is_err());
+    }
+    assert_eq!(name.as_deref(), Some("localhost"));
+    assert_eq!(port, Some(80));
+
+    // `&mut dyn Deserialize` is also a `serde::de::DeserializeSeed`.
+    let mut pair: Option<(u8, u8)> = None;
+    let slot: &mut dyn Deserialize<'_> = &mut pair;
+    let mut deserializer = serde_json::Deserializer::from_str("[1,2]");
+    serde::de::DeserializeSeed::deserialize(slot, &mut deserializer).unwrap();
+    assert_eq!(pair, Some((1, 2)));
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_deserialize_slot_vec() {
+    use serde_dyn::de::Deserialize;
+
+    let mut ports: Vec<u16> = Vec::new();
+    let slot: &mut dyn Deserialize<'_> = &mut ports;
+    make_deserializer!(deserializer = "80");
+    slot.dyn_deserialize(deserializer).unwrap();
+    make_deserializer!(deserializer = "443");
+    slot.dyn_deserialize(deserializer).unwrap();
+    assert_eq!(ports, [80, 443]);
+}
+
+#[test]
+fn test_deserialize_in_place() {
+    use serde_dyn::de::DeserializeInPlace;
+
+    let mut place: (String, Vec<u32>) = (String::with_capacity(32), Vec::with_capacity(32));
+    let buffers = (place.0.as_ptr(), place.1.as_ptr());
+
+    make_deserializer!(deserializer = "[\"snapshot\",[1,2,3]]");
+    <(String, Vec<u32>) as serde::Deserialize<'_>>::deserialize_in_place(deserializer, &mut place)
+        .unwrap();
+    assert_eq!(place, ("snapshot".to_string(), vec![1, 2, 3]));
+    assert_eq!((place.0.as_ptr(), place.1.as_ptr()), buffers);
+
+    let dyn_place: &mut dyn DeserializeInPlace<'_> = &mut place;
+    make_deserializer!(deserializer = "[\"reloaded\",[4]]");
+    dyn_place.dyn_deserialize_in_place(deserializer).unwrap();
+    assert_eq!(place, ("reloaded".to_string(), vec![4]));
+    assert_eq!((place.0.as_ptr(), place.1.as_ptr()), buffers);
+
+    // `&mut dyn DeserializeInPlace` is also a `serde::de::DeserializeSeed`.
+    let dyn_place: &mut dyn DeserializeInPlace<'_> = &mut place;
+    let mut deserializer = serde_json::Deserializer::from_str("[\"again\",[5,6]]");
+    serde::de::DeserializeSeed::deserialize(dyn_place, &mut deserializer).unwrap();
+    assert_eq!(place, ("again".to_string(), vec![5, 6]));
+    assert_eq!((place.0.as_ptr(), place.1.as_ptr()), buffers);
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_boxed() {
+    use serde_dyn::de::{self, BoxDeserializer};
+
+    struct Plugin<'de> {
+        deserializer: BoxDeserializer<'de>,
+    }
+
+    fn open(input: &str) -> Plugin<'_> {
+        Plugin {
+            deserializer: de::boxed_mut(serde_json::Deserializer::from_str(input)),
+        }
+    }
+
+    let mut plugin = open("{\"port\":8080}");
+    let deserializer: &mut dyn Deserializer<'_> = &mut plugin.deserializer;
+    let value =
+        <std::collections::BTreeMap<String, u16> as serde::Deserialize>::deserialize(deserializer)
+            .unwrap();
+    assert_eq!(value["port"], 8080);
+    assert!(plugin.deserializer.error::<serde_json::Error>().is_none());
+
+    // The original error is kept.
+    let mut plugin = open("{\"port\":\n-1}");
+    let deserializer: &mut dyn Deserializer<'_> = &mut plugin.deserializer;
+    assert!(<(u8,) as serde::Deserialize>::deserialize(deserializer).is_err());
+    let error = plugin
+        .deserializer
+        .into_error::<serde_json::Error>()
+        .unwrap();
+    assert!(error.is_data());
+    assert_eq!(error.line(), 1);
+
+    let mut deserializer = de::boxed(serde_json::json!("Hello"));
+    let value = <u8 as serde::Deserialize>::deserialize(&mut deserializer as &mut dyn Deserializer);
+    assert!(value.is_err());
+    assert_eq!(
+        deserializer
+            .error::<serde_json::Error>()
+            .unwrap()
+            .to_string(),
+        "invalid type: string \"Hello\", expected u8"
+    );
+}
+
+#[test]
+fn test_wrong_state() {
+    use serde::de::IgnoredAny;
+    use serde_dyn::de::{
+        DeserializerError, DeserializerState, InplaceDeserializer, InplaceVisitor, Visitor,
+    };
+
+    let mut visitor = InplaceVisitor::Visitor(IgnoredAny);
+    visitor.dyn_visit_u64(1).unwrap();
+    let error = visitor.dyn_visit_u64(2).unwrap_err();
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    assert_eq!(
+        error.to_string(),
+        "`Visitor::dyn_visit_u64` is called, but it has already produced a value"
+    );
+    #[cfg(not(any(feature = "std", feature = "alloc")))]
+    let _ = error;
+
+    // The failed visitor makes the deserializer fail.
+    let mut json = serde_json::Deserializer::from_str("1");
+    let mut deserializer = <dyn Deserializer>::new(&mut json);
+    let mut visitor = InplaceVisitor::<IgnoredAny>::None;
+    let error = deserializer.dyn_deserialize_any(&mut visitor).unwrap_err();
+    assert!(matches!(error, DeserializerError::Error));
+    assert_eq!(deserializer.state(), DeserializerState::Error);
+
+    let error = deserializer.dyn_deserialize_any(&mut visitor).unwrap_err();
+    assert!(matches!(
+        error,
+        DeserializerError::WrongState {
+            operation: "Deserializer::dyn_deserialize_any",
+            found: DeserializerState::Error,
+        }
+    ));
+    assert_eq!(
+        error.to_string(),
+        "`Deserializer::dyn_deserialize_any` is called, but it has already failed"
+    );
+    // The misuse leaves the concrete error in place.
+    assert_eq!(deserializer.state(), DeserializerState::Error);
+    let InplaceDeserializer::Error(error) = deserializer else {
+        unreachable!()
+    };
+    assert!(
+        error
+            .to_string()
+            .starts_with("`Visitor::dyn_visit_u64` is called, but it is not ready")
+    );
+
+    let mut visitor = InplaceVisitor::Visitor(IgnoredAny);
+    visitor.dyn_visit_u64(1).unwrap();
+    visitor.dyn_visit_u64(2).unwrap_err();
+    assert!(matches!(visitor, InplaceVisitor::Value(IgnoredAny)));
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_visitor_defaults() {
+    use std::fmt;
+
+    use serde_dyn::de::{DeserializeResult, Visitor};
+
+    #[derive(Default)]
+    struct Numbers(Vec<String>);
+
+    impl<'de> Visitor<'de> for Numbers {
+        fn dyn_expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("a number")
+        }
+
+        fn dyn_visit_i64(&mut self, v: i64) -> DeserializeResult<()> {
+            self.0.push(format!("i64 {v}"));
+            Ok(())
+        }
+
+        fn dyn_visit_u64(&mut self, v: u64) -> DeserializeResult<()> {
+            self.0.push(format!("u64 {v}"));
+            Ok(())
+        }
+
+        fn dyn_visit_str(&mut self, v: &str) -> DeserializeResult<()> {
+            self.0.push(format!("str {v}"));
+            Ok(())
+        }
+    }
+
+    let mut visitor = Numbers::default();
+    visitor.dyn_visit_i8(-1).unwrap();
+    visitor.dyn_visit_u16(2).unwrap();
+    visitor.dyn_visit_char('x').unwrap();
+    visitor.dyn_visit_borrowed_str("y").unwrap();
+    visitor.dyn_visit_string("z".to_string()).unwrap();
+    assert_eq!(visitor.0, ["i64 -1", "u64 2", "str x", "str y", "str z"]);
+
+    let error = visitor.dyn_visit_bool(true).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid type: boolean `true`, expected a number"
+    );
+    let error = visitor.dyn_visit_f32(0.5).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid type: floating point `0.5`, expected a number"
+    );
+    // The same wording as `serde::de::Visitor::visit_i128` and `visit_u128`.
+    let error = visitor.dyn_visit_i128(i128::MIN).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid type: integer `-170141183460469231731687303715884105728` as i128, expected a \
+         number"
+    );
+    let error = visitor.dyn_visit_u128(1).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid type: integer `1` as u128, expected a number"
+    );
+    assert!(matches!(
+        error.kind(),
+        serde_dyn::de::DeserializeErrorKind::InvalidType(_, "a number")
+    ));
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_fn_visitor() {
+    use serde::de::Error;
+    use serde_dyn::de::{DeserializeError, DeserializeSeed, FnSeed, FnVisitor};
+
+    fn port(input: &str) -> Result<Option<u16>, serde_json::Error> {
+        let mut json = serde_json::Deserializer::from_str(input);
+        serde_dyn::de::with_dyn(&mut json, |deserializer| {
+            let mut visitor = FnVisitor::new("a port number")
+                .on_u64(|v| u16::try_from(v).map_err(DeserializeError::custom))
+                .on_str(|s| s.parse().map_err(DeserializeError::custom));
+            deserializer.dyn_deserialize_any(&mut visitor)?;
+            Ok(visitor.into_value())
+        })
+    }
+
+    assert_eq!(port("80").unwrap(), Some(80));
+    assert_eq!(port("\"443\"").unwrap(), Some(443));
+    assert_eq!(
+        port("true").unwrap_err().to_string(),
+        "invalid type: boolean `true`, expected a port number at line 1 column 4"
+    );
+    assert_eq!(
+        port("-1").unwrap_err().to_string(),
+        "invalid type: integer `-1`, expected a port number at line 1 column 2"
+    );
+
+    // Sum a sequence with a seed for each element.
+    let mut json = serde_json::Deserializer::from_str("[1,2,3]");
+    let mut deserializer = <dyn Deserializer>::new(&mut json);
+    let mut sum = 0;
+    let mut visitor = FnVisitor::new("a sequence of numbers").on_seq(|seq| {
+        loop {
+            let mut seed =
+                FnSeed::new(|deserializer| <u64 as serde::Deserialize>::deserialize(deserializer));
+            if seq.dyn_next_element(&mut seed)?.is_none() {
+                return Ok(());
+            }
+            sum += seed.into_value().unwrap();
+        }
+    });
+    deserializer.dyn_deserialize_seq(&mut visitor).unwrap();
+    assert_eq!(visitor.into_value(), Some(()));
+    assert_eq!(sum, 6);
+
+    let mut seed = FnSeed::new(|deserializer| {
+        let mut visitor = FnVisitor::new("nothing").on_unit(|| Ok("unit"));
+        deserializer.dyn_deserialize_unit(&mut visitor)?;
+        Ok(visitor.into_value())
+    });
+    let mut json = serde_json::Deserializer::from_str("null");
+    let mut deserializer = <dyn Deserializer>::new(&mut json);
+    seed.dyn_deserialize(&mut deserializer).unwrap();
+    assert_eq!(seed.into_value(), Some(Some("unit")));
+}
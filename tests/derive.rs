@@ -0,0 +1,63 @@
+#![cfg(feature = "derive")]
+
+#[serde_dyn::serializable]
+trait Shape: serde_dyn::Serialize {
+    fn area(&self) -> f64;
+}
+
+#[derive(serde::Serialize)]
+struct Circle {
+    radius: f64,
+}
+
+impl Shape for Circle {
+    fn area(&self) -> f64 {
+        3.0 * self.radius * self.radius
+    }
+}
+
+#[derive(serde::Serialize)]
+struct Square(f64);
+
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.0 * self.0
+    }
+}
+
+#[serde_dyn::serializable]
+trait Labeled<T>: serde_dyn::Serialize {
+    fn label(&self) -> T;
+}
+
+impl Labeled<char> for Square {
+    fn label(&self) -> char {
+        'S'
+    }
+}
+
+#[test]
+fn test_serializable() {
+    let shapes: Vec<Box<dyn Shape>> = vec![Box::new(Circle { radius: 1.0 }), Box::new(Square(2.0))];
+    assert_eq!(shapes.iter().map(|shape| shape.area()).sum::<f64>(), 7.0);
+    assert_eq!(
+        serde_json::to_string(&shapes).unwrap(),
+        r#"[{"radius":1.0},2.0]"#
+    );
+
+    let shapes: Vec<Box<dyn Shape + Send + Sync>> = vec![Box::new(Square(2.0))];
+    assert_eq!(serde_json::to_string(&shapes).unwrap(), "[2.0]");
+
+    let shape: &(dyn Shape + Send) = &Square(1.5);
+    assert_eq!(serde_json::to_string(shape).unwrap(), "1.5");
+
+    let shape: &(dyn Shape + Sync) = &Circle { radius: 0.5 };
+    assert_eq!(serde_json::to_string(shape).unwrap(), r#"{"radius":0.5}"#);
+}
+
+#[test]
+fn test_serializable_generic() {
+    let labeled: Box<dyn Labeled<char>> = Box::new(Square(1.0));
+    assert_eq!(labeled.label(), 'S');
+    assert_eq!(serde_json::to_string(&labeled).unwrap(), "1.0");
+}
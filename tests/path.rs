@@ -0,0 +1,178 @@
+#![cfg(any(feature = "std", feature = "alloc"))]
+
+use std::collections::BTreeMap;
+
+use serde_dyn::path::{self, Segment};
+use serde_dyn::{Deserializer, Serialize};
+
+#[derive(Debug, serde::Deserialize)]
+#[allow(dead_code)]
+struct Config {
+    servers: Vec<Server>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+#[allow(dead_code)]
+struct Server {
+    tls: Option<Tls>,
+    mode: Mode,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[allow(dead_code)]
+struct Tls {
+    cert_path: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[allow(dead_code)]
+enum Mode {
+    Plain,
+    Proxy(String, u16),
+    Limited { rate: u32 },
+}
+
+fn deserialize<T: serde::de::DeserializeOwned + std::fmt::Debug>(
+    json: &str,
+) -> serde_dyn::de::DeserializeError {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    path::deserialize(&mut deserializer, |deserializer: &mut dyn Deserializer| {
+        T::deserialize(deserializer)
+    })
+    .unwrap_err()
+}
+
+#[test]
+fn test_deserialize_path() {
+    let error = deserialize::<Config>(
+        r#"{"servers":[{"mode":"Plain"},{"tls":{"cert_path":1},"mode":"Plain"}]}"#,
+    );
+    let path = error.path().unwrap();
+    assert_eq!(path.to_string(), "servers[1].tls.cert_path");
+    assert_eq!(
+        path.iter().cloned().collect::<Vec<_>>(),
+        vec![
+            Segment::Map {
+                key: "servers".to_string()
+            },
+            Segment::Seq { index: 1 },
+            Segment::Map {
+                key: "tls".to_string()
+            },
+            Segment::Map {
+                key: "cert_path".to_string()
+            },
+        ]
+    );
+    assert!(
+        error
+            .to_string()
+            .starts_with("servers[1].tls.cert_path: invalid type: integer `1`, expected a string")
+    );
+
+    let error = deserialize::<Config>(r#"{"servers":[{"mode":{"Proxy":["localhost",-1]}}]}"#);
+    assert_eq!(
+        error.path().unwrap().to_string(),
+        "servers[0].mode.Proxy[1]"
+    );
+
+    let error = deserialize::<Config>(r#"{"servers":[{"mode":{"Limited":{"rate":true}}}]}"#);
+    assert_eq!(
+        error.path().unwrap().to_string(),
+        "servers[0].mode.Limited.rate"
+    );
+
+    // The unknown field is reported at the struct containing it.
+    let error = deserialize::<Config>(r#"{"servers":[{"mode":"Plain","port":80}]}"#);
+    assert_eq!(error.path().unwrap().to_string(), "servers[0]");
+    assert!(error.to_string().contains("unknown field `port`"));
+
+    let error = deserialize::<BTreeMap<u32, Vec<u8>>>(r#"{"7":[0,256]}"#);
+    assert_eq!(error.path().unwrap().to_string(), "7[1]");
+
+    let error = deserialize::<Config>("[]");
+    assert!(error.path().unwrap().is_empty());
+    assert_eq!(error.path().unwrap().to_string(), ".");
+}
+
+#[test]
+fn test_deserialize_path_ok() {
+    let mut deserializer = serde_json::Deserializer::from_str(r#"{"servers":[{"mode":"Plain"}]}"#);
+    let config = path::deserialize(&mut deserializer, |deserializer: &mut dyn Deserializer| {
+        <Config as serde::Deserialize>::deserialize(deserializer)
+    })
+    .unwrap();
+    assert_eq!(config.servers.len(), 1);
+}
+
+struct Fail;
+
+impl serde::Serialize for Fail {
+    fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom("failed"))
+    }
+}
+
+#[derive(serde::Serialize)]
+enum Node {
+    Leaf(Fail),
+    Tuple(u8, Fail),
+    Struct { ok: u8, fail: Fail },
+}
+
+#[derive(serde::Serialize)]
+struct Root {
+    nodes: Vec<Node>,
+    map: BTreeMap<&'static str, (u8, Fail)>,
+}
+
+fn serialize(value: &dyn Serialize) -> serde_dyn::ser::SerializeError {
+    path::serialize(serde_json::value::Serializer, |serializer| {
+        value.dyn_serialize(serializer)
+    })
+    .unwrap_err()
+}
+
+#[test]
+fn test_serialize_path() {
+    let error = serialize(&Root {
+        nodes: vec![Node::Leaf(Fail)],
+        map: BTreeMap::new(),
+    });
+    assert_eq!(error.to_string(), "nodes[0].Leaf: failed");
+
+    let error = serialize(&Root {
+        nodes: vec![],
+        map: [("key", (0, Fail))].into_iter().collect(),
+    });
+    assert_eq!(error.to_string(), "map.key[1]: failed");
+
+    let error = serialize(&vec![Node::Tuple(0, Fail)]);
+    assert_eq!(error.to_string(), "[0].Tuple[1]: failed");
+
+    let error = serialize(&vec![Node::Struct { ok: 0, fail: Fail }]);
+    assert_eq!(error.to_string(), "[0].Struct.fail: failed");
+
+    // The items collected by the dynamic serializer are tracked as well.
+    let error = path::serialize(serde_json::value::Serializer, |serializer| {
+        let items: [&dyn Serialize; 2] = [&0, &Fail];
+        serializer.dyn_collect_seq(&mut items.iter().copied())?;
+        Ok(())
+    })
+    .unwrap_err();
+    assert_eq!(error.to_string(), "[1]: failed");
+
+    let error = serialize(&Fail);
+    assert!(error.path().unwrap().is_empty());
+    assert_eq!(error.to_string(), "failed");
+}
+
+#[test]
+fn test_serialize_path_ok() {
+    let value = path::serialize(serde_json::value::Serializer, |serializer| {
+        vec![Some(1), None].dyn_serialize(serializer)
+    })
+    .unwrap();
+    assert_eq!(value, serde_json::json!([1, null]));
+}
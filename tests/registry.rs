@@ -0,0 +1,292 @@
+use serde::Deserialize;
+use serde_dyn::Deserializer;
+use serde_dyn::de::DeserializeResult;
+use serde_dyn::registry::{Entry, EntrySeed, Registry, Tagging};
+
+trait Stage: serde_dyn::Serialize {
+    fn tag(&self) -> &'static str;
+    fn run(&self, x: i32) -> i32;
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Add {
+    n: i32,
+}
+
+impl Stage for Add {
+    fn tag(&self) -> &'static str {
+        "add"
+    }
+
+    fn run(&self, x: i32) -> i32 {
+        x + self.n
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Scale(i32);
+
+impl Stage for Scale {
+    fn tag(&self) -> &'static str {
+        "scale"
+    }
+
+    fn run(&self, x: i32) -> i32 {
+        x * self.0
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Reset;
+
+impl Stage for Reset {
+    fn tag(&self) -> &'static str {
+        "reset"
+    }
+
+    fn run(&self, _: i32) -> i32 {
+        0
+    }
+}
+
+/// Deserializes `Add`, with the amount limited to `max`.
+struct AddSeed {
+    max: i32,
+}
+
+impl EntrySeed<Box<dyn Stage>> for AddSeed {
+    fn dyn_deserialize(
+        &self,
+        deserializer: &mut dyn Deserializer<'_>,
+    ) -> DeserializeResult<Box<dyn Stage>> {
+        let Add { n } = Add::deserialize(deserializer)?;
+        Ok(Box::new(Add { n: n.min(self.max) }))
+    }
+}
+
+const TAGS: &[&str] = &["add", "scale", "reset"];
+
+const ENTRIES: &[Entry<Box<dyn Stage>>] = &[
+    Entry::with_seed("add", &AddSeed { max: 100 }),
+    Entry::new("scale", |deserializer| {
+        Ok(Box::new(Scale::deserialize(deserializer)?))
+    }),
+    Entry::new("reset", |deserializer| {
+        Ok(Box::new(Reset::deserialize(deserializer)?))
+    }),
+];
+
+static EXTERNAL: Registry<Box<dyn Stage>> =
+    Registry::new("Stage", Tagging::External, TAGS, ENTRIES);
+
+static INTERNAL: Registry<Box<dyn Stage>> =
+    Registry::new("Stage", Tagging::Internal { tag: "type" }, TAGS, ENTRIES);
+
+static ADJACENT: Registry<Box<dyn Stage>> = Registry::new(
+    "Stage",
+    Tagging::Adjacent {
+        tag: "t",
+        content: "c",
+    },
+    TAGS,
+    ENTRIES,
+);
+
+struct Tagged<'a>(&'a Registry<Box<dyn Stage>>, &'a dyn Stage);
+
+impl serde::Serialize for Tagged<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(self.1.tag(), self.1, serializer)
+    }
+}
+
+fn to_json(registry: &Registry<Box<dyn Stage>>, stage: &dyn Stage) -> String {
+    serde_json::to_string(&Tagged(registry, stage)).unwrap()
+}
+
+fn from_json(
+    registry: &Registry<Box<dyn Stage>>,
+    json: &str,
+) -> serde_json::Result<Box<dyn Stage>> {
+    registry.deserialize(&mut serde_json::Deserializer::from_str(json))
+}
+
+fn from_json_error(registry: &Registry<Box<dyn Stage>>, json: &str) -> String {
+    match from_json(registry, json) {
+        Ok(stage) => panic!("unexpected stage `{}`", stage.tag()),
+        Err(error) => error.to_string(),
+    }
+}
+
+#[test]
+fn test_registry_external() {
+    assert_eq!(to_json(&EXTERNAL, &Add { n: 2 }), r#"{"add":{"n":2}}"#);
+    assert_eq!(to_json(&EXTERNAL, &Scale(3)), r#"{"scale":3}"#);
+
+    let stage = from_json(&EXTERNAL, r#"{"scale":3}"#).unwrap();
+    assert_eq!(stage.run(2), 6);
+
+    // The seed of `add` limits the amount.
+    let stage = from_json(&EXTERNAL, r#"{"add":{"n":1000}}"#).unwrap();
+    assert_eq!(stage.run(0), 100);
+
+    // Formats that are not self-describing identify the tag by the index of the entry.
+    let cbor = serde_cbor::ser::to_vec_packed(&Tagged(&EXTERNAL, &Scale(3))).unwrap();
+    let stage = EXTERNAL
+        .deserialize(&mut serde_cbor::Deserializer::from_slice(&cbor))
+        .unwrap();
+    assert_eq!(stage.tag(), "scale");
+}
+
+#[test]
+fn test_registry_internal() {
+    assert_eq!(to_json(&INTERNAL, &Add { n: 2 }), r#"{"type":"add","n":2}"#);
+
+    let stage = from_json(&INTERNAL, r#"{"type":"add","n":2}"#).unwrap();
+    assert_eq!(stage.run(1), 3);
+
+    // Unit structs are maps with the tag only, as with `serde_derive`.
+    assert_eq!(to_json(&INTERNAL, &Reset), r#"{"type":"reset"}"#);
+    let stage = from_json(&INTERNAL, r#"{"type":"reset","x":1}"#).unwrap();
+    assert_eq!(stage.run(1), 0);
+
+    // The entries before the tag are buffered.
+    let stage = from_json(&INTERNAL, r#"{"n":2,"type":"add"}"#).unwrap();
+    assert_eq!(stage.run(1), 3);
+    let stage = from_json(&INTERNAL, r#"{"x":[1],"type":"reset","y":{}}"#).unwrap();
+    assert_eq!(stage.run(1), 0);
+
+    let error = from_json_error(&INTERNAL, r#"{"n":2}"#);
+    assert_eq!(error, "missing field `type` at line 1 column 7");
+
+    let error = serde_json::to_string(&Tagged(&INTERNAL, &Scale(3))).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "cannot serialize tagged newtype variant Stage::scale containing an integer"
+    );
+}
+
+#[test]
+fn test_registry_internal_newtype_struct() {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Twice(Add);
+
+    impl Stage for Twice {
+        fn tag(&self) -> &'static str {
+            "twice"
+        }
+
+        fn run(&self, x: i32) -> i32 {
+            self.0.run(self.0.run(x))
+        }
+    }
+
+    static REGISTRY: Registry<Box<dyn Stage>> = Registry::new(
+        "Stage",
+        Tagging::Internal { tag: "type" },
+        &["twice"],
+        &[Entry::new("twice", |deserializer| {
+            Ok(Box::new(Twice::deserialize(deserializer)?))
+        })],
+    );
+
+    // Newtype structs are transparent, as with `serde_derive`.
+    let json = to_json(&REGISTRY, &Twice(Add { n: 2 }));
+    assert_eq!(json, r#"{"type":"twice","n":2}"#);
+
+    let stage = from_json(&REGISTRY, &json).unwrap();
+    assert_eq!(stage.run(1), 5);
+}
+
+#[test]
+fn test_registry_adjacent() {
+    assert_eq!(to_json(&ADJACENT, &Scale(3)), r#"{"t":"scale","c":3}"#);
+
+    let stage = from_json(&ADJACENT, r#"{"t":"scale","x":null,"c":3}"#).unwrap();
+    assert_eq!(stage.run(2), 6);
+
+    let error = from_json_error(&ADJACENT, r#"{"t":"scale"}"#);
+    assert_eq!(error, "missing field `c` at line 1 column 13");
+}
+
+#[test]
+fn test_registry_unknown_tag() {
+    let error = from_json_error(&EXTERNAL, r#"{"mul":3}"#);
+    assert_eq!(
+        error,
+        "unknown variant `mul`, expected one of `add`, `scale`, `reset` at line 1 column 6"
+    );
+
+    let error = from_json_error(&INTERNAL, r#"{"type":"mul"}"#);
+    assert_eq!(
+        error,
+        "unknown variant `mul`, expected one of `add`, `scale`, `reset` at line 1 column 13"
+    );
+
+    struct Mul;
+
+    impl serde::Serialize for Mul {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_unit()
+        }
+    }
+
+    impl Stage for Mul {
+        fn tag(&self) -> &'static str {
+            "mul"
+        }
+
+        fn run(&self, x: i32) -> i32 {
+            x
+        }
+    }
+
+    let error = serde_json::to_string(&Tagged(&EXTERNAL, &Mul)).unwrap_err();
+    assert_eq!(error.to_string(), "tag `mul` is not registered in Stage");
+}
+
+#[test]
+fn test_registry_variants() {
+    use serde::de::{Error, Visitor};
+
+    /// Reports the variants that the enum is deserialized with.
+    struct Variants;
+
+    impl<'de> serde::Deserializer<'de> for Variants {
+        type Error = serde::de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+            Err(Error::custom("not an enum"))
+        }
+
+        fn deserialize_enum<V: Visitor<'de>>(
+            self,
+            name: &'static str,
+            variants: &'static [&'static str],
+            _: V,
+        ) -> Result<V::Value, Self::Error> {
+            Err(Error::custom(format_args!("{name} {variants:?}")))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map struct identifier ignored_any
+        }
+    }
+
+    let error = EXTERNAL.deserialize(Variants).err().unwrap();
+    assert_eq!(error.to_string(), r#"Stage ["add", "scale", "reset"]"#);
+    assert_eq!(EXTERNAL.tags(), TAGS);
+}
+
+#[test]
+#[should_panic = "the tags must be the tags of the entries"]
+fn test_registry_tags_mismatch() {
+    let _ = Registry::new(
+        "Stage",
+        Tagging::External,
+        &["scale", "add", "reset"],
+        ENTRIES,
+    );
+}
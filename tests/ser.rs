@@ -311,3 +311,772 @@ fn test_collect_str() {
         .unwrap();
     assert_eq!(buf, b"\"entity not found\"");
 }
+
+#[test]
+fn test_with_dyn() {
+    let value = serde_dyn::ser::with_dyn(serde_json::value::Serializer, |serializer| {
+        let seq = serializer.dyn_serialize_seq(Some(2))?;
+        seq.dyn_serialize_element(&1_u8)?;
+        seq.dyn_serialize_element(&"2")?;
+        seq.dyn_end()?;
+        Ok(())
+    });
+    assert_eq!(value.unwrap(), serde_json::json!([1, "2"]));
+
+    // The original error of `serde_json` is returned.
+    let error = serde_dyn::ser::with_dyn(serde_json::value::Serializer, |serializer| {
+        let map = serializer.dyn_serialize_map(None)?;
+        map.dyn_serialize_key(&[0_u8])?;
+        Ok(())
+    });
+    assert_eq!(error.unwrap_err().to_string(), "key must be a string");
+
+    // The closure returns before the serialization finishes.
+    let error = serde_dyn::ser::with_dyn(serde_json::value::Serializer, |_| Ok(()));
+    assert!(error.is_err());
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_error_source() {
+    use std::error::Error as _;
+    use std::io::{self, Write};
+
+    use serde_dyn::ser::SerializeError;
+
+    struct DiskFull;
+
+    impl Write for DiskFull {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::StorageFull, "disk full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut json = serde_json::Serializer::new(DiskFull);
+    let error = serde_dyn::path::serialize(&mut json, |serializer| {
+        (&["Hello"] as &dyn Serialize).dyn_serialize(serializer)
+    })
+    .unwrap_err();
+    let source = error.downcast_ref::<serde_json::Error>().unwrap();
+    assert!(source.is_io());
+    assert_eq!(source.io_error_kind(), Some(io::ErrorKind::StorageFull));
+    // The wrapper is transparent, and the I/O error has no source.
+    assert!(error.source().is_none());
+
+    // The same holds for a plain dynamic serializer.
+    let mut json = serde_json::Serializer::new(DiskFull);
+    let mut serializer = <dyn Serializer>::new(&mut json);
+    let result = (&["Hello"] as &dyn Serialize).dyn_serialize(&mut serializer);
+    assert!(result.as_ref().unwrap_err().source().is_none());
+    let error = serializer.into_result(result).unwrap_err();
+    let source = error.downcast_ref::<serde_json::Error>().unwrap();
+    assert_eq!(source.io_error_kind(), Some(io::ErrorKind::StorageFull));
+
+    let error = <SerializeError as serde::ser::Error>::custom("no source");
+    assert!(error.source().is_none());
+    assert!(error.downcast_ref::<serde_json::Error>().is_none());
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_error_send_sync() {
+    fn assert_send_sync<T: Send + Sync + Clone + 'static>() {}
+    assert_send_sync::<serde_dyn::ser::SerializeError>();
+    assert_eq!(
+        std::mem::size_of::<serde_dyn::ser::SerializeError>(),
+        std::mem::size_of::<usize>()
+    );
+
+    let error = <serde_dyn::ser::SerializeError as serde::ser::Error>::custom("failed");
+    let cloned = error.clone();
+    let error = std::thread::spawn(move || error).join().unwrap();
+    assert_eq!(error.to_string(), "failed");
+    assert_eq!(cloned.to_string(), "failed");
+}
+
+#[cfg(all(
+    not(any(feature = "std", feature = "alloc")),
+    feature = "heapless-errors"
+))]
+#[test]
+fn test_error_heapless() {
+    use serde_dyn::ser::SerializeError;
+
+    let error = serde_dyn::ser::with_dyn(serde_json::value::Serializer, |serializer| {
+        serializer.dyn_serialize_u8(1)?;
+        Err(<SerializeError as serde::ser::Error>::custom(
+            "out of coffee",
+        ))
+    })
+    .unwrap_err();
+    assert_eq!(error.to_string(), "out of coffee");
+
+    // Long messages are truncated at a character boundary.
+    let error = <SerializeError as serde::ser::Error>::custom("♥".repeat(30));
+    assert_eq!(error.to_string(), format!("{}...", "♥".repeat(21)));
+}
+
+#[test]
+fn test_serialize_send_sync() {
+    use std::sync::Arc;
+
+    use serde_dyn::Serialize;
+
+    let value: Arc<dyn Serialize + Send + Sync> = Arc::new([1_u8, 2, 3]);
+    let json = std::thread::spawn({
+        let value = Arc::clone(&value);
+        move || serde_json::to_string(&value).unwrap()
+    })
+    .join()
+    .unwrap();
+    assert_eq!(json, "[1,2,3]");
+
+    let value: Box<dyn Serialize + Send> = Box::new("Hello");
+    assert_eq!(serde_json::to_string(&value).unwrap(), r#""Hello""#);
+
+    let value: &(dyn Serialize + Sync) = &Some(true);
+    assert_eq!(serde_json::to_string(value).unwrap(), "true");
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_serialize_clone() {
+    use serde_dyn::ser::SerializeClone;
+
+    let events: Vec<Box<dyn SerializeClone>> = vec![Box::new(("click", 42_u8)), Box::new(1.5_f32)];
+    let copies = events.clone();
+    drop(events);
+    assert_eq!(
+        serde_json::to_string(&copies).unwrap(),
+        r#"[["click",42],1.5]"#
+    );
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_serialize_clone_send() {
+    use serde_dyn::ser::SerializeClone;
+
+    let event: Box<dyn SerializeClone + Send + Sync> = Box::new(("click", 42_u8));
+    let workers: Vec<_> = (0..2)
+        .map(|_| {
+            let event = event.clone();
+            std::thread::spawn(move || serde_json::to_string(&event).unwrap())
+        })
+        .collect();
+    for worker in workers {
+        assert_eq!(worker.join().unwrap(), r#"["click",42]"#);
+    }
+
+    let event: Box<dyn SerializeClone + Send> = Box::new(1.5_f32);
+    let copy = event.clone();
+    let json = std::thread::spawn(move || serde_json::to_string(&copy).unwrap());
+    assert_eq!(json.join().unwrap(), "1.5");
+}
+
+#[test]
+#[cfg(any(feature = "std", feature = "alloc"))]
+fn test_boxed() {
+    use serde_dyn::ser;
+
+    let mut serializer = ser::boxed(serde_json::value::Serializer);
+    serde::Serialize::serialize(&(1, "a"), &mut serializer as &mut dyn Serializer).unwrap();
+    let value = serializer.finish().unwrap();
+    assert_eq!(
+        value.downcast_ref::<serde_json::Value>(),
+        Some(&serde_json::json!([1, "a"]))
+    );
+
+    // The output is of the wrong type.
+    let mut serializer = ser::boxed(serde_json::value::Serializer);
+    serializer.dyn_serialize_unit().unwrap();
+    let error = serializer.downcast::<String>().unwrap_err();
+    assert!(error.to_string().contains("the output is not of type"));
+
+    // The original error of the concrete serializer is kept.
+    let mut serializer = ser::boxed(serde_json::value::Serializer);
+    let map = serializer.dyn_serialize_map(None).unwrap();
+    assert!(map.dyn_serialize_key(&()).is_err());
+    let error = serializer.finish().unwrap_err();
+    assert!(error.downcast_ref::<serde_json::Error>().is_some());
+
+    // Nothing has been serialized.
+    let serializer = ser::boxed(serde_json::value::Serializer);
+    let error = serializer.finish().unwrap_err();
+    assert!(error.downcast_ref::<ser::SerializerError>().is_some());
+}
+
+#[test]
+fn test_collect() {
+    use serde::de::value::Error;
+    use serde::ser::Impossible;
+
+    /// Only supports `collect_seq` and `collect_map`, and reports which one is called with the
+    /// items in JSON. The entries of a map are buffered before they are serialized.
+    struct Collector;
+
+    macro_rules! unsupported {
+        ($($method:ident($($arg:ty),*) -> $ok:ty;)*) => {
+            $(fn $method(self, $(_: $arg),*) -> Result<$ok, Error> {
+                Err(serde::ser::Error::custom(stringify!($method)))
+            })*
+        };
+    }
+
+    impl serde::Serializer for Collector {
+        type Ok = String;
+        type Error = Error;
+        type SerializeSeq = Impossible<String, Error>;
+        type SerializeTuple = Impossible<String, Error>;
+        type SerializeTupleStruct = Impossible<String, Error>;
+        type SerializeTupleVariant = Impossible<String, Error>;
+        type SerializeMap = Impossible<String, Error>;
+        type SerializeStruct = Impossible<String, Error>;
+        type SerializeStructVariant = Impossible<String, Error>;
+
+        unsupported! {
+            serialize_bool(bool) -> String;
+            serialize_i8(i8) -> String;
+            serialize_i16(i16) -> String;
+            serialize_i32(i32) -> String;
+            serialize_i64(i64) -> String;
+            serialize_u8(u8) -> String;
+            serialize_u16(u16) -> String;
+            serialize_u32(u32) -> String;
+            serialize_u64(u64) -> String;
+            serialize_f32(f32) -> String;
+            serialize_f64(f64) -> String;
+            serialize_char(char) -> String;
+            serialize_str(&str) -> String;
+            serialize_bytes(&[u8]) -> String;
+            serialize_none() -> String;
+            serialize_unit() -> String;
+            serialize_unit_struct(&'static str) -> String;
+            serialize_unit_variant(&'static str, u32, &'static str) -> String;
+            serialize_seq(Option<usize>) -> Self::SerializeSeq;
+            serialize_tuple(usize) -> Self::SerializeTuple;
+            serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+            serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+            serialize_map(Option<usize>) -> Self::SerializeMap;
+            serialize_struct(&'static str, usize) -> Self::SerializeStruct;
+            serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+        }
+
+        fn serialize_some<T: ?Sized + serde::Serialize>(self, _: &T) -> Result<String, Error> {
+            Err(serde::ser::Error::custom("serialize_some"))
+        }
+
+        fn serialize_newtype_struct<T: ?Sized + serde::Serialize>(
+            self,
+            _: &'static str,
+            _: &T,
+        ) -> Result<String, Error> {
+            Err(serde::ser::Error::custom("serialize_newtype_struct"))
+        }
+
+        fn serialize_newtype_variant<T: ?Sized + serde::Serialize>(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: &T,
+        ) -> Result<String, Error> {
+            Err(serde::ser::Error::custom("serialize_newtype_variant"))
+        }
+
+        fn collect_seq<I>(self, iter: I) -> Result<String, Error>
+        where
+            I: IntoIterator<Item: serde::Serialize>,
+        {
+            let items = iter
+                .into_iter()
+                .map(|item| serde_json::to_string(&item))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(serde::ser::Error::custom)?;
+            Ok(format!("collect_seq of [{}]", items.join(",")))
+        }
+
+        fn collect_map<K, V, I>(self, iter: I) -> Result<String, Error>
+        where
+            K: serde::Serialize,
+            V: serde::Serialize,
+            I: IntoIterator<Item = (K, V)>,
+        {
+            let entries = iter.into_iter().collect::<Vec<_>>();
+            let entries = serde_json::to_string(&entries).map_err(serde::ser::Error::custom)?;
+            Ok(format!("collect_map of {entries}"))
+        }
+    }
+
+    let items: [&dyn Serialize; 3] = [&1, &"a", &true];
+    let output = serde_dyn::ser::with_dyn(Collector, |serializer| {
+        Ok(serializer.dyn_collect_seq(&mut items.iter().copied())?)
+    });
+    assert_eq!(output.unwrap(), r#"collect_seq of [1,"a",true]"#);
+
+    let entries: [(&dyn Serialize, &dyn Serialize); 2] = [(&"a", &1), (&"b", &2)];
+    let output = serde_dyn::ser::with_dyn(Collector, |serializer| {
+        Ok(serializer.dyn_collect_map(&mut entries.iter().copied())?)
+    });
+    assert_eq!(output.unwrap(), r#"collect_map of [["a",1],["b",2]]"#);
+
+    make_serializer!(buf, serializer);
+    serializer
+        .dyn_collect_map(&mut entries.iter().copied())
+        .unwrap();
+    assert_eq!(buf, br#"{"a":1,"b":2}"#);
+
+    // Collections reach `collect_seq` and `collect_map` of the format through the dyn layer, and
+    // the owned items are lent one at a time.
+    let output = serde_dyn::ser::with_dyn(Collector, |serializer| {
+        vec![1, 2, 3].dyn_serialize(serializer)
+    });
+    assert_eq!(output.unwrap(), "collect_seq of [1,2,3]");
+
+    let iter = (1..=3).map(|n| n * 10);
+    let output = serde_dyn::ser::with_dyn(serde_json::value::Serializer, |serializer| {
+        serde::Serializer::collect_seq(serializer, iter)
+    });
+    assert_eq!(output.unwrap(), serde_json::json!([10, 20, 30]));
+
+    // A lent entry is gone once the next one is taken.
+    let map = std::collections::BTreeMap::from([("a", 1), ("b", 2)]);
+    let error = serde_dyn::ser::with_dyn(Collector, |serializer| map.dyn_serialize(serializer));
+    assert!(
+        error
+            .unwrap_err()
+            .to_string()
+            .contains("replaced by the next one")
+    );
+}
+
+#[test]
+fn test_tee() {
+    use serde_dyn::ser::{InplaceSerializer, Tee};
+    use std::collections::BTreeMap;
+
+    let mut json = Vec::new();
+    let mut json_serializer = serde_json::Serializer::new(&mut json);
+    let mut json_serializer = <dyn Serializer>::new(&mut json_serializer);
+    let mut value_serializer = <dyn Serializer>::new(serde_json::value::Serializer);
+    let mut tee = Tee::new([&mut json_serializer, &mut value_serializer]);
+
+    // The items of a one-shot iterator are only pulled once.
+    let mut pulled = 0;
+    let mut iter = [1, 2, 3].iter().map(|item| {
+        pulled += 1;
+        item as &dyn Serialize
+    });
+    tee.dyn_collect_seq(&mut iter).unwrap();
+    assert!(tee.errors().iter().all(Option::is_none));
+    assert_eq!(pulled, 3);
+    assert_eq!(json, b"[1,2,3]");
+    let InplaceSerializer::Ok(value) = value_serializer else {
+        panic!("the serialization is not finished");
+    };
+    assert_eq!(value, serde_json::json!([1, 2, 3]));
+
+    // JSON rejects the keys that are not strings, while CBOR goes on.
+    let map: BTreeMap<(u8, u8), u8> = [((1, 2), 3)].into_iter().collect();
+    let mut json_serializer = <dyn Serializer>::new(serde_json::value::Serializer);
+    let mut cbor = Vec::new();
+    let mut cbor_serializer = serde_cbor::Serializer::new(&mut cbor);
+    let mut cbor_serializer = <dyn Serializer>::new(&mut cbor_serializer);
+    let mut tee = Tee::new([&mut json_serializer, &mut cbor_serializer]);
+    map.dyn_serialize(&mut tee).unwrap();
+    let errors = tee.errors();
+    assert!(errors[0].is_some() && errors[1].is_none());
+    assert!(matches!(json_serializer, InplaceSerializer::Error(_)));
+    assert_eq!(
+        serde_cbor::from_slice::<BTreeMap<(u8, u8), u8>>(&cbor).unwrap(),
+        map
+    );
+
+    // A one-shot nested value is drained once, however deep it is.
+    struct Drain(std::cell::RefCell<Vec<u8>>);
+
+    impl serde::Serialize for Drain {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.0.borrow_mut().drain(..))
+        }
+    }
+
+    let value = (
+        Some(Drain(std::cell::RefCell::new(vec![1, 2]))),
+        [Drain(vec![3].into())],
+    );
+    let mut json = Vec::new();
+    let mut json_serializer = serde_json::Serializer::new(&mut json);
+    let mut json_serializer = <dyn Serializer>::new(&mut json_serializer);
+    let mut cbor = Vec::new();
+    let mut cbor_serializer = serde_cbor::Serializer::new(&mut cbor);
+    let mut cbor_serializer = <dyn Serializer>::new(&mut cbor_serializer);
+    let mut tee = Tee::new([&mut json_serializer, &mut cbor_serializer]);
+    value.dyn_serialize(&mut tee).unwrap();
+    assert!(tee.errors().iter().all(Option::is_none));
+    assert_eq!(json, b"[[1,2],[[3]]]");
+    assert_eq!(
+        serde_cbor::from_slice::<(Vec<u8>, [Vec<u8>; 1])>(&cbor).unwrap(),
+        (vec![1, 2], [vec![3]])
+    );
+
+    // The tee fails once every branch has failed.
+    let mut first = <dyn Serializer>::new(serde_json::value::Serializer);
+    let mut second = <dyn Serializer>::new(serde_json::value::Serializer);
+    let mut tee = Tee::new([&mut first, &mut second]);
+    map.dyn_serialize(&mut tee).unwrap_err();
+    assert!(tee.errors().iter().all(Option::is_some));
+}
+
+#[test]
+#[cfg(any(feature = "std", feature = "alloc"))]
+fn test_validating() {
+    use serde::ser::{SerializeSeq, SerializeStruct};
+    use serde_dyn::ser::{CompoundKind, Validating, ValidationError, ValidationState};
+
+    struct Long;
+
+    impl serde::Serialize for Long {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut seq = serializer.serialize_seq(Some(1))?;
+            seq.serialize_element(&1)?;
+            seq.serialize_element(&2)?;
+            seq.end()
+        }
+    }
+
+    struct LongMap;
+
+    impl serde::Serialize for LongMap {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeMap as _;
+
+            let mut map = serializer.serialize_map(Some(1))?;
+            map.serialize_entry("a", &1)?;
+            map.serialize_entry("b", &2)?;
+            map.end()
+        }
+    }
+
+    struct Duplicate;
+
+    impl serde::Serialize for Duplicate {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut s = serializer.serialize_struct("Duplicate", 1)?;
+            s.serialize_field("a", &1)?;
+            s.skip_field("a")?;
+            s.end()
+        }
+    }
+
+    fn validate(value: &dyn Serialize) -> Result<String, String> {
+        let mut json = Vec::new();
+        serde_dyn::ser::with_dyn(&mut serde_json::Serializer::new(&mut json), |serializer| {
+            Validating::serialize(serializer, value).map_err(serde::ser::Error::custom)
+        })
+        .map(|()| String::from_utf8(json).unwrap())
+        .map_err(|error| error.to_string())
+    }
+
+    assert_eq!(validate(&vec![Some(1), None]).unwrap(), "[1,null]");
+    assert_eq!(
+        validate(&Long).unwrap_err(),
+        "expected a sequence of length 1, found length 2"
+    );
+    assert_eq!(
+        validate(&LongMap).unwrap_err(),
+        "expected a map of length 1, found length 2"
+    );
+    assert_eq!(validate(&Duplicate).unwrap_err(), "duplicate field `a`");
+    // The violations of the nested values are reported as well.
+    assert_eq!(
+        validate(&[Long]).unwrap_err(),
+        "expected a sequence of length 1, found length 2"
+    );
+
+    let mut json = serde_json::Serializer::new(Vec::new());
+    let mut json = <dyn Serializer>::new(&mut json);
+    let mut validating = Validating::new(&mut json);
+    let seq = validating.dyn_serialize_seq(None).unwrap();
+    seq.dyn_end().unwrap();
+    assert!(seq.dyn_serialize_element(&1).is_err());
+    assert_eq!(validating.state(), ValidationState::Done);
+    assert_eq!(
+        validating.finish(),
+        Err(ValidationError::UnexpectedCall {
+            call: "dyn_serialize_element",
+            expected: ValidationState::Seq,
+            found: ValidationState::Done,
+        })
+    );
+
+    // The violations of the nested values are recorded by the parent.
+    let mut json = serde_json::Serializer::new(Vec::new());
+    let mut json = <dyn Serializer>::new(&mut json);
+    let mut validating = Validating::new(&mut json);
+    let seq = validating.dyn_serialize_seq(Some(1)).unwrap();
+    assert!(seq.dyn_serialize_element(&Long).is_err());
+    let violation = ValidationError::LengthMismatch {
+        kind: CompoundKind::Seq,
+        expected: 1,
+        found: 2,
+    };
+    assert_eq!(validating.error(), Some(violation));
+    assert_eq!(validating.finish(), Err(violation));
+
+    let mut json = serde_json::Serializer::new(Vec::new());
+    let mut json = <dyn Serializer>::new(&mut json);
+    let mut validating = Validating::new(&mut json);
+    validating.dyn_serialize_map(Some(1)).unwrap();
+    assert_eq!(
+        validating.finish(),
+        Err(ValidationError::Unfinished {
+            found: ValidationState::MapKey
+        })
+    );
+
+    // The validator fixes what `serde_json` trusts the caller to do.
+    serde_dyn::testing::check_serializer(|script| {
+        let mut json = serde_json::Serializer::new(Vec::new());
+        script(&mut Validating::new(&mut <dyn Serializer>::new(&mut json)));
+    })
+    .assert_ok();
+}
+
+#[test]
+fn test_wrong_state() {
+    use serde_dyn::ser::{SerializerError, SerializerState};
+
+    let mut json = serde_json::Serializer::new(Vec::new());
+    let mut serializer = <dyn Serializer>::new(&mut json);
+    serializer.dyn_serialize_unit().unwrap();
+    let error = serializer.dyn_serialize_str("a").unwrap_err();
+    assert!(matches!(
+        error,
+        SerializerError::WrongState {
+            operation: "Serializer::dyn_serialize_str",
+            found: SerializerState::Ok,
+        }
+    ));
+    assert_eq!(
+        error.to_string(),
+        "`Serializer::dyn_serialize_str` is called, but the serializer has already produced `Ok`"
+    );
+    // The misuse leaves the output in place.
+    assert_eq!(serializer.state(), SerializerState::Ok);
+    assert!(serializer.dyn_serialize_u8(1).is_err());
+    assert!(matches!(
+        serializer,
+        serde_dyn::ser::InplaceSerializer::Ok(())
+    ));
+    assert_eq!(json.into_inner(), b"null");
+
+    let mut json = serde_json::Serializer::new(Vec::new());
+    let mut serializer = <dyn Serializer>::new(&mut json);
+    serializer.dyn_serialize_map(None).unwrap();
+    let error = serde_dyn::ser::SerializeSeq::dyn_end(&mut serializer).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "`SerializeSeq::dyn_end` is called, but the serializer is serializing a map"
+    );
+    let error = serde_dyn::ser::SerializeMap::dyn_serialize_value(&mut serializer, &1).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "`SerializeMap::dyn_serialize_value` is called, but the serializer is serializing a map"
+    );
+    serde_dyn::ser::SerializeMap::dyn_serialize_key(&mut serializer, &"a").unwrap();
+    let error = serde_dyn::ser::SerializeMap::dyn_serialize_key(&mut serializer, &"b").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "`SerializeMap::dyn_serialize_key` is called, but the serializer is serializing a map and \
+         expects a value"
+    );
+    assert_eq!(serializer.state(), SerializerState::SerializeMapValue);
+    serde_dyn::ser::SerializeMap::dyn_serialize_value(&mut serializer, &1).unwrap();
+    serde_dyn::ser::SerializeMap::dyn_end(&mut serializer).unwrap();
+    assert_eq!(json.into_inner(), br#"{"a":1}"#);
+
+    let error = serde_dyn::ser::with_dyn(serde_json::value::Serializer, |serializer| {
+        serializer.dyn_serialize_seq(None)?;
+        Ok(())
+    })
+    .unwrap_err();
+    // The message may be truncated by `heapless-errors`.
+    assert!(
+        error
+            .to_string()
+            .starts_with("`ser::with_dyn` is called, but the serializer is serializing")
+    );
+
+    let mut json = serde_json::Serializer::new(Vec::new());
+    let mut json_serializer = <dyn Serializer>::new(&mut json);
+    let mut tee = serde_dyn::ser::Tee::new([&mut json_serializer as &mut dyn Serializer]);
+    tee.dyn_serialize_unit().unwrap();
+    assert!(matches!(
+        tee.dyn_serialize_str("a"),
+        Err(SerializerError::WrongState {
+            operation: "Serializer::dyn_serialize_str",
+            found: SerializerState::Ok,
+        })
+    ));
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    {
+        let mut json = serde_json::Serializer::new(Vec::new());
+        let mut json_serializer = <dyn Serializer>::new(&mut json);
+        let mut validating = serde_dyn::ser::Validating::new(&mut json_serializer);
+        let map = validating.dyn_serialize_map(None).unwrap();
+        assert!(matches!(
+            map.dyn_serialize_value(&1),
+            Err(SerializerError::WrongState {
+                operation: "dyn_serialize_value",
+                found: SerializerState::SerializeMap,
+            })
+        ));
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_simple_serializer() {
+    use std::collections::BTreeMap;
+
+    use serde_dyn::ser::SimpleSerializer;
+    use serde_dyn::ser::{Compound, SerializeError, SerializerError, SerializerResult};
+
+    #[derive(Default)]
+    struct Events {
+        events: Vec<String>,
+        error: Option<SerializeError>,
+    }
+
+    impl Events {
+        fn push(&mut self, event: impl ToString) -> SerializerResult<()> {
+            self.events.push(event.to_string());
+            Ok(())
+        }
+    }
+
+    impl SimpleSerializer for Events {
+        fn serialize_bool(&mut self, v: bool) -> SerializerResult<()> {
+            self.push(v)
+        }
+
+        fn serialize_i128(&mut self, v: i128) -> SerializerResult<()> {
+            self.push(format!("i{v}"))
+        }
+
+        fn serialize_u128(&mut self, v: u128) -> SerializerResult<()> {
+            self.push(format!("u{v}"))
+        }
+
+        fn serialize_f64(&mut self, v: f64) -> SerializerResult<()> {
+            self.push(format!("f{v}"))
+        }
+
+        fn serialize_str(&mut self, v: &str) -> SerializerResult<()> {
+            self.push(format!("{v:?}"))
+        }
+
+        fn serialize_some(&mut self, value: &dyn Serialize) -> SerializerResult<()> {
+            self.push("Some")?;
+            value
+                .dyn_serialize(self)
+                .map_err(|error| self.nested_error(error))
+        }
+
+        fn serialize_unit(&mut self) -> SerializerResult<()> {
+            self.push("()")
+        }
+
+        fn begin(&mut self, compound: Compound) -> SerializerResult<()> {
+            self.push(format!("{compound:?}"))
+        }
+
+        fn key(&mut self) -> SerializerResult<()> {
+            self.push("key")
+        }
+
+        fn field(&mut self, key: &'static str) -> SerializerResult<()> {
+            self.push(key)
+        }
+
+        fn end(&mut self) -> SerializerResult<()> {
+            self.push("end")
+        }
+
+        fn nested_error(&mut self, error: SerializeError) -> SerializerError {
+            self.error.get_or_insert(error);
+            SerializerError::Error
+        }
+    }
+
+    fn events(value: &dyn Serialize) -> Vec<String> {
+        let mut serializer = Events::default();
+        value.dyn_serialize(&mut serializer).unwrap();
+        serializer.events
+    }
+
+    struct Fail;
+
+    impl serde::Serialize for Fail {
+        fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+            Err(serde::ser::Error::custom("failed"))
+        }
+    }
+
+    #[derive(serde::Serialize)]
+    struct Meters(u8);
+
+    #[derive(serde::Serialize)]
+    enum Mode {
+        Plain,
+        Proxy(char),
+    }
+
+    #[derive(serde::Serialize)]
+    struct Config {
+        mode: Mode,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        port: Option<u16>,
+        rate: Option<f32>,
+    }
+
+    assert_eq!(events(&-1_i8), ["i-1"]);
+    // `None`, `Some(())` and `()` are told apart, and newtype structs are transparent.
+    assert_eq!(events(&None::<()>), ["()"]);
+    assert_eq!(events(&Some(())), ["Some", "()"]);
+    assert_eq!(events(&Meters(2)), ["u2"]);
+    assert_eq!(events(&Mode::Plain), ["\"Plain\""]);
+    assert_eq!(
+        events(&Config {
+            mode: Mode::Proxy('x'),
+            port: None,
+            rate: Some(0.5),
+        }),
+        [
+            "Struct { name: \"Config\", len: 2 }",
+            "mode",
+            "NewtypeVariant { name: \"Mode\", variant_index: 1, variant: \"Proxy\" }",
+            "\"x\"",
+            "end",
+            "rate",
+            "Some",
+            "f0.5",
+            "end",
+        ]
+    );
+    assert_eq!(
+        events(&BTreeMap::from([(1_u8, None::<bool>)])),
+        ["Map { len: Some(1) }", "key", "u1", "()", "end"]
+    );
+
+    // The error of a nested value is passed to `nested_error`.
+    let mut serializer = Events::default();
+    assert!([Fail].dyn_serialize(&mut serializer).is_err());
+    assert_eq!(serializer.error.unwrap().to_string(), "failed");
+}
@@ -0,0 +1,98 @@
+use serde_dyn::testing::{self, Check};
+use serde_dyn::{Deserializer, Serializer};
+
+#[test]
+fn test_check_serializer() {
+    let report = testing::check_serializer(|script| {
+        let mut json = serde_json::Serializer::new(Vec::new());
+        script(&mut <dyn Serializer>::new(&mut json));
+    });
+    assert_eq!(report.checks(), Check::SERIALIZER);
+    report.assert_ok();
+    assert!(
+        report
+            .to_string()
+            .contains("ok: rejects two map keys in a row")
+    );
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_check_serializer_recorder() {
+    testing::check_serializer(|script| script(&mut serde_dyn::ser::Recorder::new())).assert_ok();
+}
+
+#[test]
+fn test_check_serializer_lenient() {
+    use serde_dyn::ser::{Compound, SerializerResult, SimpleSerializer};
+
+    /// Accepts the calls in any order.
+    struct Lenient;
+
+    impl SimpleSerializer for Lenient {
+        fn serialize_bool(&mut self, _: bool) -> SerializerResult<()> {
+            Ok(())
+        }
+
+        fn serialize_i128(&mut self, _: i128) -> SerializerResult<()> {
+            Ok(())
+        }
+
+        fn serialize_u128(&mut self, _: u128) -> SerializerResult<()> {
+            Ok(())
+        }
+
+        fn serialize_f64(&mut self, _: f64) -> SerializerResult<()> {
+            Ok(())
+        }
+
+        fn serialize_str(&mut self, _: &str) -> SerializerResult<()> {
+            Ok(())
+        }
+
+        fn serialize_unit(&mut self) -> SerializerResult<()> {
+            Ok(())
+        }
+
+        fn begin(&mut self, _: Compound) -> SerializerResult<()> {
+            Ok(())
+        }
+
+        fn end(&mut self) -> SerializerResult<()> {
+            Ok(())
+        }
+    }
+
+    let report = testing::check_serializer(|script| script(&mut Lenient));
+    assert!(!report.is_ok());
+    assert!(!report.is_violated(Check::ValidCalls));
+    assert!(!report.is_violated(Check::NestedErrors));
+    assert!(report.is_violated(Check::ReuseAfterValue));
+    assert!(report.is_violated(Check::KeyTwice));
+    assert!(
+        report
+            .to_string()
+            .contains("violated: rejects two map keys in a row")
+    );
+}
+
+#[test]
+fn test_check_deserializer() {
+    for input in ["null", "[1,{\"a\":true}]", "{\"A\":1}"] {
+        testing::check_deserializer(|script| {
+            let mut json = serde_json::Deserializer::from_str(input);
+            script(&mut <dyn Deserializer>::new(&mut json));
+        })
+        .assert_ok();
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_check_deserializer_tokens() {
+    use serde_dyn::de::Tokens;
+    use serde_dyn::token::Token;
+
+    let tokens = [Token::Seq { len: Some(1) }, Token::U8(1), Token::SeqEnd];
+    testing::check_deserializer(|script| script(&mut Tokens::new(&tokens))).assert_ok();
+}
@@ -0,0 +1,247 @@
+#![cfg(any(feature = "std", feature = "alloc"))]
+
+use std::collections::BTreeMap;
+
+use serde_dyn::Serialize;
+use serde_dyn::de::Tokens;
+use serde_dyn::ser::Recorder;
+use serde_dyn::token::{self, Token};
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Unit;
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Newtype(u8);
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Pair(u8, char);
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Config {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    port: Option<u16>,
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+enum Mode {
+    Plain,
+    Proxy(String),
+    Pair(u8, u8),
+    Limited { rate: u32 },
+}
+
+#[test]
+fn test_tokens() {
+    token::assert_tokens(&true, &[Token::Bool(true)]);
+    token::assert_tokens(&-1_i64, &[Token::I64(-1)]);
+    token::assert_tokens(&'x', &[Token::Char('x')]);
+    token::assert_tokens(&"hi".to_string(), &[Token::Str("hi".to_string())]);
+    token::assert_tokens(&None::<u8>, &[Token::None]);
+    token::assert_tokens(&Some(1_u8), &[Token::Some, Token::U8(1)]);
+    token::assert_tokens(&(), &[Token::Unit]);
+    token::assert_tokens(&Unit, &[Token::UnitStruct("Unit")]);
+    token::assert_tokens(
+        &Newtype(7),
+        &[Token::NewtypeStruct { name: "Newtype" }, Token::U8(7)],
+    );
+    token::assert_tokens(
+        &Pair(1, 'a'),
+        &[
+            Token::TupleStruct {
+                name: "Pair",
+                len: 2,
+            },
+            Token::U8(1),
+            Token::Char('a'),
+            Token::TupleStructEnd,
+        ],
+    );
+    token::assert_tokens(
+        &vec![1_u8, 2],
+        &[
+            Token::Seq { len: Some(2) },
+            Token::U8(1),
+            Token::U8(2),
+            Token::SeqEnd,
+        ],
+    );
+    token::assert_tokens(
+        &BTreeMap::from([("a".to_string(), 1_u8)]),
+        &[
+            Token::Map { len: Some(1) },
+            Token::Str("a".to_string()),
+            Token::U8(1),
+            Token::MapEnd,
+        ],
+    );
+}
+
+#[test]
+fn test_tokens_struct() {
+    token::assert_tokens(
+        &Config {
+            name: "a".to_string(),
+            port: Some(80),
+        },
+        &[
+            Token::Struct {
+                name: "Config",
+                len: 2,
+            },
+            Token::Field("name"),
+            Token::Str("a".to_string()),
+            Token::Field("port"),
+            Token::Some,
+            Token::U16(80),
+            Token::StructEnd,
+        ],
+    );
+    token::assert_tokens(
+        &Config {
+            name: "a".to_string(),
+            port: None,
+        },
+        &[
+            Token::Struct {
+                name: "Config",
+                len: 1,
+            },
+            Token::Field("name"),
+            Token::Str("a".to_string()),
+            Token::SkippedField("port"),
+            Token::StructEnd,
+        ],
+    );
+}
+
+#[test]
+fn test_tokens_enum() {
+    token::assert_tokens(
+        &Mode::Plain,
+        &[Token::UnitVariant {
+            name: "Mode",
+            variant_index: 0,
+            variant: "Plain",
+        }],
+    );
+    token::assert_tokens(
+        &Mode::Proxy("localhost".to_string()),
+        &[
+            Token::NewtypeVariant {
+                name: "Mode",
+                variant_index: 1,
+                variant: "Proxy",
+            },
+            Token::Str("localhost".to_string()),
+        ],
+    );
+    token::assert_tokens(
+        &Mode::Pair(1, 2),
+        &[
+            Token::TupleVariant {
+                name: "Mode",
+                variant_index: 2,
+                variant: "Pair",
+                len: 2,
+            },
+            Token::U8(1),
+            Token::U8(2),
+            Token::TupleVariantEnd,
+        ],
+    );
+    token::assert_tokens(
+        &Mode::Limited { rate: 3 },
+        &[
+            Token::StructVariant {
+                name: "Mode",
+                variant_index: 3,
+                variant: "Limited",
+                len: 1,
+            },
+            Token::Field("rate"),
+            Token::U32(3),
+            Token::StructVariantEnd,
+        ],
+    );
+}
+
+/// Serializes differently depending on `is_human_readable`.
+#[derive(Debug, PartialEq)]
+struct Flag(bool);
+
+impl serde::Serialize for Flag {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(if self.0 { "yes" } else { "no" })
+        } else {
+            serializer.serialize_bool(self.0)
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Flag {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let s = <&str>::deserialize(deserializer)?;
+            Ok(Flag(s == "yes"))
+        } else {
+            bool::deserialize(deserializer).map(Flag)
+        }
+    }
+}
+
+#[test]
+fn test_tokens_human_readable() {
+    token::assert_tokens(&Flag(true), &[Token::Str("yes".to_string())]);
+
+    let mut recorder = Recorder::new().with_human_readable(false);
+    Flag(true).dyn_serialize(&mut recorder).unwrap();
+    assert_eq!(recorder.tokens(), [Token::Bool(true)]);
+
+    let tokens = recorder.into_tokens();
+    let mut deserializer = Tokens::new(&tokens).with_human_readable(false);
+    let deserializer: &mut dyn serde_dyn::Deserializer = &mut deserializer;
+    assert_eq!(
+        <Flag as serde::Deserialize>::deserialize(deserializer).unwrap(),
+        Flag(true)
+    );
+}
+
+struct Fail;
+
+impl serde::Serialize for Fail {
+    fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom("failed"))
+    }
+}
+
+#[test]
+fn test_tokens_error() {
+    token::assert_ser_tokens_error(&[Fail], "failed");
+    token::assert_de_tokens_error::<u8>(
+        &[Token::Str("a".to_string())],
+        "invalid type: string \"a\", expected u8",
+    );
+    token::assert_de_tokens_error::<Vec<u8>>(
+        &[Token::Seq { len: None }],
+        "unexpected end of tokens",
+    );
+    token::assert_de_tokens_error::<(u8,)>(
+        &[
+            Token::Tuple { len: 2 },
+            Token::U8(1),
+            Token::U8(2),
+            Token::TupleEnd,
+        ],
+        "expected token TupleEnd, found U8(2)",
+    );
+    token::assert_de_tokens_error::<Mode>(
+        &[Token::UnitVariant {
+            name: "Mode",
+            variant_index: 1,
+            variant: "Proxy",
+        }],
+        "expected a newtype variant, found token UnitVariant { name: \"Mode\", variant_index: 1, variant: \"Proxy\" }",
+    );
+}
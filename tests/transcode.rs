@@ -0,0 +1,110 @@
+use serde_dyn::{Deserializer, Serializer};
+
+macro_rules! transcode {
+    ($str:literal) => {{
+        let mut deserializer = serde_json::Deserializer::from_str($str);
+        let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+
+        let mut buf = Vec::with_capacity(100);
+        let writer = std::io::Cursor::new(&mut buf);
+        let mut serializer = serde_json::Serializer::new(writer);
+        let mut serializer = <dyn Serializer>::new(&mut serializer);
+
+        serde_dyn::transcode(&mut deserializer, &mut serializer).map(|()| buf)
+    }};
+}
+
+#[test]
+fn test_transcode_scalar() {
+    assert_eq!(transcode!("true").unwrap(), b"true");
+    assert_eq!(transcode!("-128").unwrap(), b"-128");
+    assert_eq!(transcode!("3.5").unwrap(), b"3.5");
+    assert_eq!(transcode!("\"♥️\"").unwrap(), "\"♥️\"".as_bytes());
+    assert_eq!(transcode!("null").unwrap(), b"null");
+}
+
+#[test]
+fn test_transcode_compound() {
+    let json = r#"{"a":[1,{"b":null},[]],"c":{},"d":"e"}"#;
+    let buf = transcode!(r#" { "a" : [ 1, { "b" : null }, [ ] ], "c": {}, "d": "e" } "#).unwrap();
+    assert_eq!(buf, json.as_bytes());
+}
+
+#[test]
+fn test_transcode_error() {
+    transcode!("[1,2,").unwrap_err();
+    transcode!(r#"{"a":}"#).unwrap_err();
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_transcode_error_side() {
+    use serde_dyn::ser::{InplaceSerializer, SerializerError};
+
+    // The input is invalid, so the serializer has nothing to do with it.
+    let error = transcode!("[1,[2,").unwrap_err();
+    assert!(error.downcast_ref::<SerializerError>().is_none());
+
+    // The output doesn't fit in the buffer.
+    let mut deserializer = serde_json::Deserializer::from_str("[1,[2,3]]");
+    let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+    let mut buf = [0; 4];
+    let mut serializer = serde_json::Serializer::new(&mut buf[..]);
+    let mut serializer = <dyn Serializer>::new(&mut serializer);
+
+    let error = serde_dyn::transcode(&mut deserializer, &mut serializer).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<SerializerError>(),
+        Some(SerializerError::Error)
+    ));
+    let InplaceSerializer::Error(error) = serializer else {
+        unreachable!()
+    };
+    assert!(error.is_io());
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_transcode_cbor() {
+    use serde_dyn::ser::InplaceSerializer;
+    use serde_dyn::value::ValueSerializer;
+
+    let data = (1_u8, -(1_i128 << 64), "two".to_string(), [3.5_f32]);
+    let cbor = serde_cbor::to_vec(&data).unwrap();
+
+    let mut deserializer = serde_cbor::Deserializer::from_slice(&cbor);
+    let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+    let mut serializer = <dyn Serializer>::new(ValueSerializer);
+    serde_dyn::transcode(&mut deserializer, &mut serializer).unwrap();
+
+    let InplaceSerializer::Ok(value) = serializer else {
+        unreachable!()
+    };
+    assert_eq!(serde_cbor::to_vec(&value).unwrap(), cbor);
+    assert_eq!(serde_dyn::value::from_value(value).ok(), Some(data));
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_transcode_enum() {
+    use serde_dyn::de::InplaceDeserializer;
+    use serde_dyn::value::Value;
+
+    let value = Value::UnitVariant {
+        name: "E",
+        variant_index: 0,
+        variant: "A",
+    };
+    let mut deserializer = <dyn Deserializer>::new(value);
+    let mut serializer = serde_json::Serializer::new(Vec::new());
+    let mut serializer = <dyn Serializer>::new(&mut serializer);
+
+    serde_dyn::transcode(&mut deserializer, &mut serializer).unwrap_err();
+    let InplaceDeserializer::Error(error) = deserializer else {
+        unreachable!()
+    };
+    assert_eq!(
+        error.to_string(),
+        "invalid type: enum, expected any value except enum"
+    );
+}
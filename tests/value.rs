@@ -0,0 +1,314 @@
+#![cfg(any(feature = "std", feature = "alloc"))]
+
+use serde_dyn::ser::InplaceSerializer;
+use serde_dyn::value::{self, Value, ValueSerializer};
+use serde_dyn::{Deserializer, Serialize, Serializer};
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+enum Enum {
+    Unit,
+    Newtype(i128),
+    Tuple(u128, Option<char>),
+    Struct {
+        #[serde(with = "bytes")]
+        bytes: Vec<u8>,
+        unit: (),
+    },
+}
+
+mod bytes {
+    pub fn serialize<S: serde::Serializer>(v: &[u8], s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_bytes(v)
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+        struct BytesVisitor;
+
+        impl serde::de::Visitor<'_> for BytesVisitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("bytes")
+            }
+
+            fn visit_byte_buf<E: serde::de::Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+                Ok(v)
+            }
+        }
+
+        d.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+fn dyn_to_value(value: &dyn Serialize) -> Value {
+    let mut serializer = <dyn Serializer>::new(ValueSerializer);
+    value.dyn_serialize(&mut serializer).unwrap();
+    match serializer {
+        InplaceSerializer::Ok(value) => value,
+        _ => unreachable!(),
+    }
+}
+
+fn dyn_from_value<T: serde::de::DeserializeOwned>(value: Value) -> T {
+    let mut deserializer = <dyn Deserializer>::new(value);
+    let deserializer: &mut dyn Deserializer = &mut deserializer;
+    T::deserialize(deserializer).unwrap()
+}
+
+#[test]
+fn test_value_enum() {
+    let values = vec![
+        Enum::Unit,
+        Enum::Newtype(i128::MIN),
+        Enum::Tuple(u128::MAX, Some('A')),
+        Enum::Struct {
+            bytes: vec![0xde, 0xad],
+            unit: (),
+        },
+    ];
+
+    let value = dyn_to_value(&values);
+    assert_eq!(
+        value,
+        Value::Seq(vec![
+            Value::UnitVariant {
+                name: "Enum",
+                variant_index: 0,
+                variant: "Unit",
+            },
+            Value::NewtypeVariant {
+                name: "Enum",
+                variant_index: 1,
+                variant: "Newtype",
+                value: Box::new(Value::I128(i128::MIN)),
+            },
+            Value::TupleVariant {
+                name: "Enum",
+                variant_index: 2,
+                variant: "Tuple",
+                fields: vec![
+                    Value::U128(u128::MAX),
+                    Value::Some(Box::new(Value::Char('A'))),
+                ],
+            },
+            Value::StructVariant {
+                name: "Enum",
+                variant_index: 3,
+                variant: "Struct",
+                fields: vec![
+                    ("bytes", Value::Bytes(vec![0xde, 0xad])),
+                    ("unit", Value::Unit),
+                ],
+            },
+        ])
+    );
+
+    let deserialized: Vec<Enum> = dyn_from_value(value);
+    assert_eq!(deserialized, values);
+}
+
+#[test]
+fn test_value_struct() {
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Unit;
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Newtype(u8);
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Struct {
+        unit: Unit,
+        newtype: Newtype,
+        map: std::collections::BTreeMap<String, bool>,
+    }
+
+    let data = Struct {
+        unit: Unit,
+        newtype: Newtype(7),
+        map: [("x".to_string(), true)].into_iter().collect(),
+    };
+
+    let value = value::to_value(&data).unwrap();
+    assert_eq!(
+        value,
+        Value::Struct {
+            name: "Struct",
+            fields: vec![
+                ("unit", Value::UnitStruct("Unit")),
+                (
+                    "newtype",
+                    Value::NewtypeStruct {
+                        name: "Newtype",
+                        value: Box::new(Value::U8(7)),
+                    },
+                ),
+                (
+                    "map",
+                    Value::Map(vec![(Value::String("x".to_string()), Value::Bool(true))]),
+                ),
+            ],
+        }
+    );
+
+    let deserialized: Struct = value::from_value(value).unwrap();
+    assert_eq!(deserialized, data);
+}
+
+#[test]
+fn test_value_replay() {
+    let value = value::to_value(&(1_u8, "two", [3.0_f32])).unwrap();
+
+    let json = serde_json::to_string(&value).unwrap();
+    assert_eq!(json, r#"[1,"two",[3.0]]"#);
+
+    let mut cbor = Vec::new();
+    let writer = serde_cbor::ser::IoWrite::new(std::io::Cursor::new(&mut cbor));
+    let mut serializer = serde_cbor::Serializer::new(writer);
+    let mut serializer = <dyn Serializer>::new(&mut serializer);
+    value.dyn_serialize(&mut serializer).unwrap();
+    let data: (u8, String, [f32; 1]) = serde_cbor::from_slice(&cbor).unwrap();
+    assert_eq!(data, (1, "two".to_string(), [3.0]));
+}
+
+#[test]
+fn test_value_deserialize() {
+    let value: Value = serde_json::from_str(r#"{"a":[1,-2,null,"x"]}"#).unwrap();
+    assert_eq!(
+        value,
+        Value::Map(vec![(
+            Value::String("a".to_string()),
+            Value::Seq(vec![
+                Value::U64(1),
+                Value::I64(-2),
+                Value::Unit,
+                Value::String("x".to_string()),
+            ]),
+        )])
+    );
+
+    // Bytes and integers beyond 64 bits survive a binary format.
+    #[derive(serde::Serialize)]
+    struct Bytes(#[serde(with = "bytes")] Vec<u8>);
+
+    let data = (Bytes(vec![0, 255]), -(1_i128 << 64));
+    let cbor = serde_cbor::to_vec(&data).unwrap();
+    let value: Value = serde_cbor::from_slice(&cbor).unwrap();
+    assert_eq!(
+        value,
+        Value::Seq(vec![Value::Bytes(vec![0, 255]), Value::I128(-(1 << 64))])
+    );
+
+    // The same through a dynamic deserializer.
+    let mut deserializer = serde_cbor::Deserializer::from_slice(&cbor);
+    let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+    let deserializer: &mut dyn Deserializer = &mut deserializer;
+    let dyn_value = <Value as serde::Deserialize>::deserialize(deserializer).unwrap();
+    assert_eq!(dyn_value, value);
+}
+
+#[test]
+fn test_value_error() {
+    let error = value::from_value::<u8>(Value::String("x".to_string())).unwrap_err();
+    assert_eq!(error.to_string(), "invalid type: string \"x\", expected u8");
+
+    let error =
+        value::from_value::<(u8,)>(Value::Seq(vec![Value::U8(1), Value::U8(2)])).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid length 2, expected 1 element in sequence"
+    );
+}
+
+#[test]
+fn test_value_deserialize_value() {
+    #[derive(serde::Serialize)]
+    struct Unit;
+
+    #[derive(serde::Serialize)]
+    struct Newtype(u8);
+
+    #[derive(serde::Serialize)]
+    struct Tuple(u8, Unit);
+
+    #[derive(serde::Serialize)]
+    struct Struct {
+        a: Option<()>,
+        b: (),
+    }
+
+    #[derive(serde::Serialize)]
+    enum Enum {
+        Unit,
+        Newtype(Newtype),
+        Tuple(u8, Tuple),
+        Struct { s: Struct },
+    }
+
+    let data = (
+        [Enum::Unit, Enum::Newtype(Newtype(1))],
+        Enum::Tuple(2, Tuple(3, Unit)),
+        Enum::Struct {
+            s: Struct { a: Some(()), b: () },
+        },
+        None::<u8>,
+    );
+    let value = value::to_value(&data).unwrap();
+
+    // The names of structs and variants survive, and `()` stays apart from `None`.
+    assert_eq!(value::from_value::<Value>(value.clone()).unwrap(), value);
+    assert_eq!(
+        value::from_value::<Option<()>>(Value::Unit).unwrap(),
+        None,
+        "`Option` reads a unit as `None`"
+    );
+
+    // The same through a dynamic deserializer.
+    let mut deserializer = <dyn Deserializer>::new(value.clone());
+    let deserializer: &mut dyn Deserializer = &mut deserializer;
+    let dyn_value = <Value as serde::Deserialize>::deserialize(deserializer).unwrap();
+    assert_eq!(dyn_value, value);
+}
+
+#[test]
+fn test_value_error_kind() {
+    use serde::de::Unexpected;
+    use serde_dyn::de::DeserializeErrorKind;
+
+    #[derive(Debug, serde::Deserialize)]
+    #[serde(deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct Config {
+        port: u16,
+    }
+
+    let error = value::from_value::<u8>(Value::String("x".to_string())).unwrap_err();
+    assert_eq!(
+        error.kind(),
+        DeserializeErrorKind::InvalidType(Unexpected::Str("x"), "u8")
+    );
+
+    let value = Value::Map(vec![(Value::String("prot".to_string()), Value::U16(80))]);
+    let error = value::from_value::<Config>(value).unwrap_err();
+    assert_eq!(
+        error.kind(),
+        DeserializeErrorKind::UnknownField("prot", &["port"])
+    );
+    assert_eq!(error.to_string(), "unknown field `prot`, expected `port`");
+
+    // The kind survives the conversion out of the dynamic world.
+    let error = serde_dyn::de::with_dyn(Value::Map(vec![]), |deserializer| {
+        <Config as serde::Deserialize>::deserialize(deserializer)
+    })
+    .unwrap_err();
+    assert_eq!(error.kind(), DeserializeErrorKind::MissingField("port"));
+    assert_eq!(error.to_string(), "missing field `port`");
+
+    let error = serde_dyn::de::with_dyn(Value::Seq(vec![Value::U8(1)]), |deserializer| {
+        <(u8, u8) as serde::Deserialize>::deserialize(deserializer)
+    })
+    .unwrap_err();
+    assert_eq!(
+        error.kind(),
+        DeserializeErrorKind::InvalidLength(1, "a tuple of size 2")
+    );
+}